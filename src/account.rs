@@ -16,57 +16,215 @@
  * along with this program.  If not, see <https://www.gnu.org/licenses/>.
  */
 
-/**
+/*
  * Most of the token code comes from https://github.com/KernelFreeze/minecraft-msa-auth
  */
 
-use anyhow::Result;
+use anyhow::{bail, Result};
 use chrono::{DateTime, Duration, Utc};
-use reqwest::Client;
+use futures_util::future::{AbortHandle, Abortable};
 use serde::Deserialize;
 use serde_json::json;
 use std::collections::HashMap;
 use oauth2::{
-    AuthUrl, ClientId, DeviceAuthorizationUrl, RefreshToken, Scope, TokenResponse, TokenUrl,
-    StandardDeviceAuthorizationResponse, StandardTokenResponse, EmptyExtraTokenFields,
-    basic::BasicClient, basic::BasicTokenType, reqwest::async_http_client
+    AuthUrl, ClientId, DeviceAuthorizationUrl, DeviceCodeErrorResponseType, HttpRequest, HttpResponse,
+    RefreshToken, RequestTokenError, Scope, TokenResponse, TokenUrl, StandardDeviceAuthorizationResponse,
+    StandardTokenResponse, EmptyExtraTokenFields, basic::BasicClient, basic::BasicTokenType
 };
 
-use crate::{env, Error};
-use crate::json::{AccountManifest, MicrosoftToken, MinecraftToken, MinecraftProfile};
+use crate::{asset_client::{http_client, http_client_builder}, credential_file::FileCredential, env, Error, hash, LoginHandler};
+use crate::json::{AccountIndex, AccountManifest, MicrosoftToken, MinecraftToken, MinecraftProfile};
+
+/// Name given to the account stored before multiple accounts were supported
+const LEGACY_ACCOUNT_NAME: &str = "default";
 
 pub struct Account {
+    id: String,
+    name: String,
     manifest: AccountManifest
 }
 
-pub type LoginCallback = fn(url: &str, code: &str);
+/// Stops waiting for the sign in code to be entered, given to
+/// `LoginHandler::show_code` and usable from another task or thread
+#[derive(Clone)]
+pub struct LoginCancel(AbortHandle);
+
+impl LoginCancel {
+    pub fn cancel(&self) {
+        self.0.abort();
+    }
+}
+
+/// Stored account details, see `Account::status`
+pub struct AccountStatus {
+    /// Minecraft profile name
+    pub name: String,
+    /// Minecraft profile UUID, `None` until the next launch for accounts
+    /// stored by older versions
+    pub uuid: Option<String>,
+    pub active: bool,
+    pub msa_token_expires: DateTime<Utc>,
+    pub mc_token_expires: DateTime<Utc>,
+    /// Tokens have expired, the next launch refreshes them
+    pub needs_refresh: bool
+}
+
+/// Player credentials passed to the game at launch
+pub struct Session {
+    pub access_token: String,
+    pub name: String,
+    pub uuid: String,
+    pub user_type: &'static str
+}
 
 impl Account {
-    fn keyring_entry() -> keyring::Result<keyring::Entry> {
-        keyring::Entry::new(env::get_package_name(), &env::get_user_name())
+    /// Entry of the system keyring, or of `account.json` when the
+    /// `credential_store` config uses a file
+    fn entry(user: &str) -> keyring::Result<keyring::Entry> {
+        match FileCredential::from_env(user) {
+            Ok(Some(credential)) => Ok(keyring::Entry::new_with_credential(Box::new(credential))),
+            Ok(None) => keyring::Entry::new(env::get_package_name(), user),
+            Err(e) => Err(keyring::Error::PlatformFailure(e.into()))
+        }
+    }
+
+    fn keyring_entry(key: &str) -> keyring::Result<keyring::Entry> {
+        Self::entry(&format!("{user}:{key}", user = env::get_user_name()))
+    }
+
+    // each account is a separate entry, windows limits the size of a credential
+    fn account_entry(id: &str) -> keyring::Result<keyring::Entry> {
+        Self::keyring_entry(&format!("account:{id}"))
+    }
+
+    fn index_entry() -> keyring::Result<keyring::Entry> {
+        Self::keyring_entry("accounts")
+    }
+
+    fn legacy_entry() -> keyring::Result<keyring::Entry> {
+        Self::entry(&env::get_user_name())
+    }
+
+    fn load_index() -> Result<AccountIndex> {
+        match Self::index_entry()?.get_password() {
+            Ok(json) => Ok(serde_json::from_str(&json)?),
+            Err(keyring::Error::NoEntry) => Self::migrate_legacy(),
+            Err(e) => Err(e.into())
+        }
+    }
+
+    fn write_index(index: &AccountIndex) -> Result<()> {
+        let json = serde_json::to_string(index)?;
+        Self::index_entry()?.set_password(&json)?;
+        Ok(())
+    }
+
+    /// Move the single account stored by older versions to a named account
+    fn migrate_legacy() -> Result<AccountIndex> {
+        let mut index = AccountIndex::default();
+
+        if let Ok(json) = Self::legacy_entry()?.get_password() {
+            Self::account_entry(LEGACY_ACCOUNT_NAME)?.set_password(&json)?;
+
+            index.active = Some(LEGACY_ACCOUNT_NAME.to_string());
+            index.insert(LEGACY_ACCOUNT_NAME, LEGACY_ACCOUNT_NAME);
+            Self::write_index(&index)?;
+
+            Self::legacy_entry()?.delete_password()?;
+        }
+
+        Ok(index)
     }
 
     fn write_manifest(&self) -> Result<()> {
         let json = serde_json::to_string(&self.manifest)?;
-        Self::keyring_entry()?.set_password(&json)?;
+        Self::account_entry(&self.id)?.set_password(&json)?;
         Ok(())
     }
 
-    pub fn load() -> Result<Self> {
-        let json = Self::keyring_entry()?.get_password()
+    /// Id of the stored account with profile name or id `name_or_id`
+    fn find_id(index: &AccountIndex, name_or_id: &str) -> Result<String> {
+        match index.find(name_or_id) {
+            Some(entry) => Ok(entry.id.clone()),
+            None => bail!(Error::AccountNotFound(name_or_id.to_string()))
+        }
+    }
+
+    /// Load account by profile name or id, or the active account when
+    /// `name_or_id` is `None`
+    pub fn load(name_or_id: Option<&str>) -> Result<Self> {
+        let index = Self::load_index()?;
+
+        let id = match name_or_id {
+            Some(name_or_id) => Self::find_id(&index, name_or_id)?,
+            None => index.active.clone().ok_or(Error::CredentialNotFound)?
+        };
+
+        let entry = index.find(&id).ok_or(Error::CredentialNotFound)?;
+
+        let json = Self::account_entry(&entry.id)?.get_password()
             .map_err(|_| Error::CredentialNotFound)?;
 
         Ok(Account {
+            id: entry.id.clone(),
+            name: entry.name.clone(),
             manifest: serde_json::from_str::<AccountManifest>(&json)?
         })
     }
 
+    /// Profile names of all stored accounts
+    pub fn list() -> Result<Vec<String>> {
+        Ok(Self::load_index()?.accounts.into_iter().map(|a| a.name).collect())
+    }
+
+    /// Profile name of the account used when launching without a pinned account
+    pub fn active() -> Result<Option<String>> {
+        let index = Self::load_index()?;
+
+        Ok(index.active.as_deref()
+            .and_then(|id| index.find(id))
+            .map(|a| a.name.clone()))
+    }
+
+    /// Make the account with profile name or id `name_or_id` active
+    pub fn switch(name_or_id: &str) -> Result<()> {
+        let mut index = Self::load_index()?;
+
+        index.active = Some(Self::find_id(&index, name_or_id)?);
+        Self::write_index(&index)
+    }
+
+    /// Delete stored account, the first remaining account becomes active
+    /// when removing the active account
+    pub fn remove(name_or_id: &str) -> Result<()> {
+        let mut index = Self::load_index()?;
+
+        let id = Self::find_id(&index, name_or_id)?;
+
+        delete_entry(Self::account_entry(&id)?)?;
+
+        index.accounts.retain(|a| a.id != id);
+        if index.active.as_deref() == Some(&id) {
+            index.active = index.accounts.first().map(|a| a.id.clone());
+        }
+
+        Self::write_index(&index)
+    }
+
+    /// Delete all stored accounts
     pub fn clear() -> Result<()> {
-        Ok(Self::keyring_entry()?.delete_password()?)
+        let index = Self::load_index()?;
+
+        for entry in &index.accounts {
+            delete_entry(Self::account_entry(&entry.id)?)?;
+        }
+
+        Ok(delete_entry(Self::index_entry()?)?)
     }
 
-    pub async fn load_with_tokens() -> Result<Self> {
-        let mut account = Self::load()?;
+    /// Load account by name, or the active account, refreshing expired tokens
+    pub async fn load_with_tokens(name: Option<&str>) -> Result<Self> {
+        let mut account = Self::load(name)?;
 
         if account.manifest.msa_token.is_expired() {
             account.manifest.msa_token =
@@ -85,21 +243,74 @@ impl Account {
         Ok(account)
     }
 
-    pub async fn login(callback: LoginCallback) -> Result<Account> {
-        let msa_token = access_token(callback).await?;
+    /// Sign in and store the account under the minecraft profile id,
+    /// replacing the stored account of the same profile, and make it active
+    pub async fn login(handler: &mut dyn LoginHandler) -> Result<Account> {
+        let msa_token = access_token(handler).await?;
         let mc_token = login_token(&msa_token.access_token).await?;
 
+        let profile = get_profile(&mc_token.access_token).await?;
+
+        let mut index = Self::load_index()?;
+
+        // an account stored by an older version is keyed by the profile name
+        if index.accounts.iter().any(|a| a.id == profile.name) {
+            delete_entry(Self::account_entry(&profile.name)?)?;
+            index.rekey(&profile.name, &profile.id);
+        }
+
         let account = Account {
+            id: profile.id.clone(),
+            name: profile.name,
             manifest: AccountManifest {
-                msa_token, mc_token
+                msa_token, mc_token, profile_id: Some(profile.id)
             }
         };
 
         account.write_manifest()?;
 
+        index.insert(&account.id, &account.name);
+        index.active = Some(account.id.clone());
+        Self::write_index(&index)?;
+
         Ok(account)
     }
 
+    /// Minecraft profile UUID the account is stored under, or the profile name
+    /// for accounts stored by older versions until their next launch
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Store the account under the profile id, and update the profile name
+    /// after the profile was renamed
+    fn update_profile(&mut self, profile: &MinecraftProfile) -> Result<()> {
+        if self.id == profile.id && self.name == profile.name {
+            return Ok(());
+        }
+
+        let mut index = Self::load_index()?;
+
+        // accounts stored by older versions are keyed by name, without the profile id
+        if self.id != profile.id {
+            let old_id = std::mem::replace(&mut self.id, profile.id.clone());
+            self.manifest.profile_id = Some(profile.id.clone());
+            self.write_manifest()?;
+
+            delete_entry(Self::account_entry(&old_id)?)?;
+            index.rekey(&old_id, &profile.id);
+        }
+
+        self.name = profile.name.clone();
+        index.insert(&self.id, &self.name);
+
+        Self::write_index(&index)
+    }
+
     pub fn access_token(&self) -> &String {
         &self.manifest.mc_token.access_token
     }
@@ -108,6 +319,61 @@ impl Account {
         get_profile(&self.manifest.mc_token.access_token).await
     }
 
+    /// Session for the account profile, tokens should be refreshed first
+    /// with `load_with_tokens`
+    pub async fn session(&mut self) -> Result<Session> {
+        if !has_game_entitlement(&self.manifest.mc_token.access_token).await? {
+            bail!(Error::GameNotOwned);
+        }
+
+        let profile = self.fetch_profile().await?;
+
+        self.update_profile(&profile)?;
+
+        Ok(Session {
+            access_token: self.access_token().clone(),
+            name: profile.name,
+            uuid: profile.id,
+            user_type: "msa"
+        })
+    }
+
+    /// Session without Microsoft credentials, with a placeholder token and
+    /// the UUID an offline-mode server would assign to `username`. Only works
+    /// for single player, LAN and offline-mode servers.
+    pub fn offline(username: &str) -> Result<Session> {
+        let is_valid = (3..=16).contains(&username.len())
+            && username.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+
+        if !is_valid {
+            bail!(Error::InvalidOfflineUsername(username.to_string()));
+        }
+
+        Ok(Session {
+            // the game only requires a non-empty token
+            access_token: String::from("0"),
+            name: username.to_string(),
+            uuid: hash::offline_player_uuid(username),
+            user_type: "legacy"
+        })
+    }
+
+    /// Stored details of the account, without refreshing tokens or making
+    /// any requests
+    pub fn status(&self) -> Result<AccountStatus> {
+        let msa_token = &self.manifest.msa_token;
+        let mc_token = &self.manifest.mc_token;
+
+        Ok(AccountStatus {
+            name: self.name.clone(),
+            uuid: self.manifest.profile_id.clone(),
+            active: Self::load_index()?.active.as_deref() == Some(&self.id),
+            msa_token_expires: msa_token.expires,
+            mc_token_expires: mc_token.expires,
+            needs_refresh: msa_token.is_expired() || mc_token.is_expired()
+        })
+    }
+
     pub fn msa_token_expires(&self) -> &DateTime<Utc> {
         &self.manifest.msa_token.expires
     }
@@ -129,6 +395,14 @@ impl MicrosoftToken {
     }
 }
 
+/// Delete keyring entry, an entry that was never stored is already deleted
+fn delete_entry(entry: keyring::Entry) -> keyring::Result<()> {
+    match entry.delete_password() {
+        Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+        Err(e) => Err(e)
+    }
+}
+
 fn oauth_client() -> Result<BasicClient> {
     let auth_url = AuthUrl::new(
         "https://login.microsoftonline.com/consumers/oauth2/v2.0/authorize".to_string()
@@ -151,27 +425,81 @@ fn oauth_client() -> Result<BasicClient> {
     .set_device_authorization_url(device_auth_url))
 }
 
-pub async fn access_token(callback: LoginCallback) -> Result<MicrosoftToken> {
+/// Sign in with a device code, a new code is requested when the handler
+/// chooses to retry after the code expires
+pub async fn access_token(handler: &mut dyn LoginHandler) -> Result<MicrosoftToken> {
     let oauth2_client = oauth_client()?;
 
-    let details: StandardDeviceAuthorizationResponse = oauth2_client
-        .exchange_device_code()?
-        .add_scope(Scope::new("XboxLive.signin".to_string()))
-        .add_scope(Scope::new("offline_access".to_string()))
-        .request_async(async_http_client)
-        .await?;
+    loop {
+        let details: StandardDeviceAuthorizationResponse = oauth2_client
+            .exchange_device_code()?
+            .add_scope(Scope::new("XboxLive.signin".to_string()))
+            .add_scope(Scope::new("offline_access".to_string()))
+            .request_async(async_http_client)
+            .await?;
+
+        let (abort_handle, abort_registration) = AbortHandle::new_pair();
+
+        handler.show_code(
+            details.verification_uri(),
+            details.user_code().secret(),
+            LoginCancel(abort_handle)
+        );
+
+        let request = oauth2_client
+            .exchange_device_access_token(&details)
+            .request_async(async_http_client, sleep, Some(env::get_login_timeout()));
+
+        let result = match Abortable::new(request, abort_registration).await {
+            Ok(result) => result,
+            Err(_) => bail!(Error::LoginCancelled)
+        };
 
-    callback(
-        details.verification_uri(),
-        details.user_code().secret()
-    );
+        match result {
+            Ok(msa_token_result) => return MicrosoftToken::from_token_response(msa_token_result),
+            // the login timeout is reported the same as the code expiring
+            Err(RequestTokenError::ServerResponse(e))
+                if *e.error() == DeviceCodeErrorResponseType::ExpiredToken =>
+            {
+                if !handler.retry_expired()? {
+                    bail!(Error::LoginExpired);
+                }
+            },
+            Err(e) => return Err(e.into())
+        }
+    }
+}
 
-    let msa_token_result = oauth2_client
-        .exchange_device_access_token(&details)
-        .request_async(async_http_client, sleep, None)
-        .await?;
+/// Same as `oauth2::reqwest::async_http_client` but with timeouts applied
+async fn async_http_client(
+    request: HttpRequest
+) -> Result<HttpResponse, oauth2::reqwest::Error<reqwest::Error>> {
+    use oauth2::reqwest::Error as OAuthError;
+
+    let client = http_client_builder()
+        .map_err(|e| OAuthError::Other(format!("{e:#}")))?
+        .timeout(env::get_http_request_timeout())
+        // following redirects opens the client up to SSRF vulnerabilities
+        .redirect(reqwest::redirect::Policy::none())
+        .build()
+        .map_err(OAuthError::Reqwest)?;
+
+    let mut request_builder = client
+        .request(request.method, request.url.as_str())
+        .body(request.body);
+
+    for (name, value) in &request.headers {
+        request_builder = request_builder.header(name.as_str(), value.as_bytes());
+    }
 
-    Ok(MicrosoftToken::from_token_response(msa_token_result)?)
+    let response = request_builder.send().await
+        .map_err(OAuthError::Reqwest)?;
+
+    Ok(HttpResponse {
+        status_code: response.status(),
+        headers: response.headers().to_owned(),
+        body: response.bytes().await.map_err(OAuthError::Reqwest)?.to_vec()
+    })
 }
 
 async fn sleep(dur: std::time::Duration) {
@@ -186,11 +514,11 @@ async fn refresh_token(refresh_token: &str) -> Result<MicrosoftToken> {
         .request_async(async_http_client)
         .await?;
 
-    Ok(MicrosoftToken::from_token_response(msa_token_result)?)
+    MicrosoftToken::from_token_response(msa_token_result)
 }
 
 async fn login_token(msa_access_token: &str) -> Result<MinecraftToken> {
-    let client = Client::new();
+    let client = http_client()?;
 
     let xbox_authenticate_json = json!({
         "Properties": {
@@ -205,6 +533,7 @@ async fn login_token(msa_access_token: &str) -> Result<MinecraftToken> {
     let xbox_authenticate_response: XboxAuthResponse = client
         .post("https://user.auth.xboxlive.com/user/authenticate")
         .json(&xbox_authenticate_json)
+        .timeout(env::get_http_request_timeout())
         .send().await?
         .error_for_status()?
         .json().await?;
@@ -218,10 +547,19 @@ async fn login_token(msa_access_token: &str) -> Result<MinecraftToken> {
         "TokenType": "JWT"
     });
 
-    let xbox_authorize_response: XboxAuthResponse = client
+    let xbox_authorize_response = client
         .post("https://xsts.auth.xboxlive.com/xsts/authorize")
         .json(&xbox_authorize_json)
-        .send().await?
+        .timeout(env::get_http_request_timeout())
+        .send().await?;
+
+    // account problems are reported as an XErr code in the response body
+    if xbox_authorize_response.status() == reqwest::StatusCode::UNAUTHORIZED {
+        let error: XboxErrorResponse = xbox_authorize_response.json().await?;
+        bail!(xbox_error(error.xerr));
+    }
+
+    let xbox_authorize_response: XboxAuthResponse = xbox_authorize_response
         .error_for_status()?
         .json().await?;
 
@@ -235,6 +573,7 @@ async fn login_token(msa_access_token: &str) -> Result<MinecraftToken> {
     let mc_login_response: MinecraftAuthResponse = client
         .post("https://api.minecraftservices.com/authentication/login_with_xbox")
         .json(&mc_login_json)
+        .timeout(env::get_http_request_timeout())
         .send().await?
         .error_for_status()?
         .json().await?;
@@ -245,26 +584,48 @@ async fn login_token(msa_access_token: &str) -> Result<MinecraftToken> {
     })
 }
 
+/// Error with guidance for the XSTS authorization `XErr` code
+fn xbox_error(xerr: u64) -> Error {
+    match xerr {
+        2148916227 => Error::XboxAccountBanned,
+        2148916233 => Error::XboxProfileNotFound,
+        2148916235 => Error::XboxRegionUnavailable,
+        2148916236 | 2148916237 => Error::XboxAdultVerification,
+        2148916238 => Error::XboxChildAccount,
+        _ => Error::XboxAuthorizationFailed(xerr)
+    }
+}
+
+/// True when the account owns the game, either purchased or through Game Pass
+async fn has_game_entitlement(mc_access_token: &str) -> Result<bool> {
+    let client = http_client()?;
+
+    let entitlements: EntitlementsResponse = client
+        .get("https://api.minecraftservices.com/entitlements/mcstore")
+        .bearer_auth(mc_access_token)
+        .timeout(env::get_http_request_timeout())
+        .send().await?
+        .error_for_status()?
+        .json().await?;
+
+    Ok(entitlements.items.iter()
+        .any(|e| e.name == "game_minecraft" || e.name == "product_minecraft"))
+}
+
 async fn get_profile(mc_access_token: &str) -> Result<MinecraftProfile> {
-    let client = Client::new();
+    let client = http_client()?;
 
     Ok(client
         .get("https://api.minecraftservices.com/minecraft/profile")
         .bearer_auth(mc_access_token)
+        .timeout(env::get_http_request_timeout())
         .send().await?
         .error_for_status()?
         .json::<MinecraftProfile>().await?)
 }
 
-#[allow(dead_code)]
 #[derive(Deserialize)]
 struct XboxAuthResponse {
-    #[serde(rename(deserialize = "IssueInstant"))]
-    issue_instant: String,
-
-    #[serde(rename(deserialize = "NotAfter"))]
-    not_after: String,
-
     #[serde(rename(deserialize = "Token"))]
     token: String,
 
@@ -272,17 +633,39 @@ struct XboxAuthResponse {
     display_claims: HashMap<String, Vec<HashMap<String, String>>>
 }
 
-#[allow(dead_code)]
 #[derive(Deserialize)]
-struct MinecraftAuthResponse {
-    username: String,
+struct XboxErrorResponse {
+    #[serde(rename(deserialize = "XErr"))]
+    xerr: u64
+}
 
-    roles: Vec<String>,
+#[derive(Deserialize)]
+struct EntitlementsResponse {
+    items: Vec<Entitlement>
+}
 
-    access_token: String,
+#[derive(Deserialize)]
+struct Entitlement {
+    name: String
+}
 
-    token_type: String,
+#[derive(Deserialize)]
+struct MinecraftAuthResponse {
+    access_token: String,
 
     /// Number of seconds until the token expires
     expires_in: u32
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn clear_without_accounts() {
+        // mock entries start out empty, as if nothing was ever stored
+        keyring::set_default_credential_builder(keyring::mock::default_credential_builder());
+
+        assert!(Account::clear().is_ok());
+    }
+}
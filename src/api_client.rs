@@ -0,0 +1,144 @@
+/*
+ * Steve Launcher - A Minecraft Launcher
+ * Copyright (C) 2023 Josh Kropf <josh@slashdev.ca>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use chrono::{DateTime, Utc};
+use reqwest::{header, RequestBuilder, Response, StatusCode};
+use std::{collections::BTreeMap, sync::Mutex, time::{Duration, Instant}};
+
+use crate::env;
+
+/// Delay before the first retry, doubled for each retry after
+const BASE_DELAY: Duration = Duration::from_millis(500);
+
+/// Longest wait before a retry, including waits asked for by Retry-After
+const MAX_DELAY: Duration = Duration::from_secs(60);
+
+/// Hosts that responded with 429 too many requests, and the time requests to
+/// the host can resume. Shared so concurrent requests back off together.
+static COOLDOWNS: Mutex<BTreeMap<String, Instant>> = Mutex::new(BTreeMap::new());
+
+pub trait SendRetry {
+    /// Send request, retrying rate limited requests, connection errors,
+    /// timeouts and server errors with jittered exponential backoff
+    async fn send_with_retry(self) -> reqwest::Result<Response>;
+}
+
+impl SendRetry for RequestBuilder {
+    async fn send_with_retry(self) -> reqwest::Result<Response> {
+        let (client, request) = self.build_split();
+        let request = request?;
+
+        let host = request.url().host_str()
+            .unwrap_or_default()
+            .to_string();
+
+        let mut attempt = 0;
+
+        loop {
+            if let Some(wait) = cooldown(&host) {
+                futures_time::task::sleep(wait.into()).await;
+            }
+
+            // requests with a streaming body can't be sent twice
+            let next = match attempt < env::get_download_retries() {
+                true => request.try_clone(),
+                false => None
+            };
+
+            let next = match next {
+                Some(next) => next,
+                None => return client.execute(request).await
+            };
+
+            let response = client.execute(next).await;
+
+            let delay = match &response {
+                Ok(r) if r.status() == StatusCode::TOO_MANY_REQUESTS => {
+                    let delay = retry_after(r).unwrap_or_else(|| backoff(attempt));
+                    set_cooldown(&host, delay);
+                    delay
+                },
+                Ok(r) if r.status().is_server_error() => {
+                    retry_after(r).unwrap_or_else(|| backoff(attempt))
+                },
+                Err(e) if e.is_timeout() || e.is_connect() => backoff(attempt),
+                _ => return response
+            };
+
+            futures_time::task::sleep(delay.into()).await;
+
+            attempt += 1;
+        }
+    }
+}
+
+/// Delay of retry `attempt`, randomized so clients don't retry in lock step
+fn backoff(attempt: u32) -> Duration {
+    let delay = BASE_DELAY.saturating_mul(2u32.saturating_pow(attempt)).min(MAX_DELAY);
+    delay.mul_f64(0.5 + fastrand::f64() / 2.0)
+}
+
+fn retry_after(response: &Response) -> Option<Duration> {
+    response.headers().get(header::RETRY_AFTER)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| parse_retry_after(v, Utc::now()))
+        .map(|d| d.min(MAX_DELAY))
+}
+
+/// Retry-After header value, either seconds to wait or the date to retry at
+fn parse_retry_after(value: &str, now: DateTime<Utc>) -> Option<Duration> {
+    let value = value.trim();
+
+    if let Ok(secs) = value.parse() {
+        return Some(Duration::from_secs(secs));
+    }
+
+    DateTime::parse_from_rfc2822(value).ok()
+        .map(|date| (date.with_timezone(&Utc) - now).to_std().unwrap_or_default())
+}
+
+/// Time left waiting on a rate limited host
+fn cooldown(host: &str) -> Option<Duration> {
+    COOLDOWNS.lock().unwrap()
+        .get(host)
+        .and_then(|until| until.checked_duration_since(Instant::now()))
+}
+
+fn set_cooldown(host: &str, delay: Duration) {
+    let until = Instant::now() + delay;
+
+    let mut cooldowns = COOLDOWNS.lock().unwrap();
+    let entry = cooldowns.entry(host.to_string()).or_insert(until);
+    *entry = (*entry).max(until);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn retry_after_seconds_and_date() {
+        let now = DateTime::parse_from_rfc2822("Wed, 21 Oct 2015 07:28:00 GMT").unwrap()
+            .with_timezone(&Utc);
+
+        assert_eq!(parse_retry_after("120", now), Some(Duration::from_secs(120)));
+        assert_eq!(parse_retry_after("Wed, 21 Oct 2015 07:28:30 GMT", now), Some(Duration::from_secs(30)));
+        assert_eq!(parse_retry_after("Wed, 21 Oct 2015 07:27:00 GMT", now), Some(Duration::ZERO));
+        assert_eq!(parse_retry_after("soon", now), None);
+    }
+}
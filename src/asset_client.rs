@@ -17,93 +17,315 @@
  */
 
 use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use futures_time::future::FutureExt;
 use futures_util::StreamExt;
 use semver::Version;
-use std::{collections::HashMap, io, fs, fs::File, path::Path};
-use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, fmt, io, fs, fs::File, path::Path, sync::OnceLock, time::Duration};
+use reqwest::{header, Certificate, Client, ClientBuilder, IntoUrl, RequestBuilder, StatusCode};
 
 use crate::{env, Error, ModLoader, ModLoaderName};
+use crate::api_client::SendRetry;
 use crate::json::{
-    AssetManifest, CurseForgeResponse, CurseForgeFile, CurseForgeMod,
-    ForgeVersionManifest, ModpackSearch, ModpackManifest, ModpackVersionManifest,
-    VersionManifest
+    AssetManifest, CurseForgeCategory, CurseForgeResponse, CurseForgeChangelogResponse, CurseForgeFile,
+    CurseForgeFingerprintMatch, CurseForgeFingerprintResponse, CurseForgeMod,
+    ForgeVersionManifest, JavaRuntimeIndex, JavaRuntimeManifest, ModpackChangelog, ModpackSearch,
+    ModpackManifest, ModpackVersionManifest, CurseForgeSearchResponse,
+    ModrinthVersion, VersionManifest
 };
 
 const VERSION_MANIFEST_URL: &str = "https://piston-meta.mojang.com/mc/game/version_manifest_v2.json";
 const FORGE_INDEX_URL: &str = "https://meta.prismlauncher.org/v1/net.minecraftforge/index.json";
 const NEOFORGE_INDEX_URL: &str = "https://meta.prismlauncher.org/v1/net.neoforged/index.json";
+const QUILT_INDEX_URL: &str = "https://meta.prismlauncher.org/v1/org.quiltmc.quilt-loader/index.json";
+const JAVA_RUNTIMES_URL: &str = "https://launchermeta.mojang.com/v1/products/java-runtime/2ec0cc96c44e5a76b9c8b7c39df7210883d12871/all.json";
 const CURSE_MOD_FILES_URL: &str = "https://api.curseforge.com/v1/mods/files";
 const CURSE_MODS_URL: &str = "https://api.curseforge.com/v1/mods";
+const CURSE_FINGERPRINTS_URL: &str = "https://api.curseforge.com/v1/fingerprints";
+const CURSE_CATEGORIES_URL: &str = "https://api.curseforge.com/v1/categories";
+const MODRINTH_VERSION_FILES_URL: &str = "https://api.modrinth.com/v2/version_files";
 const MODPACKS_CH_URL: &str = "https://api.modpacks.ch/public";
 
+/// CurseForge game id of Minecraft and class id of modpacks
+const CURSE_GAME_ID: &str = "432";
+const CURSE_MODPACKS_CLASS_ID: &str = "4471";
+
+/// Most results CurseForge returns for a page of search results
+const CURSE_SEARCH_PAGE_SIZE: usize = 50;
+
+/// CurseForge only pages through the first 10,000 search results
+const CURSE_SEARCH_MAX_RESULTS: usize = 10_000;
+
+/// Most fingerprints sent in one request, the endpoint rejects large lists
+const CURSE_FINGERPRINTS_CHUNK_SIZE: usize = 100;
+
+/// Response headers of cached json, for revalidating the cached copy
+#[derive(Deserialize, Serialize)]
+struct CacheMeta {
+    etag: Option<String>,
+    last_modified: Option<String>,
+    fetched: DateTime<Utc>
+}
+
 pub struct AssetClient {
     client: Client
 }
 
+/// Mojang hosts and the path they map to on a download mirror, following the
+/// BMCLAPI layout
+const MOJANG_MIRROR_HOSTS: &[(&str, &str)] = &[
+    ("https://piston-meta.mojang.com", ""),
+    ("https://piston-data.mojang.com", ""),
+    ("https://launchermeta.mojang.com", ""),
+    ("https://launcher.mojang.com", ""),
+    ("https://resources.download.minecraft.net", "/assets"),
+    ("https://libraries.minecraft.net", "/maven")
+];
+
+/// HTTP client builder with the connection timeout, user agent and extra CA
+/// certificates from the environment. Proxies are read from `HTTP_PROXY`,
+/// `HTTPS_PROXY` and `NO_PROXY` by reqwest.
+pub fn http_client_builder() -> Result<ClientBuilder> {
+    let mut builder = Client::builder()
+        .connect_timeout(env::get_http_connect_timeout())
+        .user_agent(env::get_user_agent());
+
+    if let Some(path) = env::get_ca_certs_path() {
+        let invalid = || Error::InvalidCaCertificate(path.to_string_lossy().to_string());
+
+        let pem = fs::read_to_string(&path)
+            .with_context(invalid)?;
+
+        for cert in split_pem_certs(&pem) {
+            let cert = Certificate::from_pem(cert.as_bytes())
+                .with_context(invalid)?;
+
+            builder = builder.add_root_certificate(cert);
+        }
+    }
+
+    Ok(builder)
+}
+
+/// HTTP client shared by all requests, so connections are reused
+pub fn http_client() -> Result<Client> {
+    static CLIENT: OnceLock<Client> = OnceLock::new();
+
+    if let Some(client) = CLIENT.get() {
+        return Ok(client.clone());
+    }
+
+    let client = http_client_builder()?
+        .build()
+        .context("Unable to initialize HTTP client")?;
+
+    Ok(CLIENT.get_or_init(|| client).clone())
+}
+
+/// Individual certificates of PEM bundle
+fn split_pem_certs(pem: &str) -> impl Iterator<Item = &str> {
+    pem.split_inclusive("-----END CERTIFICATE-----")
+        .filter(|cert| cert.contains("-----BEGIN CERTIFICATE-----"))
+}
+
+/// Replace mojang host of `url` with `mirror`, other urls are unchanged
+fn mirror_url(url: &str, mirror: Option<&str>) -> String {
+    let mirror = match mirror {
+        Some(mirror) => mirror.trim_end_matches('/'),
+        None => return url.to_string()
+    };
+
+    for (host, path) in MOJANG_MIRROR_HOSTS {
+        if let Some(rest) = url.strip_prefix(host) {
+            return format!("{mirror}{path}{rest}");
+        }
+    }
+
+    url.to_string()
+}
+
 impl AssetClient {
-    pub fn new() -> Self {
-        AssetClient { client: Client::new() }
+    pub fn new() -> Result<Self> {
+        Ok(AssetClient { client: http_client()? })
+    }
+
+    /// GET request for API calls, limited by the request timeout
+    fn api_get<U: IntoUrl>(&self, url: U) -> RequestBuilder {
+        self.client.get(url)
+            .timeout(env::get_http_request_timeout())
+    }
+
+    /// POST request for API calls, limited by the request timeout
+    fn api_post<U: IntoUrl>(&self, url: U) -> RequestBuilder {
+        self.client.post(url)
+            .timeout(env::get_http_request_timeout())
     }
 
     async fn fetch_json<T: serde::de::DeserializeOwned>(&self, url: &str) -> Result<T> {
-        Ok(self.client.get(url)
-            .send().await?
+        Ok(self.api_get(mirror_url(url, env::get_mojang_mirror().as_deref()))
+            .send_with_retry().await?
             .error_for_status()?
             .json::<T>().await?)
     }
 
+    /// Download to a temporary file renamed to `file_path` once complete, an
+    /// interrupted download is never mistaken for a complete file
     pub async fn download_file(&self, url: &str, file_path: &Path) -> Result<()> {
         fs::create_dir_all(file_path.parent().unwrap())?;
 
-        let mut stream = self.client.get(url)
+        let temp_path = super::fs::temp_path(file_path);
+
+        match self.download_to(url, &temp_path).await {
+            Ok(()) => Ok(fs::rename(&temp_path, file_path)?),
+            Err(e) => {
+                let _ = fs::remove_file(&temp_path);
+                Err(e)
+            }
+        }
+    }
+
+    async fn download_to(&self, url: &str, file_path: &Path) -> Result<()> {
+        let mut stream = self.client.get(mirror_url(url, env::get_mojang_mirror().as_deref()))
             .send().await?
             .error_for_status()?
             .bytes_stream();
 
         let mut file = File::create(file_path)?;
 
-        while let Some(item) = stream.next().await {
+        // downloads can take any amount of time, instead give up when
+        // the connection stalls for longer than the read timeout
+        let read_timeout = futures_time::time::Duration::from(env::get_http_read_timeout());
+
+        while let Some(item) = stream.next().timeout(read_timeout).await
+            .with_context(|| format!("Timed out reading {url}"))?
+        {
             io::copy(&mut item?.as_ref(), &mut file)?;
         }
 
         Ok(())
     }
 
+    /// Fetch json from cache when it was fetched within the manifest TTL,
+    /// otherwise revalidate the cached copy using its ETag and Last-Modified
+    /// headers. The stale copy is used when the request fails.
+    async fn fetch_cached_json<T: serde::de::DeserializeOwned>(&self, url: &str) -> Result<T> {
+        self.fetch_cached_json_in(&env::get_cache_dir().join("http"), url, env::get_manifest_ttl()).await
+    }
+
+    /// `fetch_cached_json` with the cache in `cache_dir`, a zero `ttl`
+    /// always revalidates the cached copy
+    async fn fetch_cached_json_in<T: serde::de::DeserializeOwned>(&self,
+        cache_dir: &Path,
+        url: &str,
+        ttl: Duration
+    ) -> Result<T> {
+        // i.e. meta.prismlauncher.org_v1_net.minecraftforge_index.json
+        let file_name = url.trim_start_matches("https://")
+            .trim_start_matches("http://")
+            .replace(['/', ':'], "_");
+        let cache_path = cache_dir.join(&file_name);
+        let meta_path = cache_dir.join(format!("{file_name}.meta"));
+
+        let meta: Option<CacheMeta> = fs::read_to_string(&meta_path).ok()
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .filter(|_| cache_path.exists());
+
+        let is_fresh = meta.as_ref()
+            .and_then(|m| (Utc::now() - m.fetched).to_std().ok())
+            .is_some_and(|age| age < ttl);
+
+        if !is_fresh {
+            let mut request = self.api_get(mirror_url(url, env::get_mojang_mirror().as_deref()));
+
+            if let Some(meta) = &meta {
+                if let Some(etag) = &meta.etag {
+                    request = request.header(header::IF_NONE_MATCH, etag);
+                }
+                if let Some(modified) = &meta.last_modified {
+                    request = request.header(header::IF_MODIFIED_SINCE, modified);
+                }
+            }
+
+            let response = match request.send_with_retry().await.and_then(|r| r.error_for_status()) {
+                Ok(response) => response,
+                // fallback to stale cache on flaky connections
+                Err(_) if meta.is_some() => return Ok(serde_json::from_slice(&fs::read(cache_path)?)?),
+                Err(e) => return Err(e.into())
+            };
+
+            let header_value = |name| response.headers().get(name)
+                .and_then(|v: &header::HeaderValue| v.to_str().ok())
+                .map(String::from);
+
+            let new_meta = CacheMeta {
+                etag: header_value(header::ETAG),
+                last_modified: header_value(header::LAST_MODIFIED),
+                fetched: Utc::now()
+            };
+
+            fs::create_dir_all(cache_dir)?;
+
+            if response.status() != StatusCode::NOT_MODIFIED {
+                super::fs::write_atomic(&cache_path, response.bytes().await?)?;
+            }
+
+            super::fs::write_atomic(&meta_path, serde_json::to_string(&new_meta)?)?;
+        }
+
+        Ok(serde_json::from_slice(&fs::read(cache_path)?)?)
+    }
+
     pub async fn get_mc_version_manifest(&self) -> Result<VersionManifest> {
-        self.fetch_json::<VersionManifest>(VERSION_MANIFEST_URL).await
+        self.fetch_cached_json::<VersionManifest>(VERSION_MANIFEST_URL).await
     }
 
     pub async fn get_game_manifest_json(&self, mc_version: &str) -> Result<String> {
-        let manifest = self.get_mc_version_manifest().await?;
+        let mut manifest = self.get_mc_version_manifest().await?;
+
+        // the cached manifest may be from before the version was released
+        if !manifest.versions.iter().any(|v| v.id == mc_version) {
+            manifest = self.fetch_cached_json_in(&env::get_cache_dir().join("http"), VERSION_MANIFEST_URL, Duration::ZERO)
+                .await?;
+        }
 
         let version = manifest.versions.iter()
             .find(|v| v.id == mc_version)
             .ok_or(Error::MinecraftVersionNotFound(mc_version.to_string()))?;
 
-        Ok(self.client.get(&version.url)
-            .send().await?
+        Ok(self.api_get(mirror_url(&version.url, env::get_mojang_mirror().as_deref()))
+            .send_with_retry().await?
             .text().await?)
     }
 
     pub async fn get_asset_manfiest(&self, url: &str) -> Result<AssetManifest> {
-        Ok(self.fetch_json(url).await?)
+        self.fetch_json(url).await
+    }
+
+    pub async fn get_java_runtime_index(&self) -> Result<JavaRuntimeIndex> {
+        self.fetch_json(JAVA_RUNTIMES_URL).await
+    }
+
+    pub async fn get_java_runtime_manifest(&self, url: &str) -> Result<JavaRuntimeManifest> {
+        self.fetch_json(url).await
     }
 
     pub async fn get_loader_manifest_json(&self, mod_loader: &ModLoader) -> Result<String> {
         let url = match mod_loader.name {
             ModLoaderName::Forge => FORGE_INDEX_URL,
-            ModLoaderName::NeoForge => NEOFORGE_INDEX_URL
+            ModLoaderName::NeoForge => NEOFORGE_INDEX_URL,
+            ModLoaderName::Quilt => QUILT_INDEX_URL
         };
 
-        let index: ForgeVersionManifest = self.fetch_json(url).await?;
+        let index: ForgeVersionManifest = self.fetch_cached_json(url).await?;
 
         index.versions.iter()
             .find(|v| v.version == mod_loader.version)
             .ok_or(Error::ForgeVersionNotFound(mod_loader.version.clone()))?;
 
         let file_name = format!("{ver}.json", ver = mod_loader.version);
-        Ok(self.client.get(url.replace("index.json", file_name.as_str()))
-            .send().await?
+        Ok(self.api_get(url.replace("index.json", file_name.as_str()))
+            .send_with_retry().await?
             .text().await?)
     }
 
@@ -113,10 +335,11 @@ impl AssetClient {
     ) -> Result<Vec<ModLoaderVersion>> {
         let url = match loader {
             ModLoaderName::Forge => FORGE_INDEX_URL,
-            ModLoaderName::NeoForge => NEOFORGE_INDEX_URL
+            ModLoaderName::NeoForge => NEOFORGE_INDEX_URL,
+            ModLoaderName::Quilt => QUILT_INDEX_URL
         };
 
-        let index: ForgeVersionManifest = self.fetch_json(url).await?;
+        let index: ForgeVersionManifest = self.fetch_cached_json(url).await?;
 
         let mut versions = index.versions.iter()
             .filter(|v| v.is_for_mc_version(mc_version))
@@ -129,10 +352,10 @@ impl AssetClient {
     }
 
     pub async fn get_curseforge_file_list(&self, file_ids: &Vec<u64>) -> Result<Vec<CurseForgeFile>> {
-        let response = self.client.post(CURSE_MOD_FILES_URL)
+        let response = self.api_post(CURSE_MOD_FILES_URL)
             .header("x-api-key", env::get_curse_api_key())
             .json(&HashMap::from([("fileIds", file_ids)]))
-            .send().await?
+            .send_with_retry().await?
             .error_for_status()?
             .json::<CurseForgeResponse<CurseForgeFile>>().await?;
 
@@ -144,19 +367,68 @@ impl AssetClient {
     }
 
     pub async fn get_curseforge_mods(&self, mod_ids: &Vec<u64>) -> Result<Vec<CurseForgeMod>> {
-        let response = self.client.post(CURSE_MODS_URL)
+        let response = self.api_post(CURSE_MODS_URL)
             .header("x-api-key", env::get_curse_api_key())
             .json(&HashMap::from([("modIds", mod_ids)]))
-            .send().await?
+            .send_with_retry().await?
             .error_for_status()?
             .json::<CurseForgeResponse<CurseForgeMod>>().await?;
 
         Ok(response.data)
     }
 
+    /// Changelog of a CurseForge file, converted from HTML to plain text
+    pub async fn get_curseforge_changelog(&self, mod_id: u32, file_id: u32) -> Result<String> {
+        let response = self.api_get(format!("{CURSE_MODS_URL}/{mod_id}/files/{file_id}/changelog"))
+            .header("x-api-key", env::get_curse_api_key())
+            .send_with_retry().await?
+            .error_for_status()?
+            .json::<CurseForgeChangelogResponse>().await?;
+
+        Ok(html_to_text(&response.data))
+    }
+
+    /// Files matching fingerprints exactly, fingerprints without a match are
+    /// left out. Large lists are sent `CURSE_FINGERPRINTS_CHUNK_SIZE` at a time.
+    pub async fn get_curseforge_fingerprint_matches(&self,
+        fingerprints: &[u32]
+    ) -> Result<Vec<CurseForgeFingerprintMatch>> {
+        let mut matches = vec![];
+
+        for chunk in fingerprints.chunks(CURSE_FINGERPRINTS_CHUNK_SIZE) {
+            let response = self.api_post(CURSE_FINGERPRINTS_URL)
+                .header("x-api-key", env::get_curse_api_key())
+                .json(&HashMap::from([("fingerprints", chunk)]))
+                .send_with_retry().await?
+                .error_for_status()?
+                .json::<CurseForgeFingerprintResponse>().await?;
+
+            matches.extend(response.data.exact_matches);
+        }
+
+        Ok(matches)
+    }
+
+    /// Find Modrinth versions by SHA-1 file hash, keyed by the hash
+    pub async fn get_modrinth_versions_from_hashes(&self,
+        hashes: &[String]
+    ) -> Result<HashMap<String, ModrinthVersion>> {
+        let body = serde_json::json!({
+            "hashes": hashes,
+            "algorithm": "sha1"
+        });
+
+        // modrinth asks that API clients identify themselves, see `env::get_user_agent`
+        Ok(self.api_post(MODRINTH_VERSION_FILES_URL)
+            .json(&body)
+            .send_with_retry().await?
+            .error_for_status()?
+            .json().await?)
+    }
+
     pub async fn get_ftb_modpack_versions(&self, pack_id: u32) -> Result<ModpackManifest> {
-        let response = self.client.get(format!("{MODPACKS_CH_URL}/modpack/{pack_id}"))
-            .send().await?
+        let response = self.api_get(format!("{MODPACKS_CH_URL}/modpack/{pack_id}"))
+            .send_with_retry().await?
             .error_for_status()?
             .json::<ModpackManifest>().await?;
 
@@ -164,17 +436,26 @@ impl AssetClient {
     }
 
     pub async fn get_ftb_modpack(&self, pack_id: u32, version_id: u32) -> Result<ModpackVersionManifest> {
-        let response = self.client.get(format!("{MODPACKS_CH_URL}/modpack/{pack_id}/{version_id}"))
-            .send().await?
+        let response = self.api_get(format!("{MODPACKS_CH_URL}/modpack/{pack_id}/{version_id}"))
+            .send_with_retry().await?
             .error_for_status()?
             .json::<ModpackVersionManifest>().await?;
 
         Ok(response)
     }
 
+    pub async fn get_ftb_modpack_changelog(&self, pack_id: u32, version_id: u32) -> Result<String> {
+        let response = self.api_get(format!("{MODPACKS_CH_URL}/modpack/{pack_id}/{version_id}/changelog"))
+            .send_with_retry().await?
+            .error_for_status()?
+            .json::<ModpackChangelog>().await?;
+
+        Ok(response.content)
+    }
+
     pub async fn get_curse_modpack_versions(&self, pack_id: u32) -> Result<ModpackManifest> {
-        let response = self.client.get(format!("{MODPACKS_CH_URL}/curseforge/{pack_id}"))
-            .send().await?
+        let response = self.api_get(format!("{MODPACKS_CH_URL}/curseforge/{pack_id}"))
+            .send_with_retry().await?
             .error_for_status()?
             .json::<ModpackManifest>().await?;
 
@@ -182,19 +463,77 @@ impl AssetClient {
     }
 
     pub async fn get_curse_modpack(&self, pack_id: u32, version_id: u32) -> Result<ModpackVersionManifest> {
-        let response = self.client.get(format!("{MODPACKS_CH_URL}/curseforge/{pack_id}/{version_id}"))
-            .send().await?
+        let response = self.api_get(format!("{MODPACKS_CH_URL}/curseforge/{pack_id}/{version_id}"))
+            .send_with_retry().await?
             .error_for_status()?
             .json::<ModpackVersionManifest>().await?;
 
         Ok(response)
     }
 
+    /// Search CurseForge modpacks, requesting pages of results until `limit`
+    /// results or all of the matching packs are found
+    pub async fn search_curseforge_modpacks(&self,
+        term: &str,
+        sort: CurseForgeSort,
+        category_id: Option<u32>,
+        limit: usize
+    ) -> Result<Vec<CurseForgeMod>> {
+        let limit = limit.min(CURSE_SEARCH_MAX_RESULTS);
+        let mut results = vec![];
+
+        while results.len() < limit {
+            let page_size = (limit - results.len()).min(CURSE_SEARCH_PAGE_SIZE);
+
+            let mut query = vec![
+                ("gameId", CURSE_GAME_ID.to_string()),
+                ("classId", CURSE_MODPACKS_CLASS_ID.to_string()),
+                ("searchFilter", term.to_string()),
+                ("sortField", sort.field_id().to_string()),
+                ("sortOrder", sort.order().to_string()),
+                ("index", results.len().to_string()),
+                ("pageSize", page_size.to_string())
+            ];
+
+            if let Some(category_id) = category_id {
+                query.push(("categoryId", category_id.to_string()));
+            }
+
+            let response = self.api_get(format!("{CURSE_MODS_URL}/search"))
+                .header("x-api-key", env::get_curse_api_key())
+                .query(&query)
+                .send_with_retry().await?
+                .error_for_status()?
+                .json::<CurseForgeSearchResponse>().await?;
+
+            let count = response.data.len();
+            results.extend(response.data);
+
+            if count < page_size || results.len() >= response.pagination.total_count as usize {
+                break;
+            }
+        }
+
+        Ok(results)
+    }
+
+    /// Categories of CurseForge modpacks, used to filter search results
+    pub async fn get_curseforge_modpack_categories(&self) -> Result<Vec<CurseForgeCategory>> {
+        let response = self.api_get(CURSE_CATEGORIES_URL)
+            .header("x-api-key", env::get_curse_api_key())
+            .query(&[("gameId", CURSE_GAME_ID), ("classId", CURSE_MODPACKS_CLASS_ID)])
+            .send_with_retry().await?
+            .error_for_status()?
+            .json::<CurseForgeResponse<CurseForgeCategory>>().await?;
+
+        Ok(response.data)
+    }
+
     /// * `limit` - Search result limit, max 50
     pub async fn search_modpacks(&self, term: &str, limit: u8) -> Result<ModpackSearch> {
         // 50 appears to be max, i.e. setting limit to 99 but response includes "limit: 50"
-        let response = self.client.get(format!("{MODPACKS_CH_URL}/modpack/search/{limit}?term={term}"))
-            .send().await?
+        let response = self.api_get(format!("{MODPACKS_CH_URL}/modpack/search/{limit}?term={term}"))
+            .send_with_retry().await?
             .error_for_status()?
             .json::<ModpackSearch>().await?;
 
@@ -202,9 +541,37 @@ impl AssetClient {
     }
 }
 
-impl Default for AssetClient {
-    fn default() -> Self {
-        Self::new()
+/// Order of CurseForge search results
+#[derive(Clone, Copy, Default)]
+#[cfg_attr(feature = "clap", derive(clap::ValueEnum))]
+pub enum CurseForgeSort {
+    Featured,
+    #[default]
+    Popularity,
+    #[cfg_attr(feature = "clap", value(name = "updated"))]
+    LastUpdated,
+    Name,
+    #[cfg_attr(feature = "clap", value(name = "downloads"))]
+    TotalDownloads
+}
+
+impl CurseForgeSort {
+    /// https://docs.curseforge.com/#tocS_ModsSearchSortField
+    fn field_id(&self) -> u8 {
+        match self {
+            CurseForgeSort::Featured => 1,
+            CurseForgeSort::Popularity => 2,
+            CurseForgeSort::LastUpdated => 3,
+            CurseForgeSort::Name => 4,
+            CurseForgeSort::TotalDownloads => 6
+        }
+    }
+
+    fn order(&self) -> &'static str {
+        match self {
+            CurseForgeSort::Name => "asc",
+            _ => "desc"
+        }
     }
 }
 
@@ -228,12 +595,176 @@ impl ModLoaderVersion {
     }
 }
 
-impl ToString for ModLoaderVersion {
-    fn to_string(&self) -> String {
+impl fmt::Display for ModLoaderVersion {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         if self.recommended {
-            format!("{ver} *", ver = self.version)
+            write!(f, "{ver} *", ver = self.version)
         } else {
-            self.version.to_string()
+            write!(f, "{ver}", ver = self.version)
+        }
+    }
+}
+
+/// Strip tags from an HTML changelog, keeping line breaks and list items
+fn html_to_text(html: &str) -> String {
+    let mut text = String::new();
+    let mut chars = html.chars();
+
+    while let Some(c) = chars.next() {
+        if c != '<' {
+            text.push(c);
+            continue;
+        }
+
+        let tag: String = chars.by_ref().take_while(|c| *c != '>').collect();
+        let closing = tag.starts_with('/');
+        let name = tag.trim_start_matches('/')
+            .split(|c: char| c.is_whitespace() || c == '/')
+            .next()
+            .unwrap_or("")
+            .to_lowercase();
+
+        match name.as_str() {
+            "br" => text.push('\n'),
+            "li" if !closing => text.push_str("\n- "),
+            "p" | "div" | "li" | "ul" | "ol" | "tr" | "h1" | "h2" | "h3" | "h4" | "h5" | "h6"
+                if closing => text.push('\n'),
+            _ => { }
         }
     }
+
+    let text = text.replace("&nbsp;", " ")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&apos;", "'")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&amp;", "&");
+
+    // drop blank lines left behind by nested block tags
+    text.lines()
+        .map(str::trim_end)
+        .filter(|l| !l.trim().is_empty())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn html_changelog_to_text() {
+        let html = "<p>Changes:</p><ul><li>Updated <b>JEI</b></li>\n<li>Fixed &quot;crash&quot; &amp; lag</li></ul><p>Thanks<br/>Team</p>";
+
+        assert_eq!(
+            html_to_text(html),
+            "Changes:\n- Updated JEI\n- Fixed \"crash\" & lag\nThanks\nTeam"
+        );
+    }
+
+    #[test]
+    fn mirror_mojang_urls() {
+        let mirror = Some("https://bmclapi2.bangbang93.com/");
+
+        assert_eq!(
+            mirror_url("https://resources.download.minecraft.net/ab/abcd", mirror),
+            "https://bmclapi2.bangbang93.com/assets/ab/abcd"
+        );
+        assert_eq!(
+            mirror_url("https://piston-meta.mojang.com/mc/game/version_manifest_v2.json", mirror),
+            "https://bmclapi2.bangbang93.com/mc/game/version_manifest_v2.json"
+        );
+        assert_eq!(
+            mirror_url("https://maven.minecraftforge.net/net/minecraftforge/forge.jar", mirror),
+            "https://maven.minecraftforge.net/net/minecraftforge/forge.jar"
+        );
+        assert_eq!(
+            mirror_url("https://libraries.minecraft.net/com/mojang/brigadier.jar", None),
+            "https://libraries.minecraft.net/com/mojang/brigadier.jar"
+        );
+    }
+
+    /// Answer one request per connection with each of `responses`, returning
+    /// the request heads received
+    fn serve(responses: Vec<&'static str>) -> (String, std::thread::JoinHandle<Vec<String>>) {
+        use std::io::{Read, Write};
+
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/manifest.json", listener.local_addr().unwrap());
+
+        let handle = std::thread::spawn(move || {
+            responses.into_iter()
+                .map(|response| {
+                    let (mut stream, _) = listener.accept().unwrap();
+                    let mut head = Vec::new();
+                    let mut buf = [0; 1024];
+
+                    while !head.ends_with(b"\r\n\r\n") {
+                        let n = stream.read(&mut buf).unwrap();
+                        head.extend_from_slice(&buf[..n]);
+                    }
+
+                    stream.write_all(response.as_bytes()).unwrap();
+                    String::from_utf8(head).unwrap().to_lowercase()
+                })
+                .collect()
+        });
+
+        (url, handle)
+    }
+
+    #[tokio::test]
+    async fn cached_json_etag_ttl_and_stale() {
+        let cache_dir = std::env::temp_dir().join(format!("steve-http-cache-test-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&cache_dir);
+
+        let (url, server) = serve(vec![
+            "HTTP/1.1 200 OK\r\nETag: \"v1\"\r\nContent-Length: 7\r\nConnection: close\r\n\r\n{\"a\":1}",
+            "HTTP/1.1 304 Not Modified\r\nETag: \"v1\"\r\nConnection: close\r\n\r\n",
+            "HTTP/1.1 200 OK\r\nETag: \"v2\"\r\nContent-Length: 7\r\nConnection: close\r\n\r\n{\"a\":2}",
+            "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"
+        ]);
+
+        let client = AssetClient::new().unwrap();
+        let hour = Duration::from_secs(3600);
+
+        let mut results = Vec::new();
+        // fetched, then used from cache within the TTL
+        results.push(client.fetch_cached_json_in::<serde_json::Value>(&cache_dir, &url, hour).await.unwrap());
+        results.push(client.fetch_cached_json_in(&cache_dir, &url, hour).await.unwrap());
+        // revalidated as not modified, then changed
+        results.push(client.fetch_cached_json_in(&cache_dir, &url, Duration::ZERO).await.unwrap());
+        results.push(client.fetch_cached_json_in(&cache_dir, &url, Duration::ZERO).await.unwrap());
+        // stale copy used when the request fails
+        results.push(client.fetch_cached_json_in(&cache_dir, &url, Duration::ZERO).await.unwrap());
+
+        let requests = server.join().unwrap();
+        let _ = fs::remove_dir_all(&cache_dir);
+
+        let values: Vec<_> = results.iter().map(|v| v["a"].as_i64().unwrap()).collect();
+        assert_eq!(values, [1, 1, 1, 2, 2]);
+
+        assert_eq!(requests.len(), 4);
+        assert!(!requests[0].contains("if-none-match"));
+        assert!(requests[1].contains("if-none-match: \"v1\""));
+        assert!(requests[3].contains("if-none-match: \"v2\""));
+    }
+
+    #[tokio::test]
+    async fn cached_json_error_without_cache() {
+        let cache_dir = std::env::temp_dir().join(format!("steve-http-cache-miss-test-{}", std::process::id()));
+
+        let (url, server) = serve(vec![
+            "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"
+        ]);
+
+        let result = AssetClient::new().unwrap()
+            .fetch_cached_json_in::<serde_json::Value>(&cache_dir, &url, Duration::ZERO).await;
+
+        server.join().unwrap();
+
+        assert!(result.is_err());
+        assert!(!cache_dir.exists());
+    }
 }
@@ -16,30 +16,36 @@
  * along with this program.  If not, see <https://www.gnu.org/licenses/>.
  */
 
-use anyhow::{Context, Result};
+use anyhow::{bail, Context, Result};
+use futures_util::stream::{self, StreamExt};
 use semver::{Version, VersionReq};
-use std::{collections::HashMap, fs, path::Path, path::PathBuf};
+use std::{collections::{HashMap, HashSet}, fs, path::Path, path::PathBuf, time::Duration};
+use walkdir::WalkDir;
 
-use crate::{asset_client::AssetClient, env, Error, Progress, zip};
+use crate::{asset_client::AssetClient, env, Error, hash, java_runtime::java_major_version, Progress, zip};
 use crate::json::{
-    AssetManifest, ForgeDistribution, ForgeLibrary, ForgeManifest, GameLibrary,
-    GameManifest, ModLoader
+    AssetDownload, AssetManifest, ForgeDistribution, ForgeLibrary, ForgeManifest,
+    GameLibrary, GameLoggingClient, GameManifest, JavaRuntimeFile, JavaRuntimeManifest, ModLoader
 };
 
 pub struct AssetManager {
     client: AssetClient,
     assets_dir: PathBuf,
     cache_dir: PathBuf,
-    libs_dir: PathBuf
+    libs_dir: PathBuf,
+    runtimes_dir: PathBuf,
+    offline: bool
 }
 
 impl AssetManager {
     pub fn new() -> Result<Self> {
         let manager = AssetManager {
-            client: AssetClient::new(),
+            client: AssetClient::new()?,
             assets_dir: env::get_assets_dir(),
             cache_dir: env::get_cache_dir(),
-            libs_dir: env::get_libs_dir()
+            libs_dir: env::get_libs_dir(),
+            runtimes_dir: env::get_runtimes_dir(),
+            offline: false
         };
 
         fs::create_dir_all(manager.objects_dir())?;
@@ -49,6 +55,26 @@ impl AssetManager {
         Ok(manager)
     }
 
+    /// Use only files that already exist when `offline`, failing with the
+    /// list of missing files in place of downloading them
+    pub fn with_offline(mut self, offline: bool) -> Self {
+        self.offline = offline;
+        self
+    }
+
+    /// Fail with `missing` files when offline, there is nothing to do when online
+    fn check_offline<P: AsRef<Path>>(&self, missing: &[P]) -> Result<()> {
+        if self.offline && !missing.is_empty() {
+            let paths: Vec<_> = missing.iter()
+                .map(|p| p.as_ref().to_string_lossy().to_string())
+                .collect();
+
+            bail!(Error::OfflineFilesMissing(paths.join("\n")));
+        }
+
+        Ok(())
+    }
+
     pub fn objects_dir(&self) -> PathBuf {
         self.assets_dir.join("objects")
     }
@@ -61,49 +87,58 @@ impl AssetManager {
         self.cache_dir.join("versions")
     }
 
+    pub fn log_configs_dir(&self) -> PathBuf {
+        self.assets_dir.join("log_configs")
+    }
+
     pub fn virtual_assets_dir(&self, asset_index_id: &str) -> PathBuf {
         self.assets_dir.join("virtual").join(asset_index_id)
     }
 
+    pub fn java_runtime_dir(&self, component: &str) -> PathBuf {
+        self.runtimes_dir.join(component)
+    }
+
+    /// Path of the java executable in the runtime `component`, whether or
+    /// not the runtime is installed
+    pub fn java_runtime_executable(&self, component: &str) -> PathBuf {
+        self.java_runtime_dir(component).join(java_executable_path())
+    }
+
+    pub fn game_manifest_path(&self, mc_version: &str) -> PathBuf {
+        self.versions_dir().join(game_manifest_file_name(mc_version))
+    }
+
+    pub fn loader_manifest_path(&self, mod_loader: &ModLoader) -> PathBuf {
+        self.versions_dir().join(loader_manifest_file_name(mod_loader))
+    }
+
     pub async fn get_game_manifest(&self, mc_version: &str) -> Result<GameManifest> {
-        let version_file_path = self.versions_dir()
-            .join(format!("{mc_version}.json"));
+        let version_file_path = self.game_manifest_path(mc_version);
 
         if !version_file_path.exists() {
+            self.check_offline(&[&version_file_path])?;
+
             let game_manifest_json = self.client.get_game_manifest_json(mc_version).await?;
 
-            fs::write(&version_file_path, game_manifest_json)?;
+            super::fs::write_atomic(&version_file_path, game_manifest_json)?;
         }
 
-        let version_file = fs::File::open(version_file_path)?;
-        let mut game_manifest: GameManifest = serde_json::from_reader(version_file)?;
-
-        apply_lib_overrides(&mut game_manifest)?;
-
-        Ok(game_manifest)
+        load_game_manifest(&version_file_path)
     }
 
     pub async fn get_loader_manifest(&self, mod_loader: &ModLoader) -> Result<ForgeManifest> {
-        let file_name = format!("{name}_{ver}.json",
-            name = mod_loader.name.to_string(),
-            ver = mod_loader.version
-        );
-
-        let version_file_path = self.versions_dir()
-            .join(file_name);
+        let version_file_path = self.loader_manifest_path(mod_loader);
 
         if !version_file_path.exists() {
+            self.check_offline(&[&version_file_path])?;
+
             let json = self.client.get_loader_manifest_json(mod_loader).await?;
 
-            fs::write(&version_file_path, json)?;
+            super::fs::write_atomic(&version_file_path, json)?;
         }
 
-        let version_file = fs::File::open(version_file_path)?;
-        let mut forge_manifest: ForgeManifest = serde_json::from_reader(version_file)?;
-
-        populate_fml_libs(&mut forge_manifest)?;
-
-        Ok(forge_manifest)
+        load_loader_manifest(&version_file_path)
     }
 
     pub async fn get_asset_manfiest(&self, game_manifest: &GameManifest) -> Result<AssetManifest> {
@@ -115,11 +150,12 @@ impl AssetManager {
             return Ok(serde_json::from_reader(index_file)?);
         }
 
+        self.check_offline(&[&index_file_path])?;
+
         let asset_index_url = game_manifest.asset_index.download.url.as_str();
         let asset_manifest = self.client.get_asset_manfiest(asset_index_url).await?;
 
-        let index_file = fs::File::create(index_file_path)?;
-        serde_json::to_writer(index_file, &asset_manifest)?;
+        super::fs::write_atomic(&index_file_path, serde_json::to_string(&asset_manifest)?)?;
 
         Ok(asset_manifest)
     }
@@ -128,11 +164,29 @@ impl AssetManager {
         asset_manifest: &AssetManifest,
         progress: &mut dyn Progress
     ) -> Result<()> {
-        progress.begin("Downloading assets", asset_manifest.objects.len());
+        if self.offline {
+            let missing: Vec<_> = asset_manifest.objects.values()
+                .map(|obj| self.objects_dir().join(&obj.hash[0..2]).join(&obj.hash))
+                .filter(|path| !path.exists())
+                .collect();
 
-        for (i, (_, obj)) in asset_manifest.objects.iter().enumerate() {
-            progress.advance(i + 1);
-            self.download_asset(&obj.hash).await?;
+            return self.check_offline(&missing);
+        }
+
+        let total_size = asset_manifest.objects.values()
+            .map(|obj| obj.size as u64)
+            .sum();
+
+        progress.begin_bytes("Downloading assets", total_size);
+
+        let mut downloads = stream::iter(asset_manifest.objects.values())
+            .map(|obj| async move { self.download_asset(&obj.hash).await.map(|_| obj.size as usize) })
+            .buffer_unordered(env::get_download_jobs());
+
+        let mut downloaded = 0;
+        while let Some(result) = downloads.next().await {
+            downloaded += result?;
+            progress.advance(downloaded);
         }
 
         progress.end();
@@ -140,6 +194,59 @@ impl AssetManager {
         Ok(())
     }
 
+    /// Download log4j configuration for the game client, returns the path
+    /// of the configuration file
+    pub async fn get_logging_config(&self, logging: &GameLoggingClient) -> Result<PathBuf> {
+        let config_path = self.log_configs_dir().join(&logging.file.id);
+
+        if !config_path.exists() {
+            self.check_offline(&[&config_path])?;
+            self.client.download_file(&logging.file.download.url, &config_path).await?;
+        }
+
+        Ok(config_path)
+    }
+
+    /// Copy asset objects from another launcher's assets dir, skipping objects
+    /// already downloaded and objects with contents not matching their hash.
+    /// Returns the number of objects copied.
+    pub fn import_assets(&self, assets_dir: &Path, progress: &mut dyn Progress) -> Result<usize> {
+        let objects: Vec<_> = WalkDir::new(assets_dir.join("objects"))
+            .into_iter()
+            .filter_map(|e| e.ok())
+            .filter(|e| e.file_type().is_file())
+            .collect();
+
+        progress.begin("Importing assets", objects.len());
+
+        let mut count = 0;
+
+        for (i, entry) in objects.iter().enumerate() {
+            progress.advance(i + 1);
+
+            let hash = entry.file_name().to_string_lossy();
+            if hash.len() != 40 {
+                continue;
+            }
+
+            let object_file = self.objects_dir()
+                .join(&hash[0..2])
+                .join(hash.as_ref());
+
+            if object_file.exists() || hash::sha1(entry.path())? != hash {
+                continue;
+            }
+
+            fs::create_dir_all(object_file.parent().unwrap())?;
+            fs::copy(entry.path(), object_file)?;
+            count += 1;
+        }
+
+        progress.end();
+
+        Ok(count)
+    }
+
     async fn download_asset(&self, hash: &str) -> Result<()> {
         // first 2 chars of hash is used for directory of objects
         let hash_prefix = &hash[0..2];
@@ -155,69 +262,207 @@ impl AssetManager {
 
         let url = format!("https://resources.download.minecraft.net/{hash_prefix}/{hash}");
 
-        self.client.download_file(&url, &object_file).await
+        self.download_with_retry(&url, &object_file).await
     }
 
-    pub async fn download_libraries(&self,
-        game_manifest: &GameManifest,
+    /// Download mojang java runtime component, when not already installed,
+    /// and return the path of the java executable
+    pub async fn get_java_runtime(&self,
+        component: &str,
+        progress: &mut dyn Progress
+    ) -> Result<PathBuf> {
+        let runtime_dir = self.java_runtime_dir(component);
+
+        // version file is written last, an install that was interrupted
+        // will pick up where it left off
+        let version_file = runtime_dir.join(".version");
+
+        if !version_file.exists() {
+            self.check_offline(&[&runtime_dir])?;
+
+            let index = self.client.get_java_runtime_index().await?;
+
+            let runtime = env::get_java_runtime_platform()
+                .and_then(|platform| index.get(platform))
+                .and_then(|components| components.get(component))
+                .and_then(|runtimes| runtimes.first())
+                .ok_or_else(|| Error::JavaRuntimeNotFound {
+                    component: component.to_string(),
+                    platform: format!("{}-{}", std::env::consts::OS, std::env::consts::ARCH)
+                })?;
+
+            let manifest = self.client.get_java_runtime_manifest(&runtime.manifest.url).await?;
+
+            self.install_java_runtime(&runtime_dir, &manifest, progress).await?;
+
+            super::fs::write_atomic(&version_file, &runtime.version.name)?;
+        }
+
+        Ok(runtime_dir.join(java_executable_path()))
+    }
+
+    /// Find the runtime component name for the major version of java
+    pub async fn get_java_runtime_component(&self, major_version: u32) -> Result<String> {
+        let index = self.client.get_java_runtime_index().await?;
+
+        let components = env::get_java_runtime_platform()
+            .and_then(|platform| index.get(platform))
+            .ok_or(Error::JavaVersionNotFound(major_version))?;
+
+        let mut matches: Vec<&String> = components.iter()
+            .filter(|(_, runtimes)| runtimes.first()
+                .and_then(|r| java_major_version(&r.version.name))
+                .is_some_and(|v| v == major_version))
+            .map(|(component, _)| component)
+            .collect();
+
+        // prefer release components over snapshots, i.e. `java-runtime-gamma`
+        // over `java-runtime-gamma-snapshot`
+        matches.sort();
+
+        match matches.first() {
+            Some(component) => Ok(component.to_string()),
+            None => bail!(Error::JavaVersionNotFound(major_version))
+        }
+    }
+
+    async fn install_java_runtime(&self,
+        runtime_dir: &Path,
+        manifest: &JavaRuntimeManifest,
         progress: &mut dyn Progress
     ) -> Result<()> {
-        let client_path = get_client_jar_path(&game_manifest.id);
-        let mut lib_downloads: Vec<(&str, &String)> = vec![
-            (client_path.as_str(), &game_manifest.downloads.client.url)
-        ];
+        let files: Vec<_> = manifest.files.iter()
+            .filter_map(|(path, file)| match file {
+                JavaRuntimeFile::File { downloads, executable } =>
+                    Some((runtime_dir.join(path), &downloads.raw, *executable)),
+                _ => None
+            })
+            .collect();
 
-        lib_downloads.extend(
-            game_manifest.libraries.iter()
-                .filter(|lib| lib.has_rules_match())
-                // FIXME how to let this result error propagate?
-                .flat_map(|lib| lib.artifacts_for_download().unwrap())
-                .map(|a| (a.path.as_str(), &a.download.url))
-        );
+        let total_size = files.iter()
+            .map(|(_, download, _)| download.size as u64)
+            .sum();
 
-        progress.begin("Downloading libraries", lib_downloads.len());
+        progress.begin_bytes("Downloading Java runtime", total_size);
 
-        for (i, (path, url)) in lib_downloads.iter().enumerate() {
-            progress.advance(i + 1);
-            self.download_library(path, url).await?;
+        let mut downloads = stream::iter(&files)
+            .map(|(path, download, executable)| async move {
+                self.download_runtime_file(path, download, *executable).await
+                    .map(|_| download.size as usize)
+            })
+            .buffer_unordered(env::get_download_jobs());
+
+        let mut downloaded = 0;
+        while let Some(result) = downloads.next().await {
+            downloaded += result?;
+            progress.advance(downloaded);
         }
 
         progress.end();
 
+        // links and empty directories after files, so link targets exist
+        for (path, file) in &manifest.files {
+            match file {
+                JavaRuntimeFile::Directory => fs::create_dir_all(runtime_dir.join(path))?,
+                JavaRuntimeFile::Link { target } => super::fs::make_symlink(target, runtime_dir.join(path))?,
+                JavaRuntimeFile::File { .. } => ()
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn download_runtime_file(&self,
+        path: &Path,
+        download: &AssetDownload,
+        executable: bool
+    ) -> Result<()> {
+        // skip files that already exist from a previous partial install
+        let is_complete = fs::metadata(path)
+            .is_ok_and(|m| m.len() == download.size as u64);
+
+        if !is_complete {
+            self.client.download_file(&download.url, path).await?;
+        }
+
+        if executable {
+            super::fs::set_executable(path)?;
+        }
+
         Ok(())
     }
 
+    pub async fn download_libraries(&self,
+        game_manifest: &GameManifest,
+        progress: &mut dyn Progress
+    ) -> Result<()> {
+        let mut lib_downloads = vec![
+            (get_client_jar_path(&game_manifest.id), game_manifest.downloads.client.url.clone())
+        ];
+
+        for lib in game_manifest.libraries.iter().filter(|lib| lib.has_rules_match()) {
+            lib_downloads.extend(lib.artifacts_for_download()?.iter()
+                .map(|a| (a.path.clone(), a.download.url.clone())));
+        }
+
+        self.download_library_files("Downloading libraries", lib_downloads, progress).await
+    }
+
     pub async fn download_loader_libraries(&self,
         forge_manifest: &ForgeManifest,
         progress: &mut dyn Progress
     ) -> Result<()> {
-        let mut downloads: Vec<&ForgeLibrary> = vec![];
+        let downloads: Vec<_> = loader_libraries(forge_manifest).iter()
+            .map(|lib| (lib.asset_path(), lib.download_url()))
+            .collect();
 
-        match &forge_manifest.dist {
-            ForgeDistribution::Legacy { jar_mods, fml_libs } => {
-                downloads.extend(jar_mods.iter());
-                if let Some(fml_libs) = fml_libs {
-                    downloads.extend(fml_libs.iter());
-                }
-            },
-            ForgeDistribution::Current { libraries, maven_files, .. } => {
-                downloads.extend(libraries.iter());
+        self.download_library_files("Downloading mod loader libraries", downloads, progress).await
+    }
 
-                if let Some(maven_files) = maven_files {
-                    downloads.extend(maven_files.iter());
-                }
-            }
+    /// Download `(path, url)` libraries concurrently, continuing past failed
+    /// downloads to report all of the failures at the end
+    async fn download_library_files(&self,
+        message: &'static str,
+        mut downloads: Vec<(String, String)>,
+        progress: &mut dyn Progress
+    ) -> Result<()> {
+        // concurrent downloads of the same file would write over each other
+        let mut paths = HashSet::new();
+        downloads.retain(|(path, _)| paths.insert(path.clone()));
+
+        if self.offline {
+            let missing: Vec<_> = downloads.iter()
+                .map(|(path, _)| self.libs_dir.join(path))
+                .filter(|path| !path.exists())
+                .collect();
+
+            return self.check_offline(&missing);
         }
 
-        progress.begin("Downloading mod loader libraries", downloads.len());
+        progress.begin(message, downloads.len());
 
-        for (i, (path, url)) in downloads.iter().map(|lib| (lib.asset_path(), lib.download_url())).enumerate() {
-            progress.advance(i + 1);
-            self.download_library(&path, &url).await?;
+        let mut results = stream::iter(&downloads)
+            .map(|(path, url)| async move { (path, self.download_library(path, url).await) })
+            .buffer_unordered(env::get_download_jobs());
+
+        let mut failed = vec![];
+        let mut count = 0;
+
+        while let Some((path, result)) = results.next().await {
+            if let Err(e) = result {
+                failed.push(format!("{path}: {e:#}"));
+            }
+
+            count += 1;
+            progress.advance(count);
         }
 
         progress.end();
 
+        if !failed.is_empty() {
+            bail!(Error::LibraryDownloadsFailed(failed.join("\n")));
+        }
+
         Ok(())
     }
 
@@ -229,7 +474,209 @@ impl AssetManager {
             return Ok(());
         }
 
-        self.client.download_file(url, &lib_file).await
+        self.download_with_retry(url, &lib_file).await
+    }
+
+    /// Download file, retrying connection errors, timeouts and server errors
+    /// with exponential backoff
+    async fn download_with_retry(&self, url: &str, file_path: &Path) -> Result<()> {
+        let mut delay = Duration::from_millis(500);
+        let mut attempt = 0;
+
+        loop {
+            let result = self.client.download_file(url, file_path).await;
+
+            let err = match result {
+                Ok(()) => return Ok(()),
+                Err(err) => err
+            };
+
+            if attempt >= env::get_download_retries() || !is_transient_error(&err) {
+                return Err(err);
+            }
+
+            futures_time::task::sleep(delay.into()).await;
+
+            delay *= 2;
+            attempt += 1;
+        }
+    }
+
+    /// Paths of shared files used to launch the game, directories include
+    /// all of their contents
+    pub fn referenced_paths(&self,
+        game_manifest: &GameManifest,
+        asset_manifest: &AssetManifest,
+        loader_manifest: Option<&ForgeManifest>
+    ) -> Result<Vec<PathBuf>> {
+        let asset_index_id = &game_manifest.asset_index.id;
+
+        let mut paths = vec![
+            self.indexes_dir().join(format!("{asset_index_id}.json")),
+            self.virtual_assets_dir(asset_index_id),
+            self.libs_dir.join(get_client_jar_path(&game_manifest.id))
+        ];
+
+        paths.extend(asset_manifest.objects.values()
+            .map(|obj| self.objects_dir().join(&obj.hash[0..2]).join(&obj.hash)));
+
+        for lib in game_manifest.libraries.iter().filter(|lib| lib.has_rules_match()) {
+            paths.extend(lib.artifacts_for_download()?.iter().map(|a| self.libs_dir.join(&a.path)));
+        }
+
+        if let Some(logging) = &game_manifest.logging {
+            paths.push(self.log_configs_dir().join(&logging.client.file.id));
+        }
+
+        if let Some(loader_manifest) = loader_manifest {
+            paths.extend(loader_libraries(loader_manifest).iter()
+                .map(|lib| self.libs_dir.join(lib.asset_path())));
+
+            if let ForgeDistribution::Legacy { .. } = loader_manifest.dist {
+                paths.push(modded_jar_path(&loader_manifest.version));
+            }
+
+            paths.extend(self.processor_output_paths(&game_manifest.id, loader_manifest)?);
+        }
+
+        Ok(paths)
+    }
+
+    /// Directories of the files written by the Forge installer processors,
+    /// which ForgeWrapper runs at the first launch of a version. The output
+    /// is shared by every instance of the same Minecraft and loader version.
+    pub fn processor_output_paths(&self,
+        mc_version: &str,
+        loader_manifest: &ForgeManifest
+    ) -> Result<Vec<PathBuf>> {
+        // only modern forge and neoforge ship an installer to run
+        let installers = match &loader_manifest.dist {
+            ForgeDistribution::Current { maven_files: Some(maven_files), .. } => maven_files,
+            _ => return Ok(vec![])
+        };
+
+        // patched client jar is written next to the installer
+        let mut paths: Vec<PathBuf> = installers.iter()
+            .filter_map(|lib| self.libs_dir.join(lib.asset_path()).parent().map(Path::to_path_buf))
+            .collect();
+
+        // deobfuscated client jars are versioned by minecraft and mappings
+        // version, i.e. `net/minecraft/client/1.20.1-20230612.114412`
+        let client_dir = self.libs_dir.join("net/minecraft/client");
+
+        if client_dir.is_dir() {
+            let prefix = format!("{mc_version}-");
+
+            for entry in fs::read_dir(&client_dir)? {
+                let entry = entry?;
+
+                if entry.file_type()?.is_dir() && entry.file_name().to_string_lossy().starts_with(&prefix) {
+                    paths.push(entry.path());
+                }
+            }
+        }
+
+        Ok(paths)
+    }
+
+    /// Re-hash the asset objects and libraries used to launch the game,
+    /// deleting files that don't match the manifest hash so they are
+    /// downloaded again; returns the paths of the deleted files
+    pub fn remove_corrupted(&self,
+        game_manifest: &GameManifest,
+        asset_manifest: &AssetManifest,
+        loader_manifest: Option<&ForgeManifest>,
+        progress: &mut dyn Progress
+    ) -> Result<Vec<PathBuf>> {
+        let mut files = vec![
+            (self.libs_dir.join(get_client_jar_path(&game_manifest.id)), &game_manifest.downloads.client.sha1)
+        ];
+
+        files.extend(asset_manifest.objects.values()
+            .map(|obj| (self.objects_dir().join(&obj.hash[0..2]).join(&obj.hash), &obj.hash)));
+
+        for lib in game_manifest.libraries.iter().filter(|lib| lib.has_rules_match()) {
+            files.extend(lib.artifacts_for_download()?.iter()
+                .map(|a| (self.libs_dir.join(&a.path), &a.download.sha1)));
+        }
+
+        // libraries from a maven url don't have a hash to check
+        if let Some(loader_manifest) = loader_manifest {
+            files.extend(loader_libraries(loader_manifest).iter()
+                .filter_map(|lib| lib.sha1().map(|sha1| (self.libs_dir.join(lib.asset_path()), sha1))));
+        }
+
+        // missing files are downloaded anyway, and objects can be shared
+        let mut paths = HashSet::new();
+        files.retain(|(path, _)| path.exists() && paths.insert(path.clone()));
+
+        progress.begin("Verifying files", files.len());
+
+        let mut removed = vec![];
+
+        for (i, (path, sha1)) in files.iter().enumerate() {
+            if !hash::sha1(path)?.eq_ignore_ascii_case(sha1) {
+                fs::remove_file(path)?;
+                removed.push(path.clone());
+            }
+
+            progress.advance(i + 1);
+        }
+
+        progress.end();
+
+        Ok(removed)
+    }
+
+    /// Delete shared libraries, assets and cached files not in `referenced`,
+    /// returning paths and sizes in bytes of the deleted files; with `dry_run`
+    /// files are listed but not deleted
+    pub fn collect_garbage(&self,
+        referenced: &HashSet<PathBuf>,
+        dry_run: bool
+    ) -> Result<Vec<(PathBuf, u64)>> {
+        let roots = [
+            self.libs_dir.clone(),
+            self.objects_dir(),
+            self.indexes_dir(),
+            self.assets_dir.join("virtual"),
+            self.log_configs_dir(),
+            self.versions_dir()
+        ];
+
+        let files = roots.iter()
+            .filter(|root| root.exists())
+            .flat_map(|root| WalkDir::new(root).into_iter())
+            // modded forge jars are the only files in the top of the cache dir
+            .chain(WalkDir::new(&self.cache_dir).max_depth(1));
+
+        let mut result = vec![];
+
+        for entry in files {
+            let entry = entry?;
+
+            if !entry.file_type().is_file()
+                || entry.path().ancestors().any(|p| referenced.contains(p))
+            {
+                continue;
+            }
+
+            let size = entry.metadata()?.len();
+
+            if !dry_run {
+                fs::remove_file(entry.path())?;
+            }
+
+            result.push((entry.into_path(), size));
+        }
+
+        if !dry_run {
+            for root in &roots {
+                remove_empty_dirs(root)?;
+            }
+        }
+
+        Ok(result)
     }
 
     pub fn copy_resources(&self,
@@ -284,6 +731,45 @@ impl AssetManager {
     }
 }
 
+/// File name of game manifest json for version of minecraft
+pub fn game_manifest_file_name(mc_version: &str) -> String {
+    format!("{mc_version}.json")
+}
+
+/// File name of mod loader manifest json
+pub fn loader_manifest_file_name(mod_loader: &ModLoader) -> String {
+    format!("{name}_{ver}.json", name = mod_loader.name, ver = mod_loader.version)
+}
+
+/// Read game manifest json file and apply library overrides
+pub fn load_game_manifest(path: &Path) -> Result<GameManifest> {
+    let version_file = fs::File::open(path)?;
+    let mut game_manifest: GameManifest = serde_json::from_reader(version_file)?;
+
+    apply_lib_overrides(&mut game_manifest)?;
+
+    Ok(game_manifest)
+}
+
+/// Read mod loader manifest json file and populate legacy FML libs
+pub fn load_loader_manifest(path: &Path) -> Result<ForgeManifest> {
+    let version_file = fs::File::open(path)?;
+    let mut forge_manifest: ForgeManifest = serde_json::from_reader(version_file)?;
+
+    populate_fml_libs(&mut forge_manifest)?;
+
+    Ok(forge_manifest)
+}
+
+/// Path of java executable relative to the runtime directory
+fn java_executable_path() -> &'static str {
+    match env::get_host_os() {
+        "osx" => "jre.bundle/Contents/Home/bin/java",
+        "windows" => "bin/javaw.exe",
+        _ => "bin/java"
+    }
+}
+
 /// Get path of minecraft client jar relative to shared libs directory
 pub fn get_client_jar_path(mc_version: &str) -> String {
     format!("com/mojang/minecraft/{mc_version}/minecraft-{mc_version}-client.jar")
@@ -292,13 +778,12 @@ pub fn get_client_jar_path(mc_version: &str) -> String {
 /// Make modded minecraft jar with forge, if it doesn't already exist, and
 /// return the path of the modded jar
 pub fn make_forge_modded_jar(
-    mc_jar_path: &String, forge_version: &String, jar_mods: &Vec<ForgeLibrary>
+    mc_jar_path: &String, forge_version: &str, jar_mods: &[ForgeLibrary]
 ) -> Result<PathBuf> {
-    let modded_jar = format!("minecraft+forge-{}.jar", forge_version);
-    let modded_jar_path = env::get_cache_dir().join(&modded_jar);
+    let modded_jar_path = modded_jar_path(forge_version);
     if !modded_jar_path.exists() {
         // path to vanilla `minecraft.jar`
-        let mc_jar_path = env::get_libs_dir().join(&mc_jar_path);
+        let mc_jar_path = env::get_libs_dir().join(mc_jar_path);
 
         // map forge jar_mods asset library paths
         let jar_mods: Vec<_> = jar_mods.iter()
@@ -316,25 +801,78 @@ pub fn make_forge_modded_jar(
     Ok(modded_jar_path)
 }
 
-pub fn dedup_libs(libs: &[String]) -> Result<Vec<&String>> {
+/// Errors worth retrying a download for, as opposed to i.e. 404 not found
+fn is_transient_error(err: &anyhow::Error) -> bool {
+    if let Some(err) = err.downcast_ref::<reqwest::Error>() {
+        return match err.status() {
+            Some(status) => status.is_server_error() || status == reqwest::StatusCode::TOO_MANY_REQUESTS,
+            None => err.is_timeout() || err.is_connect() || err.is_request() || err.is_body()
+        };
+    }
+
+    // stalled download from the read timeout
+    err.downcast_ref::<std::io::Error>()
+        .is_some_and(|e| e.kind() == std::io::ErrorKind::TimedOut)
+}
+
+/// Path of the minecraft jar modded with legacy forge jar mods
+pub fn modded_jar_path(forge_version: &str) -> PathBuf {
+    env::get_cache_dir().join(format!("minecraft+forge-{forge_version}.jar"))
+}
+
+/// Libraries of the mod loader downloaded to the shared libs directory
+fn loader_libraries(forge_manifest: &ForgeManifest) -> Vec<&ForgeLibrary> {
+    let mut libs: Vec<&ForgeLibrary> = vec![];
+
+    match &forge_manifest.dist {
+        ForgeDistribution::Legacy { jar_mods, fml_libs } => {
+            libs.extend(jar_mods.iter());
+            if let Some(fml_libs) = fml_libs {
+                libs.extend(fml_libs.iter());
+            }
+        },
+        ForgeDistribution::Current { libraries, maven_files, .. } => {
+            libs.extend(libraries.iter());
+
+            if let Some(maven_files) = maven_files {
+                libs.extend(maven_files.iter());
+            }
+        }
+    }
+
+    libs
+}
+
+/// Remove directories left empty under `root`, keeping `root` itself
+fn remove_empty_dirs(root: &Path) -> Result<()> {
+    let dirs = WalkDir::new(root)
+        .min_depth(1)
+        .contents_first(true)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_dir());
+
+    for dir in dirs {
+        if fs::read_dir(dir.path())?.next().is_none() {
+            fs::remove_dir(dir.path())?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Remove libraries with the same group, artifact and classifier, keeping
+/// the first position of each. Libraries of the mod loader replace vanilla
+/// libraries, otherwise the newest version is kept.
+pub fn dedup_libs<'a>(libs: &'a [String], loader_libs: &'a [String]) -> Result<Vec<&'a String>> {
+    let mut order = Vec::new();
     let mut lib_map = HashMap::new();
 
-    // native jars have the same artifact path and version as their
-    // companion jar and will get incorrectly removed in the dedup process
-    // this naive approach splits natives jars, assuming these jars will always
-    // have the substring "natives" in the path, and includes them after the
-    // dedup process is complete
-    let (natives, non_natives): (Vec<_>, Vec<_>) = libs.iter()
-        .partition(|l| l.contains("natives"));
-
-    for path in non_natives {
-        let mut parts = path.rsplitn(3, '/');
-
-        let (_, sversion, artifact_id) = (
-            parts.next().ok_or(Error::InvalidLibraryPath(path.clone()))?,
-            parts.next().ok_or(Error::InvalidLibraryPath(path.clone()))?,
-            parts.next().ok_or(Error::InvalidLibraryPath(path.clone()))?
-        );
+    let all_libs = libs.iter().map(|path| (path, false))
+        .chain(loader_libs.iter().map(|path| (path, true)));
+
+    for (path, from_loader) in all_libs {
+        let (key, sversion) = lib_path_key(path)?;
 
         // some paths don't have a valid version
         // e.g. "mmc2" -> io/github/zekerzhayard/ForgeWrapper/mmc2/ForgeWrapper-mmc2.jar
@@ -343,21 +881,58 @@ pub fn dedup_libs(libs: &[String]) -> Result<Vec<&String>> {
         let version = lenient_semver::parse(sversion)
             .unwrap_or(Version::new(9, 9, 9));
 
-        if let Some((existing_version, _)) = lib_map.get(artifact_id) {
-            if *existing_version < version {
-                lib_map.insert(artifact_id, (version, path));
+        match lib_map.get(&key) {
+            Some((existing_version, _, existing_from_loader)) => {
+                let replace = if from_loader != *existing_from_loader {
+                    from_loader
+                } else {
+                    *existing_version < version
+                };
+
+                if replace {
+                    lib_map.insert(key, (version, path, from_loader));
+                }
+            },
+            None => {
+                order.push(key);
+                lib_map.insert(key, (version, path, from_loader));
             }
-        } else {
-            lib_map.insert(artifact_id, (version, path));
         }
     }
 
-    Ok(lib_map.values()
-        .map(|(_, path)| *path)
-        .chain(natives)
+    Ok(order.iter()
+        .map(|key| lib_map[key].1)
         .collect())
 }
 
+/// Group, artifact and classifier of library `path` in the maven layout
+/// `<group>/<artifact>/<version>/<artifact>-<version>[-<classifier>].jar`,
+/// and the version
+fn lib_path_key(path: &str) -> Result<((&str, &str, &str), &str)> {
+    let mut parts = path.rsplitn(4, '/');
+
+    let (file_name, version, artifact_id) = (
+        parts.next().ok_or(Error::InvalidLibraryPath(path.to_string()))?,
+        parts.next().ok_or(Error::InvalidLibraryPath(path.to_string()))?,
+        parts.next().ok_or(Error::InvalidLibraryPath(path.to_string()))?
+    );
+
+    let group = parts.next().unwrap_or("");
+
+    let stem = file_name.rsplit_once('.')
+        .map_or(file_name, |(stem, _)| stem);
+
+    // native jars share the artifact and version of their companion jar,
+    // i.e. lwjgl-3.3.1.jar and lwjgl-3.3.1-natives-linux.jar; files that
+    // don't follow the layout are kept apart by the whole name
+    let classifier = match stem.strip_prefix(&format!("{artifact_id}-{version}")) {
+        Some(rest) => rest.trim_start_matches('-'),
+        None => stem
+    };
+
+    Ok(((group, artifact_id, classifier), version))
+}
+
 // This logic is taken from PrismLauncher meta data generator
 // https://github.com/PrismLauncher/meta/blob/44d7582f91ae87fdf9d99ef8715e6a5562b5a715/generateMojang.py
 // I understand this is in response to the nasty log4j vulnerability.
@@ -399,7 +974,27 @@ fn apply_lib_overrides(game_manifest: &mut GameManifest) -> Result<()> {
     Ok(())
 }
 
+/// Quilt loads mods against intermediary mappings, which are published per
+/// version of minecraft and aren't part of the loader manifest
+pub fn add_intermediary_library(loader_manifest: &mut ForgeManifest, mc_version: &str) {
+    if loader_manifest.uid != "org.quiltmc.quilt-loader" {
+        return;
+    }
+
+    if let ForgeDistribution::Current { ref mut libraries, .. } = loader_manifest.dist {
+        libraries.push(ForgeLibrary::Url {
+            name: format!("net.fabricmc:intermediary:{mc_version}"),
+            url: Some(String::from("https://maven.fabricmc.net"))
+        });
+    }
+}
+
 fn populate_fml_libs(forge_manifest: &mut ForgeManifest) -> Result<()> {
+    // only legacy forge distributions need fml libs
+    if !matches!(forge_manifest.dist, ForgeDistribution::Legacy { .. }) {
+        return Ok(());
+    }
+
     let mc_version = forge_manifest.get_minecraft_version()?;
     let mc_version_semver = lenient_semver::parse(&mc_version)
         .map_err(|_| Error::VersionParse { version: mc_version.clone() })
@@ -428,14 +1023,14 @@ mod tests {
     #[test]
     fn dedup_libs_simple() {
         let input = vec!["a/b/1.2.3/b-1.2.3.jar".to_string(), "a/b/1.2.4/b-1.2.4.jar".to_string()];
-        let result: Vec<_> = dedup_libs(&input).unwrap();
+        let result: Vec<_> = dedup_libs(&input, &[]).unwrap();
         assert_eq!(result, vec!["a/b/1.2.4/b-1.2.4.jar"]);
     }
 
     #[test]
     fn dedup_libs_semver_order() {
         let input = vec!["a/b/45.1.2/b-45.1.2.jar".to_string(), "a/b/45.1.16/b-45.1.16.jar".to_string()];
-        let result: Vec<_> = dedup_libs(&input).unwrap();
+        let result: Vec<_> = dedup_libs(&input, &[]).unwrap();
         assert_eq!(result, vec!["a/b/45.1.16/b-45.1.16.jar"]);
     }
 
@@ -445,7 +1040,7 @@ mod tests {
             "net/minecraftforge/forge/1.7.10-10.13.4.1566-1.7.10/forge-1.7.10-10.13.4.1566-1.7.10-universal.jar".to_string(),
             "net/minecraftforge/forge/1.7.10-10.13.4.1614-1.7.10/forge-1.7.10-10.13.4.1614-1.7.10-universal.jar".to_string()
         ];
-        let result: Vec<_> = dedup_libs(&input).unwrap();
+        let result: Vec<_> = dedup_libs(&input, &[]).unwrap();
         assert_eq!(result, vec!["net/minecraftforge/forge/1.7.10-10.13.4.1614-1.7.10/forge-1.7.10-10.13.4.1614-1.7.10-universal.jar"]);
     }
 
@@ -455,17 +1050,33 @@ mod tests {
             "org/lwjgl/lwjgl/3.3.1/lwjgl-3.3.1.jar".to_string(),
             "org/lwjgl/lwjgl/3.3.1/lwjgl-3.3.1-natives-linux.jar".to_string()
         ];
-        let result: Vec<_> = dedup_libs(&input).unwrap();
+        let result: Vec<_> = dedup_libs(&input, &[]).unwrap();
         assert_eq!(result.len(), 2);
     }
 
+    #[test]
+    fn dedup_libs_group_and_loader() {
+        let input = vec![
+            "com/google/guava/guava/32.1.2-jre/guava-32.1.2-jre.jar".to_string(),
+            "org/example/guava/1.0/guava-1.0.jar".to_string(),
+            "org/ow2/asm/asm/9.6/asm-9.6.jar".to_string()
+        ];
+        let loader = vec!["org/ow2/asm/asm/9.5/asm-9.5.jar".to_string()];
+        let result: Vec<_> = dedup_libs(&input, &loader).unwrap();
+        assert_eq!(result, vec![
+            "com/google/guava/guava/32.1.2-jre/guava-32.1.2-jre.jar",
+            "org/example/guava/1.0/guava-1.0.jar",
+            "org/ow2/asm/asm/9.5/asm-9.5.jar"
+        ]);
+    }
+
     #[test]
     fn dedup_invalid_version() {
         let input = vec![
             "io/github/zekerzhayard/ForgeWrapper/mmc2/ForgeWrapper-mmc2.jar".to_string(),
             "org/ow2/asm/asm/9.5/asm-9.5.jar".to_string()
         ];
-        let result: Vec<_> = dedup_libs(&input).unwrap();
+        let result: Vec<_> = dedup_libs(&input, &[]).unwrap();
         assert_eq!(result.len(), 2);
     }
 }
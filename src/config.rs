@@ -0,0 +1,270 @@
+/*
+ * Steve Launcher - A Minecraft Launcher
+ * Copyright (C) 2023 Josh Kropf <josh@slashdev.ca>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use anyhow::{bail, Result};
+use std::{fs, path::PathBuf};
+use toml_edit::{Array, Document, value};
+
+use crate::{env, Error, WindowResolution};
+
+const CONFIG_FILE: &str = "config.toml";
+
+enum ValueType {
+    /// Whitespace separated list of strings
+    List,
+    Integer,
+    Bool,
+    /// Window size formatted as `<width>x<height>`
+    Resolution,
+    /// One of the listed strings
+    Choice(&'static [&'static str]),
+    String
+}
+
+/// Config keys, with the environment variable that takes precedence over the
+/// config value
+const KEYS: &[(&str, &str, ValueType)] = &[
+    ("ca_certs", "STEVE_CA_CERTS", ValueType::String),
+    ("config_conflicts", "STEVE_CONFIG_CONFLICTS", ValueType::Choice(&["keep", "overwrite", "backup"])),
+    ("credential_store", "STEVE_CREDENTIAL_STORE", ValueType::Choice(&["keyring", "file", "encrypted-file"])),
+    ("download_jobs", "STEVE_DOWNLOAD_JOBS", ValueType::Integer),
+    ("download_retries", "STEVE_DOWNLOAD_RETRIES", ValueType::Integer),
+    ("downloads_dir", "STEVE_DOWNLOADS_DIR", ValueType::String),
+    ("fullscreen", "STEVE_FULLSCREEN", ValueType::Bool),
+    ("http_connect_timeout", "STEVE_HTTP_CONNECT_TIMEOUT", ValueType::Integer),
+    ("http_read_timeout", "STEVE_HTTP_READ_TIMEOUT", ValueType::Integer),
+    ("http_request_timeout", "STEVE_HTTP_REQUEST_TIMEOUT", ValueType::Integer),
+    ("instances_dir", "STEVE_INSTANCES_DIR", ValueType::String),
+    ("java_args", "STEVE_JAVA_ARGS", ValueType::List),
+    ("login_timeout", "STEVE_LOGIN_TIMEOUT", ValueType::Integer),
+    ("manifest_ttl", "STEVE_MANIFEST_TTL", ValueType::Integer),
+    ("mod_source", "STEVE_MOD_SOURCE", ValueType::Choice(&["modpacks.ch", "curseforge"])),
+    ("mojang_mirror", "STEVE_MOJANG_MIRROR", ValueType::String),
+    ("resolution", "STEVE_RESOLUTION", ValueType::Resolution),
+    ("user_agent", "STEVE_USER_AGENT", ValueType::String)
+];
+
+/// Launcher defaults read from `config.toml` in the data dir. Values are
+/// exposed to the rest of the launcher through environment variables.
+pub struct Config {
+    doc: Document,
+    path: PathBuf
+}
+
+impl Config {
+    pub fn load() -> Result<Self> {
+        Self::load_from(env::get_data_dir().join(CONFIG_FILE))
+    }
+
+    fn load_from(path: PathBuf) -> Result<Self> {
+        let doc = if path.exists() {
+            fs::read_to_string(&path)?.parse::<Document>()?
+        } else {
+            Document::new()
+        };
+
+        Ok(Self { doc, path })
+    }
+
+    pub fn keys() -> impl Iterator<Item = &'static str> {
+        KEYS.iter().map(|(key, _, _)| *key)
+    }
+
+    pub fn get(&self, key: &str) -> Result<Option<String>> {
+        let (_, _, value_type) = find_key(key)?;
+
+        let item = match self.doc.get(key) {
+            Some(item) => item,
+            None => return Ok(None)
+        };
+
+        let value = match value_type {
+            ValueType::List => item.as_array().map(|a| {
+                a.iter()
+                    .filter_map(|v| v.as_str())
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            }),
+            ValueType::Integer => item.as_integer().map(|i| i.to_string()),
+            ValueType::Bool => item.as_bool().map(|b| b.to_string()),
+            ValueType::Choice(choices) => match item.as_str() {
+                Some(val) if !choices.contains(&val) =>
+                    bail!(Error::InvalidConfigValue { key: key.to_string(), value: val.to_string() }),
+                val => val.map(String::from)
+            },
+            ValueType::Resolution | ValueType::String => item.as_str().map(String::from)
+        };
+
+        Ok(value)
+    }
+
+    pub fn set(&mut self, key: &str, val: &str) -> Result<()> {
+        let (_, _, value_type) = find_key(key)?;
+
+        self.doc[key] = match value_type {
+            ValueType::List => value(val.split_whitespace().collect::<Array>()),
+            ValueType::Integer => match val.parse::<i64>() {
+                Ok(i) if i > 0 => value(i),
+                _ => bail!(Error::InvalidConfigValue { key: key.to_string(), value: val.to_string() })
+            },
+            ValueType::Bool => match val.parse::<bool>() {
+                Ok(b) => value(b),
+                _ => bail!(Error::InvalidConfigValue { key: key.to_string(), value: val.to_string() })
+            },
+            ValueType::Resolution => value(val.parse::<WindowResolution>()?.to_string()),
+            ValueType::Choice(choices) => match choices.contains(&val) {
+                true => value(val),
+                false => bail!(Error::InvalidConfigValue { key: key.to_string(), value: val.to_string() })
+            },
+            ValueType::String => value(val)
+        };
+
+        self.save()
+    }
+
+    pub fn unset(&mut self, key: &str) -> Result<()> {
+        find_key(key)?;
+        self.doc.remove(key);
+        self.save()
+    }
+
+    /// Set environment variables from config values, leaving variables that
+    /// are already set in the environment
+    pub fn apply_env(&self) -> Result<()> {
+        for (var, value) in self.env_vars(|var| std::env::var_os(var).is_some())? {
+            std::env::set_var(var, value);
+        }
+
+        Ok(())
+    }
+
+    /// Environment variables and values of the config keys that are set,
+    /// leaving out variables where `is_set` is true
+    fn env_vars(&self, is_set: impl Fn(&str) -> bool) -> Result<Vec<(&'static str, String)>> {
+        let mut vars = Vec::new();
+
+        for (key, var, _) in KEYS {
+            if is_set(var) {
+                continue;
+            }
+
+            if let Some(value) = self.get(key)? {
+                vars.push((*var, value));
+            }
+        }
+
+        Ok(vars)
+    }
+
+    fn save(&self) -> Result<()> {
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+
+        Ok(super::fs::write_atomic(&self.path, self.doc.to_string())?)
+    }
+}
+
+fn find_key(key: &str) -> Result<&'static (&'static str, &'static str, ValueType)> {
+    match KEYS.iter().find(|(k, _, _)| *k == key) {
+        Some(entry) => Ok(entry),
+        None => bail!(Error::InvalidConfigKey(key.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_config(name: &str) -> Config {
+        let path = std::env::temp_dir()
+            .join(format!("steve-config-test-{name}-{}", std::process::id()))
+            .join(CONFIG_FILE);
+
+        let _ = fs::remove_file(&path);
+
+        Config::load_from(path).unwrap()
+    }
+
+    #[test]
+    fn set_get_unset() {
+        let mut config = test_config("set");
+
+        config.set("java_args", "-Xss2m  -XX:+UseG1GC").unwrap();
+        config.set("download_jobs", "4").unwrap();
+        config.set("fullscreen", "true").unwrap();
+        config.set("http_request_timeout", "120").unwrap();
+
+        // values are read back from the saved file
+        let mut config = Config::load_from(config.path.clone()).unwrap();
+
+        assert_eq!(config.get("java_args").unwrap().as_deref(), Some("-Xss2m -XX:+UseG1GC"));
+        assert_eq!(config.get("download_jobs").unwrap().as_deref(), Some("4"));
+        assert_eq!(config.get("fullscreen").unwrap().as_deref(), Some("true"));
+        assert_eq!(config.get("http_request_timeout").unwrap().as_deref(), Some("120"));
+        assert_eq!(config.get("resolution").unwrap(), None);
+
+        config.unset("download_jobs").unwrap();
+        assert_eq!(config.get("download_jobs").unwrap(), None);
+
+        fs::remove_dir_all(config.path.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn set_invalid_value() {
+        let mut config = test_config("invalid");
+
+        assert!(config.set("download_jobs", "0").is_err());
+        assert!(config.set("fullscreen", "yes").is_err());
+        assert!(config.set("mod_source", "modrinth").is_err());
+        assert!(config.set("config_conflicts", "bakup").is_err());
+        assert!(config.set("unknown_key", "value").is_err());
+        assert!(config.get("unknown_key").is_err());
+
+        assert!(!config.path.exists());
+    }
+
+    #[test]
+    fn load_invalid_choice() {
+        let config = test_config("load");
+
+        fs::create_dir_all(config.path.parent().unwrap()).unwrap();
+        fs::write(&config.path, "config_conflicts = \"bakup\"\n").unwrap();
+
+        let config = Config::load_from(config.path.clone()).unwrap();
+        let value = config.get("config_conflicts");
+
+        fs::remove_dir_all(config.path.parent().unwrap()).unwrap();
+
+        assert!(value.is_err());
+    }
+
+    #[test]
+    fn apply_env_keeps_existing_vars() {
+        let mut config = test_config("env");
+
+        config.set("mod_source", "curseforge").unwrap();
+        config.set("user_agent", "steve-test").unwrap();
+
+        let vars = config.env_vars(|var| var == "STEVE_USER_AGENT");
+
+        fs::remove_dir_all(config.path.parent().unwrap()).unwrap();
+
+        assert_eq!(vars.unwrap(), [("STEVE_MOD_SOURCE", "curseforge".to_string())]);
+    }
+}
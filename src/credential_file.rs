@@ -0,0 +1,265 @@
+/*
+ * Steve Launcher - A Minecraft Launcher
+ * Copyright (C) 2023 Josh Kropf <josh@slashdev.ca>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use anyhow::{bail, Result};
+use base64::{Engine, engine::general_purpose::STANDARD as BASE64};
+use ring::{aead, pbkdf2, rand::{SecureRandom, SystemRandom}};
+use serde::{Deserialize, Serialize};
+use std::{any::Any, collections::BTreeMap, fs, num::NonZeroU32, path::PathBuf};
+
+use crate::{env, Error};
+
+const PBKDF2_ITERATIONS: u32 = 100_000;
+
+/// Credentials keyed by keyring user name
+type Credentials = BTreeMap<String, String>;
+
+/// Keyring credential stored in `account.json` of the data directory, for
+/// systems without a keyring. The file holds every credential, encrypted with
+/// a key derived from a passphrase or the machine id unless stored as plain text.
+pub struct FileCredential {
+    path: PathBuf,
+    user: String,
+    /// Passphrase or machine id the file is encrypted with, `None` to store plain text
+    secret: Option<String>
+}
+
+#[derive(Deserialize, Serialize)]
+struct EncryptedFile {
+    salt: String,
+    nonce: String,
+    data: String
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum CredentialFile {
+    Encrypted(EncryptedFile),
+    Plain(Credentials)
+}
+
+impl FileCredential {
+    pub fn new(path: PathBuf, user: &str, secret: Option<String>) -> Self {
+        Self { path, user: user.to_string(), secret }
+    }
+
+    /// Credential for `user` when the `credential_store` config uses a file,
+    /// `None` for the system keyring
+    pub fn from_env(user: &str) -> Result<Option<Self>> {
+        if !env::get_credential_file() {
+            return Ok(None);
+        }
+
+        let secret = match env::get_credential_file_encrypted() {
+            true => Some(env::get_credential_passphrase()
+                .or_else(env::get_machine_id)
+                .ok_or(Error::CredentialKeyNotFound)?),
+            false => None
+        };
+
+        Ok(Some(Self::new(env::get_credential_path(), user, secret)))
+    }
+
+    /// Read all credentials, a plain text file is encrypted in place when
+    /// encryption is enabled
+    fn read(&self) -> Result<Credentials> {
+        if !self.path.exists() {
+            return Ok(Credentials::new());
+        }
+
+        let json = fs::read_to_string(&self.path)?;
+
+        match serde_json::from_str::<CredentialFile>(&json)? {
+            CredentialFile::Encrypted(file) => self.decrypt(&file),
+            CredentialFile::Plain(credentials) => {
+                if self.secret.is_some() {
+                    self.write(&credentials)?;
+                }
+
+                Ok(credentials)
+            }
+        }
+    }
+
+    fn write(&self, credentials: &Credentials) -> Result<()> {
+        if credentials.is_empty() {
+            if self.path.exists() {
+                fs::remove_file(&self.path)?;
+            }
+            return Ok(());
+        }
+
+        let json = serde_json::to_string(credentials)?;
+
+        let contents = match &self.secret {
+            Some(secret) => serde_json::to_string(&encrypt(secret, &json)?)?,
+            None => json
+        };
+
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        crate::fs::write_atomic(&self.path, contents)?;
+        crate::fs::set_owner_only(&self.path)?;
+
+        Ok(())
+    }
+
+    fn decrypt(&self, file: &EncryptedFile) -> Result<Credentials> {
+        let secret = match &self.secret {
+            Some(secret) => secret.clone(),
+            // encrypted by an earlier config, key from the passphrase or machine id
+            None => env::get_credential_passphrase()
+                .or_else(env::get_machine_id)
+                .ok_or(Error::CredentialKeyNotFound)?
+        };
+
+        let json = decrypt(&secret, file)?;
+
+        Ok(serde_json::from_str(&json)?)
+    }
+
+    fn update(&self, f: impl FnOnce(&mut Credentials) -> keyring::Result<()>) -> keyring::Result<()> {
+        let mut credentials = self.read().map_err(platform_failure)?;
+
+        f(&mut credentials)?;
+
+        self.write(&credentials).map_err(platform_failure)
+    }
+}
+
+impl keyring::credential::CredentialApi for FileCredential {
+    fn set_password(&self, password: &str) -> keyring::Result<()> {
+        self.update(|credentials| {
+            credentials.insert(self.user.clone(), password.to_string());
+            Ok(())
+        })
+    }
+
+    fn get_password(&self) -> keyring::Result<String> {
+        self.read().map_err(platform_failure)?
+            .remove(&self.user)
+            .ok_or(keyring::Error::NoEntry)
+    }
+
+    fn delete_password(&self) -> keyring::Result<()> {
+        self.update(|credentials| {
+            credentials.remove(&self.user)
+                .map(|_| ())
+                .ok_or(keyring::Error::NoEntry)
+        })
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+fn platform_failure(e: anyhow::Error) -> keyring::Error {
+    keyring::Error::PlatformFailure(e.into())
+}
+
+fn derive_key(secret: &str, salt: &[u8]) -> Result<aead::LessSafeKey> {
+    let mut key = [0u8; 32];
+
+    pbkdf2::derive(
+        pbkdf2::PBKDF2_HMAC_SHA256,
+        NonZeroU32::new(PBKDF2_ITERATIONS).unwrap(),
+        salt,
+        secret.as_bytes(),
+        &mut key
+    );
+
+    let key = aead::UnboundKey::new(&aead::CHACHA20_POLY1305, &key)
+        .map_err(|_| Error::CredentialEncrypt)?;
+
+    Ok(aead::LessSafeKey::new(key))
+}
+
+fn encrypt(secret: &str, json: &str) -> Result<EncryptedFile> {
+    let rng = SystemRandom::new();
+
+    // new salt and nonce for every write, the nonce is never reused with a key
+    let mut salt = [0u8; 16];
+    let mut nonce = [0u8; aead::NONCE_LEN];
+    rng.fill(&mut salt).map_err(|_| Error::CredentialEncrypt)?;
+    rng.fill(&mut nonce).map_err(|_| Error::CredentialEncrypt)?;
+
+    let key = derive_key(secret, &salt)?;
+
+    let mut data = json.as_bytes().to_vec();
+    key.seal_in_place_append_tag(aead::Nonce::assume_unique_for_key(nonce), aead::Aad::empty(), &mut data)
+        .map_err(|_| Error::CredentialEncrypt)?;
+
+    Ok(EncryptedFile {
+        salt: BASE64.encode(salt),
+        nonce: BASE64.encode(nonce),
+        data: BASE64.encode(data)
+    })
+}
+
+fn decrypt(secret: &str, file: &EncryptedFile) -> Result<String> {
+    let salt = BASE64.decode(&file.salt)?;
+    let nonce = BASE64.decode(&file.nonce)?;
+    let mut data = BASE64.decode(&file.data)?;
+
+    let Ok(nonce) = aead::Nonce::try_assume_unique_for_key(&nonce) else {
+        bail!(Error::CredentialDecrypt);
+    };
+
+    let key = derive_key(secret, &salt)?;
+
+    let Ok(json) = key.open_in_place(nonce, aead::Aad::empty(), &mut data) else {
+        bail!(Error::CredentialDecrypt);
+    };
+
+    Ok(String::from_utf8(json.to_vec())?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use keyring::credential::CredentialApi;
+
+    #[test]
+    fn plain_text_file_encrypted_on_read() {
+        let dir = std::env::temp_dir()
+            .join(format!("steve-credential-test-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+
+        let path = dir.join("account.json");
+        fs::write(&path, r#"{"steve:accounts":"{}"}"#).unwrap();
+
+        let credential = FileCredential::new(path.clone(), "steve:accounts", Some("secret".to_string()));
+        assert_eq!(credential.get_password().unwrap(), "{}");
+
+        let json = fs::read_to_string(&path).unwrap();
+        assert!(!json.contains("steve:accounts"));
+        assert_eq!(credential.get_password().unwrap(), "{}");
+
+        let wrong = FileCredential::new(path.clone(), "steve:accounts", Some("wrong".to_string()));
+        assert!(wrong.get_password().is_err());
+
+        credential.delete_password().unwrap();
+        assert!(!path.exists());
+        assert!(matches!(credential.get_password(), Err(keyring::Error::NoEntry)));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
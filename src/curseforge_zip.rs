@@ -18,6 +18,7 @@
 
 use anyhow::Result;
 use std::{fs::{self, File}, io, path::{Path, PathBuf}};
+use walkdir::WalkDir;
 use crate::{json::CurseForgePack, zip};
 
 pub struct CurseForgeZip {
@@ -47,7 +48,24 @@ impl CurseForgeZip {
 
 impl CurseForgeZip {
     pub fn copy_game_data(&self, game_dir: &Path) -> io::Result<()> {
-        super::fs::copy_dir_all(self.zip_temp_dir.join(&self.manifest.overrides), game_dir)
+        super::fs::copy_dir_all(self.game_data_dir(), game_dir)
+    }
+
+    /// Directory of the extracted pack overrides
+    pub fn game_data_dir(&self) -> PathBuf {
+        self.zip_temp_dir.join(&self.manifest.overrides)
+    }
+
+    /// Files `copy_game_data` would copy, relative to the game dir
+    pub fn game_data_files(&self) -> Vec<PathBuf> {
+        let overrides_dir = self.game_data_dir();
+
+        WalkDir::new(&overrides_dir)
+            .into_iter()
+            .filter_map(|e| e.ok())
+            .filter(|e| e.file_type().is_file())
+            .filter_map(|e| e.path().strip_prefix(&overrides_dir).ok().map(Path::to_path_buf))
+            .collect()
     }
 }
 
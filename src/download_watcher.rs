@@ -24,23 +24,39 @@ use std::{
 
 use crate::env;
 
+/// Suffixes of files browsers write to while a download is in progress
+const TEMP_DOWNLOAD_SUFFIXES: &[&str] = &[".part", ".crdownload", ".download", ".partial"];
+
+/// Check a downloaded file is the expected file, called with the file name
+/// and path of the download
+pub type DownloadCheck = Box<dyn Fn(&str, &Path) -> bool + Send + Sync>;
+
 pub struct DownloadWatcher {
-    pub watch_dir: PathBuf,
-    file_state: Arc<Mutex<HashMap<String, bool>>>
+    pub watch_dirs: Vec<PathBuf>,
+    /// Path of each downloaded file, `None` until the file is found
+    file_state: Arc<Mutex<HashMap<String, Option<PathBuf>>>>,
+    check: DownloadCheck
 }
 
 impl<'a> DownloadWatcher {
-    pub fn new<I>(files: I) -> Self
+    pub fn new<I>(files: I, check: DownloadCheck) -> Self
         where I: Iterator<Item = &'a str>
     {
-        let watch_dir = env::get_downloads_dir();
+        let watch_dirs = env::get_downloads_dirs();
         let file_state = files
-            .map(|f| (f.to_string(), watch_dir.join(f).exists()))
+            .map(|f| {
+                let path = watch_dirs.iter()
+                    .map(|dir| dir.join(f))
+                    .find(|path| is_download_finished(path) && check(f, path));
+
+                (f.to_string(), path)
+            })
             .collect();
 
         DownloadWatcher {
-            watch_dir,
-            file_state: Arc::new(Mutex::new(file_state))
+            watch_dirs,
+            file_state: Arc::new(Mutex::new(file_state)),
+            check
         }
     }
 
@@ -55,7 +71,9 @@ impl<'a> DownloadWatcher {
         scope.spawn(move || -> notify::Result<()> {
             // starting watcher inside thread so that it doesn't get dropped
             // from parent scope when method returns
-            watcher.watch(&self.watch_dir, RecursiveMode::NonRecursive)?;
+            for dir in self.watch_dirs.iter().filter(|d| d.is_dir()) {
+                watcher.watch(dir, RecursiveMode::NonRecursive)?;
+            }
 
             for result in watch_rx {
                 match result {
@@ -85,32 +103,72 @@ impl<'a> DownloadWatcher {
         Ok(watch_cancel)
     }
 
+    /// Record file as complete when it's one of the watched files, the browser
+    /// has finished writing it, and it passes the download check. Events for
+    /// browser temp files are matched to the file they're renamed to.
     fn on_file_complete(&self, path: &Path) -> bool {
-        let path_file_name = path.file_name()
-            .and_then(|p| p.to_str())
-            .unwrap();
+        let path = match without_temp_suffix(path) {
+            Some(path) => path,
+            None => path.to_path_buf()
+        };
+
+        let path_file_name = match path.file_name().and_then(|p| p.to_str()) {
+            Some(name) => name,
+            None => return false
+        };
 
         let mut file_state = self.file_state.lock().unwrap();
-        if let Some(value) = file_state.get_mut(path_file_name) {
-            *value = true;
-            true
-        } else {
-            false
+        match file_state.get_mut(path_file_name) {
+            // browsers send several events for each file, only signal once
+            Some(value) if value.is_none() => {
+                if !is_download_finished(&path) || !(self.check)(path_file_name, &path) {
+                    return false;
+                }
+
+                *value = Some(path.clone());
+                true
+            },
+            _ => false
         }
     }
 
-    pub fn is_file_complete(&self, file_name: &String) -> bool {
-        match self.file_state.lock().unwrap().get(file_name) {
-            Some(v) => *v,
-            None => false
-        }
+    pub fn is_file_complete(&self, file_name: &str) -> bool {
+        self.file_path(file_name).is_some()
+    }
+
+    /// Path of the downloaded file in one of the watched directories
+    pub fn file_path(&self, file_name: &str) -> Option<PathBuf> {
+        self.file_state.lock().unwrap()
+            .get(file_name)
+            .cloned()
+            .flatten()
     }
 
     pub fn is_all_complete(&self) -> bool {
-        self.file_state.lock().unwrap().values().all(|v| *v)
+        self.file_state.lock().unwrap().values().all(|v| v.is_some())
     }
 }
 
+/// File exists with content and the browser isn't still writing to a temp
+/// file next to it (Firefox creates an empty file for the download up front)
+fn is_download_finished(path: &Path) -> bool {
+    let is_empty = path.metadata().map_or(true, |m| !m.is_file() || m.len() == 0);
+
+    !is_empty && TEMP_DOWNLOAD_SUFFIXES.iter().all(|suffix| {
+        let mut temp_name = path.as_os_str().to_os_string();
+        temp_name.push(suffix);
+        !Path::new(&temp_name).exists()
+    })
+}
+
+fn without_temp_suffix(path: &Path) -> Option<PathBuf> {
+    let path_str = path.to_str()?;
+
+    TEMP_DOWNLOAD_SUFFIXES.iter()
+        .find_map(|suffix| path_str.strip_suffix(suffix))
+        .map(PathBuf::from)
+}
+
 pub enum WatcherMessage {
     AllComplete,
     FileComplete(PathBuf),
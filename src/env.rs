@@ -18,6 +18,10 @@
 
 use std::env;
 use std::path::PathBuf;
+use std::sync::OnceLock;
+use std::time::Duration;
+
+use crate::{ConfigConflicts, Error, WindowResolution};
 
 pub fn set_data_dir(path: &str) {
     env::set_var("STEVE_DATA_HOME", path)
@@ -44,6 +48,14 @@ pub fn get_data_dir() -> PathBuf {
     }
 }
 
+/// Directory containing instances, `$STEVE_INSTANCES_DIR` or the current directory
+pub fn get_instances_dir() -> PathBuf {
+    match env::var("STEVE_INSTANCES_DIR") {
+        Ok(var) => PathBuf::from(var),
+        Err(_) => PathBuf::from(".")
+    }
+}
+
 pub fn get_assets_dir() -> PathBuf {
     get_data_dir().join("assets")
 }
@@ -52,10 +64,41 @@ pub fn get_libs_dir() -> PathBuf {
     get_data_dir().join("libraries")
 }
 
+/// Lock file held while shared data is written, so concurrent invocations
+/// don't write over each other's downloads
+pub fn get_data_lock_path() -> PathBuf {
+    get_data_dir().join("steve.lock")
+}
+
 pub fn get_cache_dir() -> PathBuf {
     get_data_dir().join("cache")
 }
 
+/// Directory of `options.txt` and `servers.dat` copied into new instances
+/// on first launch
+pub fn get_template_dir() -> PathBuf {
+    get_data_dir().join("template")
+}
+
+pub fn get_runtimes_dir() -> PathBuf {
+    get_data_dir().join("runtimes")
+}
+
+/// Platform name used by mojang java runtime index, `None` when mojang
+/// doesn't publish runtimes for the host
+pub fn get_java_runtime_platform() -> Option<&'static str> {
+    match (env::consts::OS, env::consts::ARCH) {
+        ("linux", "x86_64") => Some("linux"),
+        ("linux", "x86") => Some("linux-i386"),
+        ("macos", "x86_64") => Some("mac-os"),
+        ("macos", "aarch64") => Some("mac-os-arm64"),
+        ("windows", "x86_64") => Some("windows-x64"),
+        ("windows", "x86") => Some("windows-x86"),
+        ("windows", "aarch64") => Some("windows-arm64"),
+        _ => None
+    }
+}
+
 pub fn get_host_os() -> &'static str {
     match env::consts::OS {
         // mojang json files uses "osx" instead of "macos" for os name
@@ -64,6 +107,32 @@ pub fn get_host_os() -> &'static str {
     }
 }
 
+/// OS version matched against `os.version` patterns in mojang json files,
+/// i.e. `10.0.22631` on Windows or `14.1.2` on macOS
+pub fn get_host_os_version() -> &'static str {
+    static VERSION: OnceLock<String> = OnceLock::new();
+
+    VERSION.get_or_init(|| os_info::get().version().to_string())
+}
+
+pub fn get_host_arch() -> &'static str {
+    match env::consts::ARCH {
+        // mojang json files use "x86" for 32 bit and "arm64" for 64 bit arm
+        "aarch64" => "arm64",
+        arch => arch
+    }
+}
+
+/// Pointer width substituted for `${arch}` in natives of older mojang json
+/// files, i.e. `natives-windows-${arch}`
+pub fn get_host_arch_bits() -> &'static str {
+    if cfg!(target_pointer_width = "64") {
+        "64"
+    } else {
+        "32"
+    }
+}
+
 pub fn get_package_name() -> &'static str {
     env!("CARGO_PKG_NAME")
 }
@@ -82,19 +151,177 @@ pub fn get_curse_api_key() -> String {
         .map_or(env!("CURSE_API_KEY").to_string(), |val| val)
 }
 
-pub fn get_downloads_dir() -> PathBuf {
+pub fn set_downloads_dirs(dirs: &[PathBuf]) {
+    if let Ok(paths) = env::join_paths(dirs) {
+        env::set_var("STEVE_DOWNLOADS_DIR", paths)
+    }
+}
+
+/// Directories watched for manually downloaded files
+pub fn get_downloads_dirs() -> Vec<PathBuf> {
+    // downloads directory resolve order:
+    // $STEVE_DOWNLOADS_DIR, $XDG_DOWNLOAD_DIR, $HOME/Downloads
+    // where $STEVE_DOWNLOADS_DIR can list several directories like $PATH
+    if let Some(var) = env::var_os("STEVE_DOWNLOADS_DIR") {
+        let dirs: Vec<_> = env::split_paths(&var)
+            .filter(|p| !p.as_os_str().is_empty())
+            .collect();
+
+        if !dirs.is_empty() {
+            return dirs;
+        }
+    }
+
     match env::var("XDG_DOWNLOAD_DIR") {
-        Ok(var) => PathBuf::from(var),
+        Ok(var) => vec![PathBuf::from(var)],
         Err(_) => {
             let home_dir = env::var("HOME")
                 .expect("HOME env var not found");
 
-            PathBuf::from(home_dir).join("Downloads")
+            vec![PathBuf::from(home_dir).join("Downloads")]
         }
     }
 }
 
+/// Game window size for instances that don't set their own
+pub fn get_default_resolution() -> WindowResolution {
+    env::var("STEVE_RESOLUTION").ok()
+        .and_then(|val| val.parse().ok())
+        .unwrap_or_default()
+}
+
+/// Launch instances that don't set their own preference fullscreen
+pub fn get_default_fullscreen() -> bool {
+    env::var("STEVE_FULLSCREEN").is_ok_and(|val| val == "true")
+}
+
+/// JVM arguments added to every instance, ahead of the instance `java_args`
+pub fn get_default_java_args() -> Vec<String> {
+    env::var("STEVE_JAVA_ARGS")
+        .map(|val| val.split_whitespace().map(String::from).collect())
+        .unwrap_or_default()
+}
+
+// timeout values are in seconds, invalid values fallback to the default
+fn get_duration_var(name: &str, default_secs: u64) -> Duration {
+    let secs = env::var(name).ok()
+        .and_then(|val| val.parse().ok())
+        .unwrap_or(default_secs);
+
+    Duration::from_secs(secs)
+}
+
+/// Time allowed to establish HTTP connection
+pub fn get_http_connect_timeout() -> Duration {
+    get_duration_var("STEVE_HTTP_CONNECT_TIMEOUT", 10)
+}
+
+/// Time allowed waiting on data while reading a download
+pub fn get_http_read_timeout() -> Duration {
+    get_duration_var("STEVE_HTTP_READ_TIMEOUT", 30)
+}
+
+/// Overall time allowed for API requests (does not apply to file downloads)
+pub fn get_http_request_timeout() -> Duration {
+    get_duration_var("STEVE_HTTP_REQUEST_TIMEOUT", 60)
+}
+
+/// Time allowed to enter the sign in code before it's considered expired
+pub fn get_login_timeout() -> Duration {
+    get_duration_var("STEVE_LOGIN_TIMEOUT", 300)
+}
+
+/// User agent of HTTP requests, identifying the launcher to API services
+pub fn get_user_agent() -> String {
+    env::var("STEVE_USER_AGENT")
+        .unwrap_or_else(|_| format!("jiggak/steve-launcher/{}", get_package_version()))
+}
+
+/// PEM file of CA certificates trusted in addition to the system certificates
+pub fn get_ca_certs_path() -> Option<PathBuf> {
+    env::var("STEVE_CA_CERTS").ok().map(PathBuf::from)
+}
+
+/// Base url of mirror for mojang downloads (i.e. `https://bmclapi2.bangbang93.com`)
+pub fn get_mojang_mirror() -> Option<String> {
+    env::var("STEVE_MOJANG_MIRROR").ok().filter(|val| !val.is_empty())
+}
+
+/// Time the version manifest and mod loader indexes are used from cache
+/// before checking for changes
+pub fn get_manifest_ttl() -> Duration {
+    get_duration_var("STEVE_MANIFEST_TTL", 3600)
+}
+
+/// Search CurseForge directly when installing modpacks, rather than modpacks.ch
+pub fn get_prefer_curseforge() -> bool {
+    env::var("STEVE_MOD_SOURCE").is_ok_and(|val| val == "curseforge")
+}
+
+pub fn set_config_conflicts(conflicts: &str) {
+    env::set_var("STEVE_CONFIG_CONFLICTS", conflicts)
+}
+
+/// How modpack updates handle config files the user changed, an error when
+/// the variable is set to an unknown value
+pub fn get_config_conflicts() -> Result<ConfigConflicts, Error> {
+    match env::var("STEVE_CONFIG_CONFLICTS") {
+        Ok(val) => val.parse(),
+        Err(_) => Ok(ConfigConflicts::default())
+    }
+}
+
+pub fn set_download_jobs(jobs: usize) {
+    env::set_var("STEVE_DOWNLOAD_JOBS", jobs.to_string())
+}
+
+/// Number of times a failed download or API request is retried
+pub fn get_download_retries() -> u32 {
+    env::var("STEVE_DOWNLOAD_RETRIES").ok()
+        .and_then(|val| val.parse().ok())
+        .unwrap_or(3)
+}
+
+/// Number of asset downloads to run at the same time
+pub fn get_download_jobs() -> usize {
+    env::var("STEVE_DOWNLOAD_JOBS").ok()
+        .and_then(|val| val.parse().ok())
+        .filter(|jobs| *jobs > 0)
+        .unwrap_or(8)
+}
+
+/// Store accounts in `account.json` of the data directory in place of the
+/// system keyring, for systems without one
+pub fn get_credential_file() -> bool {
+    env::var("STEVE_CREDENTIAL_STORE").is_ok_and(|val| val == "file" || val == "encrypted-file")
+}
+
+/// Encrypt `account.json` with the passphrase or the machine id
+pub fn get_credential_file_encrypted() -> bool {
+    env::var("STEVE_CREDENTIAL_STORE").is_ok_and(|val| val == "encrypted-file")
+}
+
+pub fn get_credential_path() -> PathBuf {
+    get_data_dir().join("account.json")
+}
+
+/// Passphrase `account.json` is encrypted with, in place of the machine id
+pub fn get_credential_passphrase() -> Option<String> {
+    env::var("STEVE_CREDENTIAL_PASSPHRASE").ok().filter(|val| !val.is_empty())
+}
+
+/// Machine id of systemd and dbus, `None` on systems without one
+pub fn get_machine_id() -> Option<String> {
+    ["/etc/machine-id", "/var/lib/dbus/machine-id"].iter()
+        .find_map(|path| std::fs::read_to_string(path).ok())
+        .map(|id| id.trim().to_string())
+        .filter(|id| !id.is_empty())
+}
+
+/// Name of the OS user, `USER` or `USERNAME` on Windows, "unknown" when
+/// neither is set (i.e. in some containers)
 pub fn get_user_name() -> String {
     env::var("USER")
-        .expect("USER env var not found")
+        .or_else(|_| env::var("USERNAME"))
+        .unwrap_or_else(|_| String::from("unknown"))
 }
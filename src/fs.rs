@@ -1,4 +1,9 @@
-use std::{fs, io, path::Path};
+use std::{
+    ffi::OsString, fs, io, path::{Path, PathBuf}, process, sync::atomic::{AtomicUsize, Ordering}, thread
+};
+use walkdir::WalkDir;
+
+use crate::Progress;
 
 /// Copy all files recursively from the source directory to destination directory
 pub fn copy_dir_all(src: impl AsRef<Path>, dst: impl AsRef<Path>) -> io::Result<()> {
@@ -17,6 +22,29 @@ pub fn copy_dir_all(src: impl AsRef<Path>, dst: impl AsRef<Path>) -> io::Result<
     Ok(())
 }
 
+/// Temporary path next to `path` to write to before renaming into place,
+/// renaming is only atomic within the same filesystem
+pub fn temp_path(path: &Path) -> PathBuf {
+    let mut file_name = OsString::from(".");
+    file_name.push(path.file_name().unwrap_or_default());
+    file_name.push(format!(".{}.tmp", process::id()));
+
+    path.with_file_name(file_name)
+}
+
+/// Write `contents` to a temporary file and rename it to `path`, a crash
+/// while writing can't leave a partially written file at `path`
+pub fn write_atomic(path: &Path, contents: impl AsRef<[u8]>) -> io::Result<()> {
+    let temp_path = temp_path(path);
+
+    if let Err(e) = fs::write(&temp_path, contents) {
+        let _ = fs::remove_file(&temp_path);
+        return Err(e);
+    }
+
+    fs::rename(&temp_path, path)
+}
+
 /// Iterate and copy `src_files` to `dst` directory
 pub fn copy_files<I, P>(src_files: I, dst: P) -> io::Result<()>
     where I: Iterator, I::Item: AsRef<Path>, P: AsRef<Path>
@@ -29,3 +57,148 @@ pub fn copy_files<I, P>(src_files: I, dst: P) -> io::Result<()>
 
     Ok(())
 }
+
+/// Total size in bytes of file, or all files in directory recursively
+pub fn path_size(path: impl AsRef<Path>) -> io::Result<u64> {
+    let mut size = 0;
+
+    for entry in WalkDir::new(path) {
+        let metadata = entry?.metadata()?;
+        if metadata.is_file() {
+            size += metadata.len();
+        }
+    }
+
+    Ok(size)
+}
+
+/// Total size in bytes of each of `paths`, see `path_size`, walking the paths
+/// in parallel; size is zero for paths that don't exist
+pub fn path_sizes<P: AsRef<Path> + Sync>(paths: &[P]) -> io::Result<Vec<u64>> {
+    let workers = thread::available_parallelism()
+        .map_or(4, |n| n.get())
+        .min(paths.len());
+
+    let next = AtomicUsize::new(0);
+    let mut sizes = vec![0; paths.len()];
+
+    thread::scope(|scope| {
+        let workers: Vec<_> = (0..workers)
+            .map(|_| scope.spawn(|| {
+                let mut results = vec![];
+
+                // each worker takes the next path until there are none left
+                loop {
+                    let i = next.fetch_add(1, Ordering::Relaxed);
+
+                    let path = match paths.get(i) {
+                        Some(path) => path.as_ref(),
+                        None => break
+                    };
+
+                    let size = if path.exists() { path_size(path) } else { Ok(0) };
+                    results.push((i, size));
+                }
+
+                results
+            }))
+            .collect();
+
+        for worker in workers {
+            for (i, size) in worker.join().unwrap() {
+                sizes[i] = size?;
+            }
+        }
+
+        Ok(sizes)
+    })
+}
+
+/// Delete file, or directory and all of its contents
+pub fn remove_path(path: impl AsRef<Path>) -> io::Result<()> {
+    if path.as_ref().is_dir() {
+        fs::remove_dir_all(path)
+    } else {
+        fs::remove_file(path)
+    }
+}
+
+/// Make file readable and writable by the owner only, no-op on platforms
+/// without file modes
+pub fn set_owner_only(path: impl AsRef<Path>) -> io::Result<()> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+
+        fs::set_permissions(path, fs::Permissions::from_mode(0o600))?;
+    }
+
+    #[cfg(not(unix))]
+    let _ = path;
+
+    Ok(())
+}
+
+/// Make file executable by everyone, no-op on platforms without file modes
+pub fn set_executable(path: impl AsRef<Path>) -> io::Result<()> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+
+        let mut permissions = fs::metadata(&path)?.permissions();
+        permissions.set_mode(permissions.mode() | 0o111);
+        fs::set_permissions(path, permissions)?;
+    }
+
+    #[cfg(not(unix))]
+    let _ = path;
+
+    Ok(())
+}
+
+/// Create symbolic link at `link` pointing to `target`, unless `link` exists
+pub fn make_symlink(target: impl AsRef<Path>, link: impl AsRef<Path>) -> io::Result<()> {
+    if fs::symlink_metadata(&link).is_ok() {
+        return Ok(());
+    }
+
+    #[cfg(unix)]
+    std::os::unix::fs::symlink(target, link)?;
+
+    #[cfg(windows)]
+    std::os::windows::fs::symlink_file(target, link)?;
+
+    Ok(())
+}
+
+/// Exclusive lock of a file, released when dropped
+pub struct FileLock {
+    _file: fs::File
+}
+
+/// Lock the file at `path` exclusively, creating it when missing; while
+/// another steve process holds the lock, a spinner is shown until it's released
+pub fn lock_file(path: &Path, progress: &mut dyn Progress) -> io::Result<FileLock> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    let file = fs::OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(path)?;
+
+    match file.try_lock() {
+        Ok(()) => (),
+        Err(fs::TryLockError::WouldBlock) => {
+            progress.begin_spinner("Waiting for other steve process");
+            let result = file.lock();
+            progress.end();
+            result?;
+        },
+        Err(fs::TryLockError::Error(e)) => return Err(e)
+    }
+
+    Ok(FileLock { _file: file })
+}
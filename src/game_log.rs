@@ -0,0 +1,229 @@
+/*
+ * Steve Launcher - A Minecraft Launcher
+ * Copyright (C) 2023 Josh Kropf <josh@slashdev.ca>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use chrono::{DateTime, Local, TimeZone, Utc};
+use std::{
+    fs::File, io::{self, BufRead, BufReader, Read, Write}, path::PathBuf,
+    process::{Child, Command, ExitStatus, Stdio}, sync::{Arc, Mutex}, thread::{self, JoinHandle}
+};
+
+/// Game process started by `Instance::launch`, with output written to the
+/// session log in the instance `logs` directory
+pub struct GameProcess {
+    pub child: Child,
+    pub log_path: PathBuf,
+
+    /// Time the process was started, crash reports older than this are
+    /// from earlier sessions
+    pub started: DateTime<Utc>,
+
+    output_threads: Vec<JoinHandle<()>>
+}
+
+impl GameProcess {
+    /// Spawn `cmd` with output written to `log_path`. When `detach` is false
+    /// output is also echoed to the terminal, otherwise output goes straight
+    /// to the log so the game can outlive steve.
+    pub(crate) fn spawn(cmd: &mut Command, log_path: PathBuf, detach: bool) -> io::Result<Self> {
+        let log_file = File::create(&log_path)?;
+        let started = Utc::now();
+
+        if detach {
+            cmd.stdout(log_file.try_clone()?);
+            cmd.stderr(log_file);
+
+            return Ok(Self { child: cmd.spawn()?, log_path, started, output_threads: vec![] });
+        }
+
+        cmd.stdout(Stdio::piped());
+        cmd.stderr(Stdio::piped());
+
+        let mut child = cmd.spawn()?;
+        let log_file = Arc::new(Mutex::new(log_file));
+
+        let output_threads = vec![
+            copy_output(child.stdout.take().unwrap(), log_file.clone(), io::stdout()),
+            copy_output(child.stderr.take().unwrap(), log_file, io::stderr())
+        ];
+
+        Ok(Self { child, log_path, started, output_threads })
+    }
+
+    /// Wait for the process to exit and its output to be written to the log
+    pub fn wait(&mut self) -> io::Result<ExitStatus> {
+        let status = self.child.wait()?;
+
+        for thread in self.output_threads.drain(..) {
+            // output is best effort, a panicked thread only loses log lines
+            let _ = thread.join();
+        }
+
+        Ok(status)
+    }
+}
+
+/// Outcome of a game session, from `Instance::wait_for_exit`
+pub struct GameExit {
+    pub status: ExitStatus,
+
+    /// Seconds played in the session, added to the instance play time
+    pub play_time_seconds: u64,
+
+    pub log_path: PathBuf,
+
+    /// Crash report or JVM error log written during the session
+    pub crash_report: Option<PathBuf>
+}
+
+impl GameExit {
+    pub fn crashed(&self) -> bool {
+        !self.status.success() || self.crash_report.is_some()
+    }
+}
+
+fn copy_output<R, W>(reader: R, log_file: Arc<Mutex<File>>, mut echo: W) -> JoinHandle<()>
+    where R: Read + Send + 'static, W: Write + Send + 'static
+{
+    thread::spawn(move || {
+        let mut parser = LogParser::default();
+        let mut reader = BufReader::new(reader);
+        let mut buf = Vec::new();
+
+        // read raw bytes, a line that isn't valid utf-8 must not end the copy
+        while matches!(reader.read_until(b'\n', &mut buf), Ok(n) if n > 0) {
+            let line = String::from_utf8_lossy(&buf);
+            let line = line.trim_end_matches(['\n', '\r']);
+
+            if let Some(line) = parser.parse_line(line) {
+                let _ = writeln!(log_file.lock().unwrap(), "{line}");
+                let _ = writeln!(echo, "{line}");
+            }
+
+            buf.clear();
+        }
+    })
+}
+
+/// Converts log4j XML events, written to the console by the game when it
+/// launches with the mojang logging config, into plain log lines
+#[derive(Default)]
+pub struct LogParser {
+    event: Option<String>
+}
+
+impl LogParser {
+    /// Parse line of game output. Returns the line to output, or `None` while
+    /// an XML event spanning multiple lines is incomplete.
+    pub fn parse_line(&mut self, line: &str) -> Option<String> {
+        let event = match self.event.take() {
+            Some(event) => event + line + "\n",
+            None if line.trim_start().starts_with("<log4j:Event") => format!("{line}\n"),
+            None => return Some(line.to_string())
+        };
+
+        if line.trim_end().ends_with("</log4j:Event>") {
+            Some(format_event(&event))
+        } else {
+            self.event = Some(event);
+            None
+        }
+    }
+}
+
+/// Format event like the vanilla `latest.log`, `[time] [thread/level]: message`
+fn format_event(xml: &str) -> String {
+    let time = attribute(xml, "timestamp")
+        .and_then(|t| t.parse().ok())
+        .and_then(|t| Local.timestamp_millis_opt(t).single())
+        .map(|t| t.format("%H:%M:%S").to_string())
+        .unwrap_or_default();
+
+    let thread = attribute(xml, "thread").unwrap_or_default();
+    let level = attribute(xml, "level").unwrap_or_default();
+    let message = cdata(xml, "<log4j:Message>").unwrap_or_default();
+
+    let mut line = format!("[{time}] [{thread}/{level}]: {message}");
+
+    if let Some(throwable) = cdata(xml, "<log4j:Throwable>") {
+        line.push('\n');
+        line.push_str(throwable.trim_end());
+    }
+
+    line
+}
+
+fn attribute(xml: &str, name: &str) -> Option<String> {
+    let start = xml.find(&format!(" {name}=\""))? + name.len() + 3;
+    let end = xml[start..].find('"')? + start;
+
+    Some(unescape(&xml[start..end]))
+}
+
+fn cdata<'a>(xml: &'a str, tag: &str) -> Option<&'a str> {
+    let content = &xml[xml.find(tag)? + tag.len()..];
+    let start = content.find("<![CDATA[")? + 9;
+    let end = content[start..].find("]]>")? + start;
+
+    Some(&content[start..end])
+}
+
+fn unescape(value: &str) -> String {
+    value.replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&amp;", "&")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_xml_event() {
+        let mut parser = LogParser::default();
+
+        let lines = [
+            r#"<log4j:Event logger="net.minecraft.client.Minecraft" timestamp="1700000000000" level="INFO" thread="Render thread">"#,
+            r#"  <log4j:Message><![CDATA[Setting user: Steve]]></log4j:Message>"#,
+            r#"</log4j:Event>"#
+        ];
+
+        assert_eq!(parser.parse_line(lines[0]), None);
+        assert_eq!(parser.parse_line(lines[1]), None);
+
+        let line = parser.parse_line(lines[2]).unwrap();
+        assert!(line.ends_with("[Render thread/INFO]: Setting user: Steve"));
+
+        assert_eq!(parser.parse_line("plain output").as_deref(), Some("plain output"));
+    }
+
+    #[test]
+    fn copy_output_past_invalid_utf8() {
+        let log_path = std::env::temp_dir().join(format!("steve-log-test-{}.log", std::process::id()));
+        let log_file = Arc::new(Mutex::new(File::create(&log_path).unwrap()));
+
+        let output: &[u8] = b"first\nbad \xff line\r\nlast\n";
+        copy_output(output, log_file, io::sink()).join().unwrap();
+
+        let log = std::fs::read_to_string(&log_path).unwrap();
+        std::fs::remove_file(&log_path).unwrap();
+
+        assert_eq!(log, "first\nbad \u{FFFD} line\nlast\n");
+    }
+}
@@ -0,0 +1,124 @@
+/*
+ * Steve Launcher - A Minecraft Launcher
+ * Copyright (C) 2023 Josh Kropf <josh@slashdev.ca>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use md5::{Digest, Md5};
+use sha1::Sha1;
+use sha2::Sha512;
+use std::{fs, io, path::Path};
+
+/// CurseForge file fingerprint, murmur2 hash of the file contents
+/// with whitespace bytes removed
+pub fn curseforge_fingerprint(path: &Path) -> io::Result<u32> {
+    let data = fs::read(path)?;
+    Ok(curseforge_fingerprint_bytes(&data))
+}
+
+fn curseforge_fingerprint_bytes(data: &[u8]) -> u32 {
+    let data: Vec<u8> = data.iter()
+        .copied()
+        // tab, newline, carriage return, space
+        .filter(|b| !matches!(b, 9 | 10 | 13 | 32))
+        .collect();
+
+    murmur2(&data, 1)
+}
+
+/// Hex encoded SHA-1 digest of the file contents
+pub fn sha1(path: &Path) -> io::Result<String> {
+    let data = fs::read(path)?;
+    Ok(to_hex(&Sha1::digest(data)))
+}
+
+/// Hex encoded SHA-1 and SHA-512 digests of the file contents
+pub fn sha1_sha512(path: &Path) -> io::Result<(String, String)> {
+    let data = fs::read(path)?;
+    Ok((to_hex(&Sha1::digest(&data)), to_hex(&Sha512::digest(&data))))
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+/// Offline player UUID, matching the server's `UUID.nameUUIDFromBytes("OfflinePlayer:<name>")`
+pub fn offline_player_uuid(name: &str) -> String {
+    let mut bytes: [u8; 16] = Md5::digest(format!("OfflinePlayer:{name}")).into();
+
+    // version 3 (name based md5) and IETF variant
+    bytes[6] = (bytes[6] & 0x0f) | 0x30;
+    bytes[8] = (bytes[8] & 0x3f) | 0x80;
+
+    to_hex(&bytes)
+}
+
+// https://github.com/aappleby/smhasher/blob/master/src/MurmurHash2.cpp
+fn murmur2(data: &[u8], seed: u32) -> u32 {
+    const M: u32 = 0x5bd1e995;
+    const R: u32 = 24;
+
+    let mut h = seed ^ data.len() as u32;
+
+    let mut chunks = data.chunks_exact(4);
+    for chunk in &mut chunks {
+        let mut k = u32::from_le_bytes(chunk.try_into().unwrap());
+
+        k = k.wrapping_mul(M);
+        k ^= k >> R;
+        k = k.wrapping_mul(M);
+
+        h = h.wrapping_mul(M);
+        h ^= k;
+    }
+
+    let tail = chunks.remainder();
+    if !tail.is_empty() {
+        for (i, b) in tail.iter().enumerate() {
+            h ^= (*b as u32) << (8 * i);
+        }
+        h = h.wrapping_mul(M);
+    }
+
+    h ^= h >> 13;
+    h = h.wrapping_mul(M);
+    h ^= h >> 15;
+
+    h
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn murmur2_known_values() {
+        assert_eq!(murmur2(b"", 1), 1540447798);
+        assert_eq!(murmur2(b"a", 1), 626045324);
+        assert_eq!(murmur2(b"abc", 1), 1621425345);
+        assert_eq!(murmur2(b"abcd", 1), 3376380438);
+        assert_eq!(murmur2(b"hello world", 1), 2213174766);
+    }
+
+    #[test]
+    fn offline_uuid_matches_server() {
+        assert_eq!(offline_player_uuid("Notch"), "b50ad385829d3141a2167e7d7539ba7f");
+    }
+
+    #[test]
+    fn fingerprint_ignores_whitespace() {
+        assert_eq!(curseforge_fingerprint_bytes(b"a b\tc\r\n"), murmur2(b"abc", 1));
+    }
+}
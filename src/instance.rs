@@ -17,53 +17,124 @@
  */
 
 use anyhow::{bail, Result};
+use chrono::{DateTime, Local, TimeZone, Utc};
+use futures_util::stream::{self, StreamExt};
 use std::{
-    collections::HashMap, fs, path::{Path, PathBuf}, process::{Child, Command}
+    collections::{BTreeMap, HashMap, HashSet}, fs, path::{Path, PathBuf}, process::Command, str::FromStr
 };
+use walkdir::WalkDir;
 
 use crate::{
-    account::Account, asset_client::AssetClient, asset_manager::{
+    account::Session, asset_client::AssetClient, asset_manager::{
         self, AssetManager, get_client_jar_path, make_forge_modded_jar
     },
-    CurseForgeZip, env, Error, json::{
-        CurseForgeFile, CurseForgeMod, ForgeDistribution, InstanceManifest,
-        ModLoader, ModpackVersionManifest
+    CurseForgeZip, env, Error, fs::FileLock, GameExit, GameProcess, hash, InstallHandler, InstanceRegistry, json::{
+        CurseForgeFile, CurseForgeFingerprintMatch, CurseForgeMinecraft, CurseForgeMod, CurseForgeModloader, CurseForgePack,
+        CurseForgePackFile, ForgeDistribution, ForgeManifest, GameManifest, InstanceManifest,
+        InstanceModpack, InstanceServer, LauncherProfile, LauncherProfiles, ModLoader, ModpackVersionManifest, ModrinthEnvSupport, ModrinthHashes,
+        ModrinthPack, ModrinthPackFile, ModrinthPackFileEnv, ModsLock, ModsLockFile, ModsLockSource
     },
-    Progress
+    nbt, prism_instance::{self, PrismInstance}, Progress, protocol, zip
 };
 
 const MANIFEST_FILE: &str = "manifest.json";
+const MANIFEST_TOML_FILE: &str = "manifest.toml";
+
+const MODS_LOCK_FILE: &str = "mods.lock.json";
+
+/// Held while steve writes to the instance, see `Instance::lock`
+const LOCK_FILE: &str = ".steve.lock";
+
+/// Suffix added to disabled mod files, mod loaders only load `.jar` files
+const DISABLED_SUFFIX: &str = ".disabled";
+
+/// Game dir files and directories left out of exported modpacks; these are
+/// either generated by steve at launch or specific to the player
+const EXPORT_EXCLUDE: &[&str] = &[
+    "crash-reports", "lib", "logs", "resources", "saves", "screenshots",
+    ".mixin.out", "usercache.json", "usernamecache.json"
+];
+
+/// File format of the instance manifest, the manifest is written back
+/// in the same format it was loaded from
+#[derive(Clone, Copy)]
+enum ManifestFormat {
+    Json,
+    Toml
+}
 
 pub struct Instance {
     pub manifest: InstanceManifest,
 
     /// Absolute path of the instance directory
-    pub dir: PathBuf
+    pub dir: PathBuf,
+
+    manifest_format: ManifestFormat
 }
 
 impl Instance {
     fn write_manifest(&self) -> Result<()> {
-        let manifest_path = self.dir.join(MANIFEST_FILE);
-        let manifest_json = serde_json::to_string_pretty(&self.manifest)?;
-        Ok(fs::write(manifest_path, manifest_json)?)
+        match self.manifest_format {
+            ManifestFormat::Json => {
+                let manifest_path = self.dir.join(MANIFEST_FILE);
+                let manifest_json = serde_json::to_string_pretty(&self.manifest)?;
+                Ok(super::fs::write_atomic(&manifest_path, manifest_json)?)
+            },
+            ManifestFormat::Toml => {
+                let manifest_path = self.dir.join(MANIFEST_TOML_FILE);
+                let mut doc = fs::read_to_string(&manifest_path)?
+                    .parse::<toml_edit::Document>()?;
+
+                update_toml_manifest(&mut doc, &self.manifest)?;
+
+                Ok(super::fs::write_atomic(&manifest_path, doc.to_string())?)
+            }
+        }
     }
 
-    fn new(instance_dir: &Path, manifest: InstanceManifest) -> Result<Instance> {
+    fn new(instance_dir: &Path, manifest: InstanceManifest, manifest_format: ManifestFormat) -> Result<Instance> {
         Ok(Instance {
             dir: fs::canonicalize(instance_dir)?,
-            manifest
+            manifest,
+            manifest_format
         })
     }
 
+    /// Find instance directories in the immediate sub-directories of
+    /// `root_dir`, sorted by name
+    pub fn list_dirs(root_dir: &Path) -> Result<Vec<PathBuf>> {
+        let mut dirs: Vec<PathBuf> = fs::read_dir(root_dir)?
+            .filter_map(|e| e.ok())
+            .map(|e| e.path())
+            .filter(|p| Self::exists(p))
+            .collect();
+
+        dirs.sort();
+
+        Ok(dirs)
+    }
+
     pub fn exists(instance_dir: &Path) -> bool {
         if !instance_dir.exists() || !instance_dir.is_dir() {
             return false;
         }
 
-        instance_dir.join(MANIFEST_FILE).exists()
+        instance_dir.join(MANIFEST_FILE).exists() ||
+            instance_dir.join(MANIFEST_TOML_FILE).exists()
     }
 
     pub async fn create(
+        instance_dir: &Path,
+        mc_version: &str,
+        mod_loader: Option<ModLoader>,
+        progress: &mut dyn Progress
+    ) -> Result<Instance> {
+        let _lock = super::fs::lock_file(&env::get_data_lock_path(), progress)?;
+        Self::create_locked(instance_dir, mc_version, mod_loader).await
+    }
+
+    /// Create instance while the caller holds the data dir lock
+    async fn create_locked(
         instance_dir: &Path,
         mc_version: &str,
         mod_loader: Option<ModLoader>
@@ -86,41 +157,73 @@ impl Instance {
         let instance = Instance::new(
             instance_dir,
             InstanceManifest {
+                name: None,
                 mc_version: mc_version.to_string(),
                 game_dir: "minecraft".to_string(),
                 java_path: None,
+                java_runtime: None,
                 java_args: None,
+                min_memory: None,
+                max_memory: None,
+                extra_game_args: None,
                 java_env: None,
                 mod_loader,
-                custom_jar: None
-            }
+                resolution: None,
+                fullscreen: None,
+                account: None,
+                modpack: None,
+                last_played: None,
+                play_time_seconds: None,
+                custom_jar: None,
+                dedup_libs: None,
+                options: None,
+                servers: None
+            },
+            ManifestFormat::Json
         )?;
 
         // write instance manifest.json file
         instance.write_manifest()?;
 
+        InstanceRegistry::register(&instance.dir, instance.manifest.name.as_deref())?;
+
         Ok(instance)
     }
 
     pub async fn install_pack_zip(&self,
         pack: &CurseForgeZip,
         progress: &mut dyn Progress
-    ) -> Result<(Vec<PathBuf>, Option<Vec<FileDownload>>)> {
+    ) -> Result<PackInstall> {
+        let previous = self.load_mods_lock()?;
+        let mut lock = ModsLock::default();
+
         // copy pack overrides to minecraft dir
-        pack.copy_game_data(&self.game_dir())?;
+        let config_conflicts = self.copy_pack_overrides(pack, &previous, &mut lock)?;
 
-        let client = AssetClient::new();
+        let client = AssetClient::new()?;
         let file_ids = pack.manifest.get_file_ids();
         let project_ids = pack.manifest.get_project_ids();
 
-        self.download_curseforge_files(&client, file_ids, project_ids, progress).await
+        let (extra_files, blocked) = self.download_curseforge_files(&client, file_ids, project_ids, &mut lock, progress)
+            .await?;
+
+        let mut install = self.track_user_files(&previous, &mut lock, extra_files, blocked);
+        install.config_conflicts = config_conflicts;
+
+        self.write_mods_lock(&lock)?;
+
+        Ok(install)
     }
 
     pub async fn install_pack(&self,
         pack: &ModpackVersionManifest,
         progress: &mut dyn Progress
-    ) -> Result<(Vec<PathBuf>, Option<Vec<FileDownload>>)> {
-        let client = AssetClient::new();
+    ) -> Result<PackInstall> {
+        let client = AssetClient::new()?;
+
+        let previous = self.load_mods_lock()?;
+        let mut lock = ModsLock::default();
+        let mut config_conflicts = Vec::new();
 
         let assets: Vec<_> = pack.files.iter()
             .filter(|f| f.url.is_some())
@@ -138,7 +241,7 @@ impl Instance {
                 client.download_file(f.url.as_ref().unwrap(), &dest_file_path).await?;
 
                 let pack = CurseForgeZip::load_zip(&dest_file_path)?;
-                pack.copy_game_data(&self.game_dir())?;
+                config_conflicts.extend(self.copy_pack_overrides(&pack, &previous, &mut lock)?);
 
                 continue;
             }
@@ -147,6 +250,15 @@ impl Instance {
                 .join(&f.path)
                 .join(&f.name);
 
+            lock.insert(ModsLockFile {
+                path: self.mods_lock_key(&dest_file_path),
+                source: ModsLockSource::Url {
+                    url: f.url.clone().unwrap(),
+                    sha1: f.sha1.clone()
+                },
+                disabled: false
+            });
+
             // save time/bandwidth and skip download if dest file exists
             if dest_file_path.exists() {
                 continue;
@@ -164,272 +276,2049 @@ impl Instance {
         let file_ids = mods.iter().map(|c| c.file_id).collect();
         let project_ids = mods.iter().map(|c| c.project_id).collect();
 
-        self.download_curseforge_files(&client, file_ids, project_ids, progress).await
+        let (extra_files, blocked) = self.download_curseforge_files(&client, file_ids, project_ids, &mut lock, progress)
+            .await?;
+
+        let mut install = self.track_user_files(&previous, &mut lock, extra_files, blocked);
+        install.config_conflicts = config_conflicts;
+
+        self.write_mods_lock(&lock)?;
+
+        Ok(install)
     }
 
-    async fn download_curseforge_files(&self,
-        client: &AssetClient,
-        file_ids: Vec<u64>,
-        project_ids: Vec<u64>,
+    /// Install `pack` in `instance_dir`, creating the instance when it doesn't
+    /// exist. Returns `None` when `handler` declines installing over an
+    /// existing instance.
+    pub async fn install_modpack(
+        instance_dir: &Path,
+        pack: &ModpackVersionManifest,
+        modpack: InstanceModpack,
+        handler: &mut dyn InstallHandler,
         progress: &mut dyn Progress
-    ) -> Result<(Vec<PathBuf>, Option<Vec<FileDownload>>)> {
-        let mut file_list = client.get_curseforge_file_list(&file_ids).await?;
-        let mut mod_list = client.get_curseforge_mods(&project_ids).await?;
+    ) -> Result<Option<Instance>> {
+        let mc_version = pack.get_minecraft_version()?;
+        let mod_loader = pack.get_mod_loader()?;
 
-        if file_list.len() != mod_list.len() {
-            bail!(Error::CurseFileListMismatch {
-                file_list_len: file_list.len(),
-                mod_list_len: mod_list.len()
-            });
-        }
+        let _lock = super::fs::lock_file(&env::get_data_lock_path(), progress)?;
 
-        // sort the lists so that we can zip them into list of pairs
-        file_list.sort_by(|a, b| a.mod_id.cmp(&b.mod_id));
-        mod_list.sort_by(|a, b| a.mod_id.cmp(&b.mod_id));
+        let mut instance = match Self::prepare_pack_instance(instance_dir, &mc_version, mod_loader, handler).await? {
+            Some(instance) => instance,
+            None => return Ok(None)
+        };
 
-        let file_downloads: Vec<_> = file_list.iter()
-            .zip(mod_list)
-            .map(|(f, m)| FileDownload::new(f, &m))
-            .collect();
+        instance.set_modpack(Some(modpack))?;
+        instance.set_pack_memory_defaults(pack)?;
 
-        // filter files that can be auto-downloaded, and those that must be manually downloaded
-        let (downloads, blocked): (Vec<_>, Vec<_>) = file_downloads.clone().into_iter()
-            .partition(|f| f.can_auto_download);
+        let install = instance.install_pack(pack, progress).await?;
+        instance.finish_pack_install(install, handler)?;
 
-        progress.begin("Downloading mods...", downloads.len());
+        Ok(Some(instance))
+    }
 
-        // create mods dir in case there are zero automated downloads with one or more manual downloads
-        fs::create_dir_all(self.mods_dir())?;
+    /// Install CurseForge zip `pack` in `instance_dir`, creating the instance
+    /// when it doesn't exist. Returns `None` when `handler` declines
+    /// installing over an existing instance.
+    pub async fn install_modpack_zip(
+        instance_dir: &Path,
+        pack: &CurseForgeZip,
+        handler: &mut dyn InstallHandler,
+        progress: &mut dyn Progress
+    ) -> Result<Option<Instance>> {
+        let mc_version = &pack.manifest.minecraft.version;
+        let mod_loader = pack.manifest.minecraft.get_mod_loader()?;
 
-        for (i, f) in downloads.iter().enumerate() {
-            progress.advance(i + 1);
+        let _lock = super::fs::lock_file(&env::get_data_lock_path(), progress)?;
 
-            let dest_file_path = self.get_file_path(f);
+        let mut instance = match Self::prepare_pack_instance(instance_dir, mc_version, mod_loader, handler).await? {
+            Some(instance) => instance,
+            None => return Ok(None)
+        };
 
-            // save time/bandwidth and skip download if dest file exists
-            if dest_file_path.exists() {
-                continue;
-            }
+        instance.set_modpack(Some(InstanceModpack {
+            source: String::from("zip"),
+            id: None,
+            name: pack.manifest.name.clone(),
+            version: pack.manifest.version.clone()
+        }))?;
 
-            client.download_file(&f.url, &dest_file_path).await?;
+        // zip manifests only have the recommended memory
+        if let Some(ram) = pack.manifest.minecraft.recommended_ram {
+            instance.set_memory_defaults(None, Some(ram))?;
         }
 
-        progress.end();
+        let install = instance.install_pack_zip(pack, progress).await?;
+        instance.finish_pack_install(install, handler)?;
 
-        let delete_files = [
-            list_extra_files(&self.mods_dir(), &file_downloads)?,
-            list_extra_files(&self.resource_pack_dir(), &file_downloads)?,
-            list_extra_files(&self.shader_pack_dir(), &file_downloads)?
-        ].concat();
+        Ok(Some(instance))
+    }
 
-        if !blocked.is_empty() {
-            Ok((delete_files, Some(blocked)))
-        } else {
-            Ok((delete_files, None))
+    /// Load the instance to install a pack over once `handler` confirms, or
+    /// create a new instance; the caller holds the data dir lock
+    async fn prepare_pack_instance(
+        instance_dir: &Path,
+        mc_version: &str,
+        mod_loader: Option<ModLoader>,
+        handler: &mut dyn InstallHandler
+    ) -> Result<Option<Instance>> {
+        if !Self::exists(instance_dir) {
+            return Ok(Some(Self::create_locked(instance_dir, mc_version, mod_loader).await?));
         }
-    }
 
-    pub fn load(instance_dir: &Path) -> Result<Instance> {
-        let manifest_path = instance_dir.join(MANIFEST_FILE);
-        if !manifest_path.exists() {
-            bail!(Error::InstanceNotFound(instance_dir.to_str().unwrap().to_string()))
+        let mut instance = Self::load(instance_dir)?;
+
+        if !handler.confirm_existing_instance(&instance)? {
+            return Ok(None);
         }
 
-        let json = fs::read_to_string(manifest_path)?;
-        let manifest = serde_json::from_str::<InstanceManifest>(json.as_str())?;
+        instance.set_mc_version(mc_version.to_string())?;
+        instance.set_mod_loader(mod_loader)?;
 
-        Instance::new(instance_dir, manifest)
+        Ok(Some(instance))
     }
 
-    pub fn set_mc_version(&mut self, mc_version: String) -> Result<()> {
-        self.manifest.mc_version = mc_version;
-        self.write_manifest()
-    }
+    /// Hand the decisions left after installing a pack to `handler`
+    fn finish_pack_install(&self, install: PackInstall, handler: &mut dyn InstallHandler) -> Result<()> {
+        if !install.config_conflicts.is_empty() {
+            handler.config_conflicts(self, &install.config_conflicts);
+        }
 
-    pub fn set_mod_loader(&mut self, mod_loader: Option<ModLoader>) -> Result<()> {
-        self.manifest.mod_loader = mod_loader;
-        self.write_manifest()
-    }
+        if !install.conflicts.is_empty() && handler.keep_user_files(self, &install.conflicts)? {
+            self.keep_user_files(&install.conflicts)?;
+        }
 
-    pub fn game_dir(&self) -> PathBuf {
-        self.dir.join(&self.manifest.game_dir)
-    }
+        if let Some(downloads) = install.blocked {
+            handler.download_blocked(self, downloads)?;
+        }
 
-    pub fn fml_libs_dir(&self) -> PathBuf {
-        self.game_dir().join("lib")
-    }
+        if !install.remove.is_empty() {
+            for path in handler.remove_files(self, &install.remove)? {
+                fs::remove_file(path)?;
+            }
+        }
 
-    pub fn mods_dir(&self) -> PathBuf {
-        self.game_dir().join("mods")
+        Ok(())
     }
 
-    pub fn resources_dir(&self) -> PathBuf {
-        self.game_dir().join("resources")
+    /// Copy pack overrides to the game dir, recording the hash of config files
+    /// in `lock`. Config files changed since the previous install are kept,
+    /// overwritten or backed up following `env::get_config_conflicts`, and
+    /// returned.
+    fn copy_pack_overrides(&self,
+        pack: &CurseForgeZip,
+        previous: &ModsLock,
+        lock: &mut ModsLock
+    ) -> Result<Vec<PathBuf>> {
+        self.copy_overrides(&pack.game_data_dir(), pack.game_data_files(), previous, lock, env::get_config_conflicts()?)
     }
 
-    pub fn resource_pack_dir(&self) -> PathBuf {
-        self.game_dir().join("resourcepacks")
+    fn copy_overrides(&self,
+        src_dir: &Path,
+        files: Vec<PathBuf>,
+        previous: &ModsLock,
+        lock: &mut ModsLock,
+        conflict_mode: ConfigConflicts
+    ) -> Result<Vec<PathBuf>> {
+        let game_dir = self.game_dir();
+
+        let mut conflicts = Vec::new();
+
+        for file in files {
+            let src_path = src_dir.join(&file);
+            let dest_path = game_dir.join(&file);
+
+            if file.starts_with("config") {
+                let key = file.to_string_lossy().replace('\\', "/");
+                let pack_hash = hash::sha1(&src_path)?;
+
+                // without a hash from the previous install there's no telling
+                // if the user changed the file, it's overwritten as before
+                let user_changed = match previous.config_hashes.get(&key) {
+                    Some(installed_hash) if dest_path.exists() => {
+                        let current_hash = hash::sha1(&dest_path)?;
+                        &current_hash != installed_hash && current_hash != pack_hash
+                    },
+                    _ => false
+                };
+
+                lock.config_hashes.insert(key, pack_hash);
+
+                if user_changed {
+                    conflicts.push(dest_path.clone());
+
+                    match conflict_mode {
+                        ConfigConflicts::Keep => continue,
+                        ConfigConflicts::Backup => {
+                            fs::copy(&dest_path, backup_path(&dest_path))?;
+                        },
+                        ConfigConflicts::Overwrite => { }
+                    }
+                }
+            }
+
+            if let Some(parent) = dest_path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+
+            fs::copy(&src_path, &dest_path)?;
+        }
+
+        Ok(conflicts)
     }
 
-    pub fn shader_pack_dir(&self) -> PathBuf {
-        self.game_dir().join("shaderpacks")
+    /// Record files the user added in the new lockfile so they aren't offered
+    /// for removal, and find user files that are now part of the pack
+    fn track_user_files(&self,
+        previous: &ModsLock,
+        lock: &mut ModsLock,
+        extra_files: Vec<PathBuf>,
+        blocked: Option<Vec<FileDownload>>
+    ) -> PackInstall {
+        let (remove, user_files) = self.split_user_files(previous, extra_files);
+
+        let (kept, conflicts): (Vec<_>, Vec<_>) = previous.user_files.iter()
+            .filter(|key| lock.get(key).is_some())
+            .partition(|key| previous.get(key).is_some());
+
+        lock.user_files = user_files.iter()
+            .map(|p| self.mods_lock_key(p))
+            .chain(kept.into_iter().cloned())
+            .collect();
+
+        lock.user_files.sort();
+
+        PackInstall {
+            remove,
+            conflicts: conflicts.into_iter().map(|key| self.game_dir().join(key)).collect(),
+            config_conflicts: Vec::new(),
+            blocked
+        }
     }
 
-    pub fn natives_dir(&self) -> PathBuf {
-        self.dir.join("natives")
+    /// Split extra files left after installing a pack into files from a
+    /// previous version of the pack, and files the user added. Without the
+    /// lockfile of a previous install there's no telling them apart, and all
+    /// extra files are assumed to be from the pack.
+    fn split_user_files(&self, previous: &ModsLock, extra_files: Vec<PathBuf>) -> (Vec<PathBuf>, Vec<PathBuf>) {
+        extra_files.into_iter().partition(|path| {
+            let key = self.mods_lock_key(path);
+
+            !previous.user_files.contains(&key)
+                && (previous.files.is_empty() || previous.get(&key).is_some())
+        })
     }
 
-    pub fn get_file_type_dir(&self, file_type: &FileType) -> PathBuf {
-        match file_type {
-            FileType::Mod => self.mods_dir(),
-            FileType::Resource => self.resource_pack_dir(),
-            FileType::Shaders => self.shader_pack_dir()
+    /// Keep files the user added that are also part of the pack, so they
+    /// aren't removed when a later version of the pack drops them
+    pub fn keep_user_files(&self, files: &[PathBuf]) -> Result<()> {
+        let mut lock = self.load_mods_lock()?;
+
+        for path in files {
+            let key = self.mods_lock_key(path);
+            if !lock.user_files.contains(&key) {
+                lock.user_files.push(key);
+            }
         }
-    }
 
-    pub fn get_file_path(&self, file: &FileDownload) -> PathBuf {
-        self.get_file_type_dir(&file.file_type).join(&file.file_name)
+        lock.user_files.sort();
+
+        self.write_mods_lock(&lock)
     }
 
-    pub fn install_file(&self, file: &FileDownload, src_path: &Path) -> std::io::Result<()> {
-        let dest_file = self.get_file_path(file);
-        fs::copy(src_path, dest_file)?;
-        Ok(())
+    /// Files that `install_pack_zip` would add, replace or remove, without
+    /// changing the instance
+    pub async fn pack_zip_changes(&self, pack: &CurseForgeZip) -> Result<PackChanges> {
+        let client = AssetClient::new()?;
+
+        let mut changes = PackChanges::default();
+        self.game_data_changes(pack, &mut changes);
+
+        let file_ids = pack.manifest.get_file_ids();
+        let project_ids = pack.manifest.get_project_ids();
+
+        self.curseforge_changes(&client, &file_ids, &project_ids, &mut changes).await?;
+
+        Ok(changes)
     }
 
-    pub async fn launch(&self, progress: &mut dyn Progress) -> Result<Child> {
-        let account = Account::load_with_tokens().await?;
+    /// Files that `install_pack` would add, replace or remove, without
+    /// changing the instance
+    pub async fn pack_changes(&self, pack: &ModpackVersionManifest) -> Result<PackChanges> {
+        let client = AssetClient::new()?;
 
-        let profile = account.fetch_profile().await?;
+        let mut changes = PackChanges::default();
 
-        let assets = AssetManager::new()?;
+        for f in pack.files.iter().filter(|f| f.url.is_some()) {
+            if f.file_type == "cf-extract" {
+                let dest_file_path = std::env::temp_dir().join(&f.name);
+                client.download_file(f.url.as_ref().unwrap(), &dest_file_path).await?;
 
-        let game_manifest = assets.get_game_manifest(&self.manifest.mc_version).await?;
-        let asset_manifest = assets.get_asset_manfiest(&game_manifest).await?;
+                let pack = CurseForgeZip::load_zip(&dest_file_path)?;
+                self.game_data_changes(&pack, &mut changes);
 
-        let loader_manifest = match &self.manifest.mod_loader {
-            Some(mod_loader) => Some(assets.get_loader_manifest(mod_loader).await?),
-            None => None
-        };
+                continue;
+            }
 
-        assets.download_assets(&asset_manifest, progress).await?;
-        assets.download_libraries(&game_manifest, progress).await?;
+            let dest_file_path = self.game_dir()
+                .join(&f.path)
+                .join(&f.name);
 
-        if let Some(loader_manifest) = &loader_manifest {
-            assets.download_loader_libraries(loader_manifest, progress).await?;
+            // existing files are skipped by the install
+            if !dest_file_path.exists() {
+                changes.added.push(dest_file_path);
+            }
         }
 
-        let resources_dir = if asset_manifest.is_virtual.unwrap_or(false) {
-            Some(assets.virtual_assets_dir(&game_manifest.asset_index.id))
-        } else if asset_manifest.map_to_resources.unwrap_or(false) {
-            Some(self.resources_dir())
-        } else {
-            None
-        };
+        let (file_ids, project_ids): (Vec<_>, Vec<_>) = pack.files.iter()
+            .filter_map(|f| f.curseforge.as_ref())
+            .map(|c| (c.file_id, c.project_id))
+            .unzip();
 
-        if let Some(resources_dir) = &resources_dir {
-            assets.copy_resources(&asset_manifest, resources_dir, progress)?;
+        self.curseforge_changes(&client, &file_ids, &project_ids, &mut changes).await?;
+
+        Ok(changes)
+    }
+
+    fn game_data_changes(&self, pack: &CurseForgeZip, changes: &mut PackChanges) {
+        let game_dir = self.game_dir();
+
+        for path in pack.game_data_files() {
+            let path = game_dir.join(path);
+
+            if path.exists() {
+                changes.replaced.push(path);
+            } else {
+                changes.added.push(path);
+            }
         }
+    }
 
-        assets.extract_natives(&game_manifest, &self.natives_dir(), progress)?;
+    async fn curseforge_changes(&self,
+        client: &AssetClient,
+        file_ids: &Vec<u64>,
+        project_ids: &Vec<u64>,
+        changes: &mut PackChanges
+    ) -> Result<()> {
+        if file_ids.is_empty() {
+            return Ok(());
+        }
 
-        let mut cmd = LaunchCommand::new(self);
-        fs::create_dir_all(self.game_dir())?;
+        let file_downloads = fetch_curseforge_downloads(client, file_ids, project_ids).await?;
 
-        let mut main_jar: String = get_client_jar_path(&game_manifest.id);
+        changes.added.extend(file_downloads.iter()
+            .map(|f| self.get_file_path(f))
+            .filter(|p| !p.exists()));
 
-        if let Some(loader_manifest) = &loader_manifest {
-            match &loader_manifest.dist {
-                // legacy forge distributions required modifying the `minecraft.jar` file
-                ForgeDistribution::Legacy { jar_mods, fml_libs } => {
-                    main_jar = make_forge_modded_jar(&main_jar, &loader_manifest.version, &jar_mods)
-                        ?.to_string_lossy().to_string();
+        let extra_files = [
+            list_extra_files(&self.mods_dir(), &file_downloads)?,
+            list_extra_files(&self.resource_pack_dir(), &file_downloads)?,
+            list_extra_files(&self.shader_pack_dir(), &file_downloads)?
+        ].concat();
 
-                    // forge will throw an error on startup attempting to download
-                    // these libraries (404 not found), unless they already exist
-                    if let Some(fml_libs) = fml_libs {
-                        super::fs::copy_files(
-                            fml_libs.iter()
-                                .map(|l| env::get_libs_dir().join(l.asset_path())),
-                            self.fml_libs_dir()
-                        )?;
-                    }
+        let (remove, _) = self.split_user_files(&self.load_mods_lock()?, extra_files);
+        changes.removed.extend(remove);
 
-                    cmd.arg("-Dminecraft.applet.TargetDirectory=${game_directory}");
-                    cmd.arg("-Djava.library.path=${natives_directory}");
-                    cmd.arg("-Dfml.ignoreInvalidMinecraftCertificates=true");
-                    cmd.arg("-Dfml.ignorePatchDiscrepancies=true");
-                    cmd.arg("-cp").arg("${classpath}");
-                    cmd.arg(game_manifest.main_class);
+        Ok(())
+    }
 
-                    if let Some(args) = game_manifest.minecraft_arguments {
-                        cmd.args(args.split(' '));
-                    }
-                },
-                ForgeDistribution::Current { main_class, minecraft_arguments, .. } => {
-                    cmd.arg("-Djava.library.path=${natives_directory}");
-                    cmd.arg("-cp").arg("${classpath}");
-                    cmd.arg(main_class);
+    /// Reinstall files recorded in the mods lockfile that are missing from the
+    /// game dir, or that don't match the recorded hash. Returns the number of
+    /// files downloaded, and the files that must be downloaded manually.
+    pub async fn sync_mods(&self,
+        progress: &mut dyn Progress
+    ) -> Result<(usize, Option<Vec<FileDownload>>)> {
+        let _lock = self.lock(progress)?;
 
-                    if let Some(args) = minecraft_arguments {
-                        cmd.args(args.split(' '));
-                    } else if let Some(args) = game_manifest.minecraft_arguments {
-                        cmd.args(args.split(' '));
-                    }
+        let client = AssetClient::new()?;
+        let lock = self.load_mods_lock()?;
+
+        let game_dir = self.game_dir();
+
+        let missing = stale_lock_files(&game_dir, &lock)?;
+
+        // remove corrupted or replaced files so they aren't left next to the
+        // re-downloaded file with or without the disabled suffix
+        for f in &missing {
+            let path = game_dir.join(&f.path);
+            for path in [with_disabled_suffix(&path), path] {
+                if path.exists() {
+                    fs::remove_file(path)?;
                 }
             }
+        }
 
-            if let Some(tweaks) = &loader_manifest.tweakers {
-                cmd.arg("--tweakClass").arg(tweaks.first().unwrap());
+        let mut count = 0;
+
+        let urls: Vec<_> = missing.iter()
+            .filter_map(|f| match &f.source {
+                ModsLockSource::Url { url, sha1 } => Some((f, url, sha1)),
+                _ => None
+            })
+            .collect();
+
+        progress.begin("Downloading files...", urls.len());
+
+        for (i, (f, url, sha1)) in urls.iter().enumerate() {
+            progress.advance(i + 1);
+
+            let mut dest_file_path = game_dir.join(&f.path);
+            if f.disabled {
+                dest_file_path = with_disabled_suffix(&dest_file_path);
             }
 
-        // newer versions of minecraft
-        } else if let Some(args) = game_manifest.arguments {
-            cmd.args(args.jvm.matched_args());
-            cmd.arg(game_manifest.main_class);
-            cmd.args(args.game.matched_args());
+            client.download_file(url, &dest_file_path).await?;
 
-        // older version of minecraft
-        } else if let Some(args) = game_manifest.minecraft_arguments {
-            // older version don't include JVM args in manifest
-            cmd.arg("-Djava.library.path=${natives_directory}");
-            cmd.arg("-cp").arg("${classpath}");
-            cmd.arg(game_manifest.main_class);
-            cmd.args(args.split(' '));
+            // the file at the url changed since the lockfile was written
+            if !hash::sha1(&dest_file_path)?.eq_ignore_ascii_case(sha1) {
+                fs::remove_file(&dest_file_path)?;
+                bail!(Error::LockHashMismatch(f.path.clone()));
+            }
+
+            count += 1;
         }
 
-        cmd.arg("--width").arg("854");
-        cmd.arg("--height").arg("480");
+        progress.end();
 
-        if let Some(custom_jar) = &self.manifest.custom_jar {
-            main_jar = self.dir.join(custom_jar).to_string_lossy().to_string();
+        let (file_ids, project_ids): (Vec<_>, Vec<_>) = missing.iter()
+            .filter_map(|f| match &f.source {
+                ModsLockSource::CurseForge { file_id, project_id, .. } => Some((*file_id, *project_id)),
+                _ => None
+            })
+            .unzip();
+
+        if file_ids.is_empty() {
+            return Ok((count, None));
         }
 
-        let mut libs = vec![main_jar];
+        let file_downloads = fetch_curseforge_downloads(&client, &file_ids, &project_ids).await?;
 
-        libs.extend(
-            game_manifest.libraries.iter()
-                .filter(|lib| lib.has_rules_match())
-                .filter_map(|lib| lib.downloads.artifact.as_ref())
-                .map(|a| a.path.clone())
+        let (mut downloads, blocked): (Vec<_>, Vec<_>) = file_downloads.into_iter()
+            .partition(|f| f.can_auto_download);
+
+        let (mirrored, blocked) = find_modrinth_downloads(&client, blocked).await;
+        downloads.extend(mirrored);
+
+        progress.begin("Downloading mods...", downloads.len());
+
+        for (i, f) in downloads.iter().enumerate() {
+            progress.advance(i + 1);
+
+            let dest_file_path = self.get_file_path(f);
+            download_verified(&client, f, &dest_file_path).await?;
+            count += 1;
+
+            let is_disabled = lock.get(&self.mods_lock_key(&dest_file_path))
+                .is_some_and(|l| l.disabled);
+
+            if is_disabled {
+                self.disable_mod(&dest_file_path)?;
+            }
+        }
+
+        progress.end();
+
+        if blocked.is_empty() {
+            Ok((count, None))
+        } else {
+            Ok((count, Some(blocked)))
+        }
+    }
+
+    pub fn mods_lock_path(&self) -> PathBuf {
+        self.dir.join(MODS_LOCK_FILE)
+    }
+
+    fn load_mods_lock(&self) -> Result<ModsLock> {
+        let path = self.mods_lock_path();
+
+        if !path.exists() {
+            return Ok(ModsLock::default());
+        }
+
+        let file = fs::File::open(path)?;
+        Ok(serde_json::from_reader(file)?)
+    }
+
+    fn write_mods_lock(&self, lock: &ModsLock) -> Result<()> {
+        Ok(super::fs::write_atomic(&self.mods_lock_path(), serde_json::to_string_pretty(lock)?)?)
+    }
+
+    /// Path of file relative to game dir, without `.disabled` suffix
+    fn mods_lock_key(&self, path: &Path) -> String {
+        let relative = path.strip_prefix(self.game_dir()).unwrap_or(path);
+        let key = relative.to_string_lossy().replace('\\', "/");
+
+        match key.strip_suffix(DISABLED_SUFFIX) {
+            Some(key) => key.to_string(),
+            None => key
+        }
+    }
+
+    async fn download_curseforge_files(&self,
+        client: &AssetClient,
+        file_ids: Vec<u64>,
+        project_ids: Vec<u64>,
+        lock: &mut ModsLock,
+        progress: &mut dyn Progress
+    ) -> Result<(Vec<PathBuf>, Option<Vec<FileDownload>>)> {
+        progress.begin_spinner("Fetching mod list...");
+        let file_downloads = fetch_curseforge_downloads(client, &file_ids, &project_ids).await?;
+        progress.end();
+
+        for f in &file_downloads {
+            lock.insert(ModsLockFile {
+                path: self.mods_lock_key(&self.get_file_path(f)),
+                source: ModsLockSource::CurseForge {
+                    project_id: f.project_id,
+                    file_id: f.file_id,
+                    fingerprint: f.fingerprint
+                },
+                disabled: false
+            });
+        }
+
+        // filter files that can be auto-downloaded, and those that must be manually downloaded
+        let (mut downloads, blocked): (Vec<_>, Vec<_>) = file_downloads.clone().into_iter()
+            .partition(|f| f.can_auto_download);
+
+        let (mirrored, blocked) = find_modrinth_downloads(client, blocked).await;
+        downloads.extend(mirrored);
+
+        progress.begin("Downloading mods...", downloads.len());
+
+        // create mods dir in case there are zero automated downloads with one or more manual downloads
+        fs::create_dir_all(self.mods_dir())?;
+
+        let mut results = stream::iter(&downloads)
+            .map(|f| async move {
+                let dest_file_path = self.get_file_path(f);
+
+                // save time/bandwidth and skip download if dest file exists,
+                // files left by an interrupted install are downloaded again
+                let is_valid = dest_file_path.exists()
+                    && f.is_valid_file(&dest_file_path).unwrap_or(false);
+
+                if is_valid {
+                    return Ok(());
+                }
+
+                download_verified(client, f, &dest_file_path).await
+                    .map_err(|e| format!("{}: {e:#}", f.file_name))
+            })
+            .buffer_unordered(env::get_download_jobs());
+
+        let mut failed = vec![];
+        let mut count = 0;
+
+        while let Some(result) = results.next().await {
+            if let Err(e) = result {
+                failed.push(e);
+            }
+
+            count += 1;
+            progress.advance(count);
+        }
+
+        progress.end();
+
+        if !failed.is_empty() {
+            bail!(Error::ModDownloadsFailed(failed.join("\n")));
+        }
+
+        let delete_files = [
+            list_extra_files(&self.mods_dir(), &file_downloads)?,
+            list_extra_files(&self.resource_pack_dir(), &file_downloads)?,
+            list_extra_files(&self.shader_pack_dir(), &file_downloads)?
+        ].concat();
+
+        if !blocked.is_empty() {
+            Ok((delete_files, Some(blocked)))
+        } else {
+            Ok((delete_files, None))
+        }
+    }
+
+    /// Create CurseForge modpack zip from the instance. Mods, resource packs and
+    /// shader packs are matched to CurseForge files by fingerprint and referenced
+    /// in the pack manifest, all other game files are added as overrides.
+    /// Returns the list of files that could not be matched.
+    pub async fn export_pack_zip(&self,
+        zip_path: &Path,
+        name: &str,
+        version: &str,
+        author: &str,
+        progress: &mut dyn Progress
+    ) -> Result<Vec<PathBuf>> {
+        self.check_export_path(zip_path)?;
+
+        let client = AssetClient::new()?;
+
+        let candidates = [
+            list_files(&self.mods_dir())?,
+            list_files(&self.resource_pack_dir())?,
+            list_files(&self.shader_pack_dir())?
+        ].concat();
+
+        // files recorded in the lockfile are referenced without a fingerprint
+        // lookup, as long as the file hasn't changed since it was installed
+        let lock = self.load_mods_lock()?;
+        let mut locked = HashMap::new();
+
+        for path in &candidates {
+            let locked_file = lock.get(&self.mods_lock_key(path))
+                .and_then(|f| match f.source {
+                    ModsLockSource::CurseForge { project_id, file_id, fingerprint } => {
+                        Some((project_id, file_id, fingerprint))
+                    },
+                    _ => None
+                });
+
+            if let Some((project_id, file_id, fingerprint)) = locked_file {
+                if hash::curseforge_fingerprint(path)? == fingerprint {
+                    locked.insert(path.clone(), (project_id, file_id));
+                }
+            }
+        }
+
+        let unlocked: Vec<_> = candidates.iter()
+            .filter(|p| !locked.contains_key(*p))
+            .cloned()
+            .collect();
+
+        let matches = match_curseforge_files(&client, &unlocked, progress).await?;
+
+        let matches = matches.into_iter()
+            .map(|(path, m)| (path, m.mod_id, m.file.file_id))
+            .chain(locked.into_iter().map(|(path, (project_id, file_id))| (path, project_id, file_id)))
+            .collect();
+
+        let (files, matched_paths) = curseforge_pack_files(matches);
+
+        let manifest = CurseForgePack {
+            minecraft: CurseForgeMinecraft {
+                version: self.manifest.mc_version.clone(),
+                mod_loaders: self.manifest.mod_loader.iter()
+                    .map(|l| CurseForgeModloader { id: l.to_string(), primary: true })
+                    .collect(),
+                recommended_ram: self.manifest.max_memory
+            },
+            manifest_type: "minecraftModpack".to_string(),
+            manifest_version: 1,
+            name: name.to_string(),
+            version: version.to_string(),
+            author: author.to_string(),
+            files,
+            overrides: "overrides".to_string()
+        };
+
+        let overrides = self.list_overrides(&matched_paths)?;
+
+        let manifest_json = serde_json::to_string_pretty(&manifest)?;
+
+        zip::create_zip_from(
+            fs::File::create(zip_path)?,
+            &overrides,
+            &[("manifest.json", manifest_json.as_bytes())]
+        )?;
+
+        Ok(candidates.into_iter()
+            .filter(|p| !matched_paths.contains(p))
+            .collect())
+    }
+
+    /// Export instance as Modrinth `.mrpack` file. Mods, resource packs, and
+    /// shader packs hosted on Modrinth are referenced by download url, all other
+    /// files are added to overrides. Returns the files not found on Modrinth.
+    pub async fn export_pack_mrpack(&self,
+        mrpack_path: &Path,
+        name: &str,
+        version: &str,
+        progress: &mut dyn Progress
+    ) -> Result<Vec<PathBuf>> {
+        self.check_export_path(mrpack_path)?;
+
+        let client = AssetClient::new()?;
+
+        let candidates = [
+            list_files(&self.mods_dir())?,
+            list_files(&self.resource_pack_dir())?,
+            list_files(&self.shader_pack_dir())?
+        ].concat();
+
+        progress.begin("Hashing files...", candidates.len());
+
+        let mut hashes = HashMap::new();
+
+        for (i, path) in candidates.iter().enumerate() {
+            progress.advance(i + 1);
+            hashes.insert(path, hash::sha1_sha512(path)?);
+        }
+
+        progress.end();
+
+        let sha1_list: Vec<_> = hashes.values()
+            .map(|(sha1, _)| sha1.clone())
+            .collect();
+
+        let versions = if sha1_list.is_empty() {
+            HashMap::new()
+        } else {
+            client.get_modrinth_versions_from_hashes(&sha1_list).await?
+        };
+
+        let mut files = vec![];
+        let mut matched_paths = HashSet::new();
+
+        for path in &candidates {
+            let (sha1, sha512) = &hashes[path];
+
+            let url = versions.get(sha1)
+                .and_then(|v| v.files.iter().find(|f| &f.hashes.sha1 == sha1))
+                .map(|f| f.url.clone());
+
+            if let Some(url) = url {
+                // disabled mods are exported as optional
+                let env = if is_disabled_mod(path) {
+                    Some(ModrinthPackFileEnv {
+                        client: ModrinthEnvSupport::Optional,
+                        server: ModrinthEnvSupport::Optional
+                    })
+                } else {
+                    None
+                };
+
+                files.push(ModrinthPackFile {
+                    path: self.mods_lock_key(path),
+                    hashes: ModrinthHashes { sha1: sha1.clone(), sha512: sha512.clone() },
+                    env,
+                    downloads: vec![url],
+                    file_size: fs::metadata(path)?.len()
+                });
+
+                matched_paths.insert(path.clone());
+            }
+        }
+
+        files.sort_by(|a, b| a.path.cmp(&b.path));
+
+        let mut dependencies = BTreeMap::from([
+            ("minecraft".to_string(), self.manifest.mc_version.clone())
+        ]);
+
+        if let Some(loader) = &self.manifest.mod_loader {
+            dependencies.insert(loader.name.modrinth_dependency().to_string(), loader.version.clone());
+        }
+
+        let index = ModrinthPack {
+            format_version: 1,
+            game: "minecraft".to_string(),
+            version_id: version.to_string(),
+            name: name.to_string(),
+            summary: None,
+            files,
+            dependencies
+        };
+
+        let overrides = self.list_overrides(&matched_paths)?;
+
+        let index_json = serde_json::to_string_pretty(&index)?;
+
+        zip::create_zip_from(
+            fs::File::create(mrpack_path)?,
+            &overrides,
+            &[("modrinth.index.json", index_json.as_bytes())]
+        )?;
+
+        Ok(candidates.into_iter()
+            .filter(|p| !matched_paths.contains(p))
+            .collect())
+    }
+
+    /// Fail when `export_path` is inside the instance or its game dir, which
+    /// can be outside the instance, the export would walk into the file it's
+    /// writing
+    fn check_export_path(&self, export_path: &Path) -> Result<()> {
+        let export_path = resolve_path(export_path)?;
+
+        if export_path.starts_with(&self.dir) || export_path.starts_with(resolve_path(&self.game_dir())?) {
+            bail!(Error::ExportIntoSelf);
+        }
+
+        Ok(())
+    }
+
+    /// Files in game dir to include in pack overrides, paired with their zip name
+    fn list_overrides(&self, exclude: &HashSet<PathBuf>) -> Result<Vec<(String, PathBuf)>> {
+        let game_dir = self.game_dir();
+        let mut overrides = vec![];
+
+        let walker = WalkDir::new(&game_dir).into_iter()
+            .filter_entry(|e| !EXPORT_EXCLUDE.iter().any(|x| e.depth() == 1 && e.file_name() == *x));
+
+        for entry in walker {
+            let entry = entry?;
+            let path = entry.path();
+
+            if !entry.file_type().is_file() || exclude.contains(path) {
+                continue;
+            }
+
+            let rel_path = path.strip_prefix(&game_dir)?;
+            let zip_name = std::iter::once("overrides".to_string())
+                .chain(rel_path.iter().map(|p| p.to_string_lossy().to_string()))
+                .collect::<Vec<_>>()
+                .join("/");
+
+            overrides.push((zip_name, path.to_path_buf()));
+        }
+
+        Ok(overrides)
+    }
+
+    /// Find mods without a CurseForge file for version of minecraft and the
+    /// instance mod loader. Returns the incompatible mods, and mods that
+    /// could not be checked because they weren't found on CurseForge.
+    pub async fn find_incompatible_mods(&self,
+        mc_version: &str,
+        progress: &mut dyn Progress
+    ) -> Result<(Vec<PathBuf>, Vec<PathBuf>)> {
+        let client = AssetClient::new()?;
+
+        let mod_files = list_files(&self.mods_dir())?;
+        let matches = match_curseforge_files(&client, &mod_files, progress).await?;
+
+        let mod_ids: Vec<_> = matches.values().map(|m| m.mod_id).collect();
+        let mods = if mod_ids.is_empty() {
+            vec![]
+        } else {
+            client.get_curseforge_mods(&mod_ids).await?
+        };
+
+        let loader_type = self.manifest.mod_loader.as_ref()
+            .map(|l| l.name.curseforge_type());
+
+        let mut incompatible: Vec<_> = matches.iter()
+            .filter(|(_, m)| {
+                mods.iter()
+                    .find(|cf_mod| cf_mod.mod_id == m.mod_id)
+                    .is_some_and(|cf_mod| !cf_mod.has_file_for(mc_version, loader_type))
+            })
+            .map(|(path, _)| path.clone())
+            .collect();
+
+        let mut unknown: Vec<_> = mod_files.into_iter()
+            .filter(|p| !matches.contains_key(p))
+            .collect();
+
+        incompatible.sort();
+        unknown.sort();
+
+        Ok((incompatible, unknown))
+    }
+
+    /// Number of enabled and disabled mod files
+    pub fn count_mods(&self) -> Result<(usize, usize)> {
+        let mod_files = list_files(&self.mods_dir())?;
+
+        let count_suffix = |suffix: &str| mod_files.iter()
+            .filter(|p| p.to_string_lossy().ends_with(suffix))
+            .count();
+
+        Ok((count_suffix(".jar"), count_suffix(DISABLED_SUFFIX)))
+    }
+
+    /// Find mod file by file name, or by part of the file name (ignoring case)
+    /// when only one mod matches
+    pub fn find_mod_file(&self, name: &str) -> Result<PathBuf> {
+        let mod_files = list_files(&self.mods_dir())?;
+
+        let file_name = |p: &PathBuf| p.file_name().unwrap().to_string_lossy().to_string();
+
+        if let Some(path) = mod_files.iter().find(|p| file_name(p) == name) {
+            return Ok(path.clone());
+        }
+
+        let name_lower = name.to_lowercase();
+        let mut matches: Vec<_> = mod_files.iter()
+            .filter(|p| file_name(p).to_lowercase().contains(&name_lower))
+            .collect();
+
+        match matches.len() {
+            0 => bail!(Error::ModNotFound(name.to_string())),
+            1 => Ok(matches.remove(0).clone()),
+            _ => {
+                let mut names: Vec<_> = matches.into_iter().map(file_name).collect();
+                names.sort();
+
+                bail!(Error::ModNameAmbiguous {
+                    name: name.to_string(),
+                    matches: names.join(", ")
+                })
+            }
+        }
+    }
+
+    /// List mods that require `mod_file`, using CurseForge dependency data
+    /// of the files matched by fingerprint
+    pub async fn find_mod_dependents(&self,
+        mod_file: &Path,
+        progress: &mut dyn Progress
+    ) -> Result<Vec<PathBuf>> {
+        let client = AssetClient::new()?;
+
+        let mod_files = list_files(&self.mods_dir())?;
+        let matches = match_curseforge_files(&client, &mod_files, progress).await?;
+
+        let mod_id = match matches.get(mod_file) {
+            Some(m) => m.mod_id,
+            // no dependency data for mods that aren't on curseforge
+            None => return Ok(vec![])
+        };
+
+        let mut dependents: Vec<_> = matches.iter()
+            .filter(|(path, _)| path.as_path() != mod_file)
+            .filter(|(_, m)| m.file.dependencies.iter()
+                .any(|d| d.is_required() && d.mod_id == mod_id))
+            .map(|(path, _)| path.clone())
+            .collect();
+
+        dependents.sort();
+
+        Ok(dependents)
+    }
+
+    pub fn remove_mod(&self, mod_file: &Path) -> Result<()> {
+        fs::remove_file(mod_file)?;
+
+        self.update_mods_lock(mod_file, |lock, key| lock.remove(key))
+    }
+
+    /// Apply change to lockfile entry of `path`, when the instance has a lockfile
+    fn update_mods_lock<F>(&self, path: &Path, update: F) -> Result<()>
+        where F: FnOnce(&mut ModsLock, &str)
+    {
+        if !self.mods_lock_path().exists() {
+            return Ok(());
+        }
+
+        let mut lock = self.load_mods_lock()?;
+        update(&mut lock, &self.mods_lock_key(path));
+        self.write_mods_lock(&lock)
+    }
+
+    /// Rename mod file with `.disabled` suffix so the mod loader ignores it,
+    /// returns the new path
+    pub fn disable_mod(&self, mod_file: &Path) -> Result<PathBuf> {
+        if is_disabled_mod(mod_file) {
+            return Ok(mod_file.to_path_buf());
+        }
+
+        let disabled_file = with_disabled_suffix(mod_file);
+        fs::rename(mod_file, &disabled_file)?;
+
+        self.update_mods_lock(mod_file, |lock, key| {
+            if let Some(f) = lock.get_mut(key) {
+                f.disabled = true;
+            }
+        })?;
+
+        Ok(disabled_file)
+    }
+
+    /// Remove `.disabled` suffix from mod file, returns the new path
+    pub fn enable_mod(&self, mod_file: &Path) -> Result<PathBuf> {
+        let file_name = mod_file.file_name().unwrap().to_string_lossy();
+
+        let enabled_file = match file_name.strip_suffix(DISABLED_SUFFIX) {
+            Some(name) => mod_file.with_file_name(name),
+            None => return Ok(mod_file.to_path_buf())
+        };
+
+        fs::rename(mod_file, &enabled_file)?;
+
+        self.update_mods_lock(mod_file, |lock, key| {
+            if let Some(f) = lock.get_mut(key) {
+                f.disabled = false;
+            }
+        })?;
+
+        Ok(enabled_file)
+    }
+
+    /// Change version of minecraft and mod loader after validating the game
+    /// and mod loader manifests exist
+    pub async fn upgrade(&mut self,
+        mc_version: &str,
+        mod_loader: Option<ModLoader>,
+        progress: &mut dyn Progress
+    ) -> Result<()> {
+        let _lock = self.lock(progress)?;
+
+        let assets = AssetManager::new()?;
+
+        // validate `mc_version`
+        assets.get_game_manifest(mc_version).await?;
+
+        if let Some(mod_loader) = &mod_loader {
+            // validate `mod_loader`
+            assets.get_loader_manifest(mod_loader).await?;
+        }
+
+        self.manifest.mc_version = mc_version.to_string();
+        self.manifest.mod_loader = mod_loader;
+        self.write_manifest()
+    }
+
+    /// Change mod loader, after validating the loader manifest matches the
+    /// instance version of minecraft and downloading the loader libraries.
+    /// The instance is left unchanged when any step fails.
+    pub async fn change_mod_loader(&mut self,
+        mod_loader: ModLoader,
+        progress: &mut dyn Progress
+    ) -> Result<()> {
+        let _lock = self.lock(progress)?;
+
+        let assets = AssetManager::new()?;
+
+        let manifest_path = assets.loader_manifest_path(&mod_loader);
+        let is_cached = manifest_path.exists();
+
+        let result = self.apply_mod_loader(&assets, mod_loader, progress).await;
+
+        // don't leave behind an invalid manifest in the cache
+        if result.is_err() && !is_cached && manifest_path.exists() {
+            fs::remove_file(manifest_path)?;
+        }
+
+        result
+    }
+
+    async fn apply_mod_loader(&mut self,
+        assets: &AssetManager,
+        mod_loader: ModLoader,
+        progress: &mut dyn Progress
+    ) -> Result<()> {
+        let mut loader_manifest = assets.get_loader_manifest(&mod_loader).await?;
+
+        // quilt isn't tied to a version of minecraft, forge loaders are
+        if let Ok(loader_mc_version) = loader_manifest.get_minecraft_version() {
+            if loader_mc_version != self.manifest.mc_version {
+                bail!(Error::ModLoaderVersionMismatch {
+                    loader: mod_loader.to_string(),
+                    loader_mc_version,
+                    mc_version: self.manifest.mc_version.clone()
+                });
+            }
+        }
+
+        asset_manager::add_intermediary_library(&mut loader_manifest, &self.manifest.mc_version);
+
+        assets.download_loader_libraries(&loader_manifest, progress).await?;
+
+        let previous = self.manifest.mod_loader.replace(mod_loader);
+
+        if let Err(e) = self.write_manifest() {
+            self.manifest.mod_loader = previous;
+            return Err(e);
+        }
+
+        Ok(())
+    }
+
+    /// Copy instance to `dest_dir`, leaving out files that are recreated from
+    /// shared data at launch. Play time and last played start over in the copy.
+    pub fn clone_to(&self, dest_dir: &Path) -> Result<Instance> {
+        if dest_dir.exists() {
+            bail!(Error::InstanceDirExists(dest_dir.to_string_lossy().to_string()));
+        }
+
+        // compare resolved paths, a relative or symlinked `dest_dir` can be
+        // inside the instance without starting with its canonical path
+        if resolve_path(dest_dir)?.starts_with(&self.dir) {
+            bail!(Error::CloneIntoSelf);
+        }
+
+        let skip = [self.natives_dir(), self.fml_libs_dir(), self.resources_dir(), self.logs_dir()];
+
+        let entries = WalkDir::new(&self.dir)
+            .into_iter()
+            .filter_entry(|e| !skip.iter().any(|p| p == e.path()));
+
+        for entry in entries {
+            let entry = entry?;
+            let dest_path = dest_dir.join(entry.path().strip_prefix(&self.dir)?);
+
+            if entry.file_type().is_dir() {
+                fs::create_dir_all(dest_path)?;
+            } else {
+                fs::copy(entry.path(), dest_path)?;
+            }
+        }
+
+        let mut instance = Self::load(dest_dir)?;
+
+        // copy is registered by its directory name
+        instance.manifest.name = None;
+        instance.manifest.last_played = None;
+        instance.manifest.play_time_seconds = None;
+        instance.write_manifest()?;
+
+        InstanceRegistry::register(&instance.dir, instance.manifest.name.as_deref())?;
+
+        Ok(instance)
+    }
+
+    /// Create instance manifest in Prism Launcher instance directory, keeping
+    /// the existing game dir and carrying over java and play time settings
+    pub async fn import_prism(prism: &PrismInstance, progress: &mut dyn Progress) -> Result<Instance> {
+        let mut instance = Instance::create(&prism.dir, &prism.mc_version()?, prism.mod_loader()?, progress)
+            .await?;
+
+        instance.manifest.game_dir = prism.game_dir().to_string();
+        instance.manifest.java_path = prism.get_override("OverrideJavaLocation", "JavaPath")
+            .map(String::from);
+        instance.manifest.java_args = prism.get_override("OverrideJavaArgs", "JvmArgs")
+            .map(|a| a.split_whitespace().map(String::from).collect());
+        instance.manifest.min_memory = prism.get_override("OverrideMemory", "MinMemAlloc")
+            .and_then(|m| m.parse().ok());
+        instance.manifest.max_memory = prism.get_override("OverrideMemory", "MaxMemAlloc")
+            .and_then(|m| m.parse().ok());
+        instance.manifest.last_played = prism.get("lastLaunchTime")
+            .and_then(|t| t.parse().ok())
+            .and_then(|t| Utc.timestamp_millis_opt(t).single());
+        instance.manifest.play_time_seconds = prism.get("totalTimePlayed")
+            .and_then(|t| t.parse().ok());
+
+        instance.write_manifest()?;
+
+        Ok(instance)
+    }
+
+    /// Create an instance in `instances_dir` for each profile in the mojang
+    /// launcher `launcher_profiles.json`, copying already downloaded assets.
+    /// Returns the result of each profile import paired with the profile name.
+    pub async fn import_vanilla(
+        minecraft_dir: &Path,
+        instances_dir: &Path,
+        progress: &mut dyn Progress
+    ) -> Result<Vec<(String, Result<Instance>)>> {
+        let profiles_path = minecraft_dir.join("launcher_profiles.json");
+        if !profiles_path.exists() {
+            bail!(Error::LauncherProfilesNotFound(profiles_path.to_string_lossy().to_string()));
+        }
+
+        let profiles: LauncherProfiles = serde_json::from_reader(fs::File::open(profiles_path)?)?;
+
+        let _lock = super::fs::lock_file(&env::get_data_lock_path(), progress)?;
+
+        AssetManager::new()?
+            .import_assets(&minecraft_dir.join("assets"), progress)?;
+
+        let version_manifest = AssetClient::new()?
+            .get_mc_version_manifest().await?;
+
+        let mut profiles: Vec<_> = profiles.profiles.into_values().collect();
+        profiles.sort_by(|a, b| a.display_name().cmp(b.display_name()));
+
+        let mut results = vec![];
+
+        for profile in profiles {
+            let name = profile.display_name().to_string();
+
+            let (mc_version, mod_loader) = match profile.last_version_id.as_str() {
+                "latest-release" => (version_manifest.latest.release.clone(), None),
+                "latest-snapshot" => (version_manifest.latest.snapshot.clone(), None),
+                _ => profile.parse_version_id()
+            };
+
+            let instance_dir = instances_dir.join(dir_name(&name));
+
+            let result = Instance::import_vanilla_profile(
+                &instance_dir, minecraft_dir, &profile, &mc_version, mod_loader
+            ).await;
+
+            results.push((name, result));
+        }
+
+        Ok(results)
+    }
+
+    async fn import_vanilla_profile(
+        instance_dir: &Path,
+        minecraft_dir: &Path,
+        profile: &LauncherProfile,
+        mc_version: &str,
+        mod_loader: Option<ModLoader>
+    ) -> Result<Instance> {
+        if instance_dir.exists() {
+            bail!(Error::InstanceDirExists(instance_dir.to_string_lossy().to_string()));
+        }
+
+        let mut instance = Instance::create_locked(instance_dir, mc_version, mod_loader).await?;
+
+        // profiles share the launcher game dir unless they set their own
+        let game_dir = match &profile.game_dir {
+            Some(dir) => PathBuf::from(dir),
+            None => minecraft_dir.to_path_buf()
+        };
+
+        instance.manifest.game_dir = game_dir.to_string_lossy().to_string();
+        instance.manifest.java_path = profile.java_dir.clone();
+        instance.manifest.java_args = profile.java_args.as_ref()
+            .map(|a| a.split_whitespace().map(String::from).collect());
+
+        instance.write_manifest()?;
+
+        Ok(instance)
+    }
+
+    /// Export instance as zip that can be imported into Prism Launcher
+    pub fn export_prism_zip(&self, zip_path: &Path, name: &str) -> Result<()> {
+        self.check_export_path(zip_path)?;
+
+        let pack = prism_instance::make_mmc_pack(
+            &self.manifest.mc_version,
+            self.manifest.mod_loader.as_ref()
+        );
+
+        let mut cfg = vec![
+            ("InstanceType", "OneSix".to_string()),
+            ("name", name.to_string())
+        ];
+
+        if let Some(java_path) = &self.manifest.java_path {
+            cfg.push(("OverrideJavaLocation", "true".to_string()));
+            cfg.push(("JavaPath", java_path.clone()));
+        }
+
+        if let Some(java_args) = &self.manifest.java_args {
+            cfg.push(("OverrideJavaArgs", "true".to_string()));
+            cfg.push(("JvmArgs", java_args.join(" ")));
+        }
+
+        if let (Some(min), Some(max)) = (self.manifest.min_memory, self.manifest.max_memory) {
+            cfg.push(("OverrideMemory", "true".to_string()));
+            cfg.push(("MinMemAlloc", min.to_string()));
+            cfg.push(("MaxMemAlloc", max.to_string()));
+        }
+
+        if let Some(last_played) = &self.manifest.last_played {
+            cfg.push(("lastLaunchTime", last_played.timestamp_millis().to_string()));
+        }
+
+        if let Some(play_time) = self.manifest.play_time_seconds {
+            cfg.push(("totalTimePlayed", play_time.to_string()));
+        }
+
+        let game_dir = self.game_dir();
+        let skip = [self.fml_libs_dir(), self.resources_dir()];
+
+        let mut files = vec![];
+
+        let walker = WalkDir::new(&game_dir).into_iter()
+            .filter_entry(|e| !skip.iter().any(|p| e.path() == p));
+
+        for entry in walker {
+            let entry = entry?;
+
+            if !entry.file_type().is_file() {
+                continue;
+            }
+
+            let rel_path = entry.path().strip_prefix(&game_dir)?;
+            let zip_name = std::iter::once(".minecraft".to_string())
+                .chain(rel_path.iter().map(|p| p.to_string_lossy().to_string()))
+                .collect::<Vec<_>>()
+                .join("/");
+
+            files.push((zip_name, entry.path().to_path_buf()));
+        }
+
+        let cfg = prism_instance::make_instance_cfg(&cfg);
+        let pack_json = serde_json::to_string_pretty(&pack)?;
+
+        zip::create_zip_from(
+            fs::File::create(zip_path)?,
+            &files,
+            &[("instance.cfg", cfg.as_bytes()), ("mmc-pack.json", pack_json.as_bytes())]
+        )?;
+
+        Ok(())
+    }
+
+    /// Set the instance name and register the instance with it
+    pub fn rename(&mut self, name: &str) -> Result<()> {
+        let registry = InstanceRegistry::load()?;
+
+        if registry.get(name).is_some_and(|dir| *dir != self.dir) {
+            bail!(Error::InstanceNameTaken(name.to_string()));
+        }
+
+        self.manifest.name = Some(name.to_string());
+        self.write_manifest()?;
+
+        InstanceRegistry::register(&self.dir, Some(name))?;
+
+        Ok(())
+    }
+
+    /// Delete the instance directory and everything in it
+    pub fn delete(self) -> Result<()> {
+        fs::remove_dir_all(&self.dir)?;
+        InstanceRegistry::unregister(&self.dir)
+    }
+
+    /// Wait for the game to exit, adding the session to the play time and
+    /// looking for crash reports written while the game was running
+    pub fn wait_for_exit(&mut self, mut process: GameProcess) -> Result<GameExit> {
+        let status = process.wait()?;
+
+        // manifest could have been edited while playing
+        *self = Self::load(&self.dir)?;
+
+        let mut play_time_seconds = 0;
+
+        if let Some(started) = self.manifest.last_played {
+            play_time_seconds = (Utc::now() - started).num_seconds().max(0) as u64;
+
+            self.manifest.play_time_seconds =
+                Some(self.manifest.play_time_seconds.unwrap_or(0) + play_time_seconds);
+
+            self.write_manifest()?;
+        }
+
+        // reports left by earlier sessions are older than the process
+        let crash_report = self.find_crash_report(process.started)?;
+
+        Ok(GameExit { status, play_time_seconds, log_path: process.log_path, crash_report })
+    }
+
+    /// Newest crash report from the game, or `hs_err_pid*.log` from the JVM,
+    /// modified after `since`
+    fn find_crash_report(&self, since: DateTime<Utc>) -> Result<Option<PathBuf>> {
+        let game_dir = self.game_dir();
+
+        let jvm_logs = list_files(&game_dir)?
+            .into_iter()
+            .filter(|p| p.file_name().is_some_and(|n| n.to_string_lossy().starts_with("hs_err_pid")));
+
+        let mut newest = None;
+
+        for path in list_files(&game_dir.join("crash-reports"))?.into_iter().chain(jvm_logs) {
+            let modified = DateTime::<Utc>::from(fs::metadata(&path)?.modified()?);
+
+            if modified >= since && newest.as_ref().is_none_or(|(t, _)| modified > *t) {
+                newest = Some((modified, path));
+            }
+        }
+
+        Ok(newest.map(|(_, path)| path))
+    }
+
+    /// Launch instance and wait for the game to exit, see `launch` and `wait_for_exit`
+    pub async fn launch_and_wait(&mut self,
+        session: &Session,
+        options: &LaunchOptions,
+        progress: &mut dyn Progress
+    ) -> Result<GameExit> {
+        let process = self.launch(session, options, progress).await?;
+        self.wait_for_exit(process)
+    }
+
+    pub fn load(instance_dir: &Path) -> Result<Instance> {
+        let json_path = instance_dir.join(MANIFEST_FILE);
+        let toml_path = instance_dir.join(MANIFEST_TOML_FILE);
+
+        // plain json manifest takes precedence when both files exist
+        if json_path.exists() {
+            let json = fs::read_to_string(json_path)?;
+            let manifest = serde_json::from_str::<InstanceManifest>(json.as_str())?;
+
+            Instance::new(instance_dir, manifest, ManifestFormat::Json)
+        } else if toml_path.exists() {
+            let toml = fs::read_to_string(toml_path)?;
+            let manifest = toml_edit::de::from_str::<InstanceManifest>(toml.as_str())?;
+
+            Instance::new(instance_dir, manifest, ManifestFormat::Toml)
+        } else {
+            bail!(Error::InstanceNotFound(instance_dir.to_str().unwrap().to_string()))
+        }
+    }
+
+    pub fn set_mc_version(&mut self, mc_version: String) -> Result<()> {
+        self.manifest.mc_version = mc_version;
+        self.write_manifest()
+    }
+
+    pub fn set_modpack(&mut self, modpack: Option<InstanceModpack>) -> Result<()> {
+        self.manifest.modpack = modpack;
+        self.write_manifest()
+    }
+
+    /// Default memory settings to the modpack minimum and recommended memory,
+    /// keeping settings that were already set
+    pub fn set_pack_memory_defaults(&mut self, pack: &ModpackVersionManifest) -> Result<()> {
+        match &pack.specs {
+            Some(specs) => self.set_memory_defaults(Some(specs.minimum), Some(specs.recommended)),
+            None => Ok(())
+        }
+    }
+
+    /// Default memory settings to `min` and `max`, keeping settings that were
+    /// already set
+    fn set_memory_defaults(&mut self, min: Option<u32>, max: Option<u32>) -> Result<()> {
+        self.manifest.min_memory = self.manifest.min_memory.or(min);
+        self.manifest.max_memory = self.manifest.max_memory.or(max);
+
+        self.write_manifest()
+    }
+
+    pub fn set_java_runtime(&mut self, component: Option<String>) -> Result<()> {
+        self.manifest.java_runtime = component;
+        self.write_manifest()
+    }
+
+    pub fn set_account(&mut self, account: Option<String>) -> Result<()> {
+        self.manifest.account = account;
+        self.write_manifest()
+    }
+
+    pub fn set_mod_loader(&mut self, mod_loader: Option<ModLoader>) -> Result<()> {
+        self.manifest.mod_loader = mod_loader;
+        self.write_manifest()
+    }
+
+    pub fn game_dir(&self) -> PathBuf {
+        self.dir.join(&self.manifest.game_dir)
+    }
+
+    pub fn fml_libs_dir(&self) -> PathBuf {
+        self.game_dir().join("lib")
+    }
+
+    pub fn mods_dir(&self) -> PathBuf {
+        self.game_dir().join("mods")
+    }
+
+    pub fn resources_dir(&self) -> PathBuf {
+        self.game_dir().join("resources")
+    }
+
+    pub fn resource_pack_dir(&self) -> PathBuf {
+        self.game_dir().join("resourcepacks")
+    }
+
+    pub fn shader_pack_dir(&self) -> PathBuf {
+        self.game_dir().join("shaderpacks")
+    }
+
+    /// Directory of session logs written by steve, the game writes its own
+    /// logs to the `logs` directory of the game dir
+    pub fn logs_dir(&self) -> PathBuf {
+        self.dir.join("logs")
+    }
+
+    /// Most recent session log
+    pub fn latest_log(&self) -> Result<Option<PathBuf>> {
+        let mut logs: Vec<_> = list_files(&self.logs_dir())?
+            .into_iter()
+            .filter(|p| p.file_name().is_some_and(|n| n.to_string_lossy().starts_with("steve-")))
+            .collect();
+
+        // timestamp in file name sorts oldest to newest
+        logs.sort();
+
+        Ok(logs.pop())
+    }
+
+    /// Singleplayer worlds in the saves directory, most recently played first;
+    /// worlds with an unreadable `level.dat` are listed without details
+    pub fn worlds(&self) -> Result<Vec<World>> {
+        let saves_dir = self.saves_dir();
+        if !saves_dir.is_dir() {
+            return Ok(vec![]);
+        }
+
+        let mut worlds = vec![];
+
+        for entry in fs::read_dir(saves_dir)? {
+            let level_path = entry?.path().join("level.dat");
+            if !level_path.exists() {
+                continue;
+            }
+
+            let dir_name = level_path.parent().and_then(Path::file_name)
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_default();
+
+            let level = fs::File::open(&level_path).and_then(nbt::read).ok();
+
+            worlds.push(World::from_level(dir_name, level.as_ref().and_then(|l| l.get("Data"))));
+        }
+
+        worlds.sort_by_key(|w| std::cmp::Reverse(w.last_played));
+
+        Ok(worlds)
+    }
+
+    pub fn saves_dir(&self) -> PathBuf {
+        self.game_dir().join("saves")
+    }
+
+    pub fn natives_dir(&self) -> PathBuf {
+        self.dir.join("natives")
+    }
+
+    /// Directory of vendored game and mod loader manifests
+    pub fn versions_dir(&self) -> PathBuf {
+        self.dir.join("versions")
+    }
+
+    /// Copy the game and mod loader manifests into the instance directory.
+    /// Vendored manifests are used at launch instead of the shared cache,
+    /// pinning the instance against upstream meta changes.
+    pub async fn vendor_manifests(&self) -> Result<()> {
+        let assets = AssetManager::new()?;
+
+        fs::create_dir_all(self.versions_dir())?;
+
+        // fetch manifests to make sure they exist in the cache
+        let mc_version = &self.manifest.mc_version;
+        assets.get_game_manifest(mc_version).await?;
+
+        fs::copy(
+            assets.game_manifest_path(mc_version),
+            self.versions_dir().join(asset_manager::game_manifest_file_name(mc_version))
+        )?;
+
+        if let Some(mod_loader) = &self.manifest.mod_loader {
+            assets.get_loader_manifest(mod_loader).await?;
+
+            fs::copy(
+                assets.loader_manifest_path(mod_loader),
+                self.versions_dir().join(asset_manager::loader_manifest_file_name(mod_loader))
+            )?;
+        }
+
+        Ok(())
+    }
+
+    async fn get_game_manifest(&self, assets: &AssetManager) -> Result<GameManifest> {
+        let mc_version = &self.manifest.mc_version;
+        let vendored_path = self.versions_dir()
+            .join(asset_manager::game_manifest_file_name(mc_version));
+
+        if vendored_path.exists() {
+            asset_manager::load_game_manifest(&vendored_path)
+        } else {
+            assets.get_game_manifest(mc_version).await
+        }
+    }
+
+    /// Shared files used to launch the instance, see `InstanceRegistry::collect_garbage`
+    pub(crate) async fn shared_paths(&self, assets: &AssetManager) -> Result<Vec<PathBuf>> {
+        let game_manifest = self.get_game_manifest(assets).await?;
+        let asset_manifest = assets.get_asset_manfiest(&game_manifest).await?;
+        let loader_manifest = self.get_loader_manifest(assets).await?;
+
+        let mut paths = assets.referenced_paths(&game_manifest, &asset_manifest, loader_manifest.as_ref())?;
+
+        paths.push(assets.game_manifest_path(&self.manifest.mc_version));
+
+        if let Some(mod_loader) = &self.manifest.mod_loader {
+            paths.push(assets.loader_manifest_path(mod_loader));
+        }
+
+        Ok(paths)
+    }
+
+    /// Shared Forge installer processor output of the instance, see
+    /// `AssetManager::processor_output_paths`
+    pub(crate) async fn processor_output_paths(&self, assets: &AssetManager) -> Result<Vec<PathBuf>> {
+        match self.get_loader_manifest(assets).await? {
+            Some(loader_manifest) => assets.processor_output_paths(&self.manifest.mc_version, &loader_manifest),
+            None => Ok(vec![])
+        }
+    }
+
+    async fn get_loader_manifest(&self, assets: &AssetManager) -> Result<Option<ForgeManifest>> {
+        let mod_loader = match &self.manifest.mod_loader {
+            Some(mod_loader) => mod_loader,
+            None => return Ok(None)
+        };
+
+        let vendored_path = self.versions_dir()
+            .join(asset_manager::loader_manifest_file_name(mod_loader));
+
+        let mut loader_manifest = if vendored_path.exists() {
+            asset_manager::load_loader_manifest(&vendored_path)?
+        } else {
+            assets.get_loader_manifest(mod_loader).await?
+        };
+
+        asset_manager::add_intermediary_library(&mut loader_manifest, &self.manifest.mc_version);
+
+        Ok(Some(loader_manifest))
+    }
+
+    /// Java override path from instance manifest, or mojang java runtime
+    /// for the version of minecraft
+    async fn get_java_path(&self,
+        assets: &AssetManager,
+        game_manifest: &GameManifest,
+        progress: &mut dyn Progress
+    ) -> Result<PathBuf> {
+        if let Some(path) = &self.manifest.java_path {
+            return Ok(PathBuf::from(path));
+        }
+
+        // mojang doesn't publish runtimes for every platform (i.e. linux arm)
+        if env::get_java_runtime_platform().is_none() {
+            return Ok(PathBuf::from("java"));
+        }
+
+        let component = self.java_runtime_component(game_manifest);
+
+        assets.get_java_runtime(component, progress).await
+    }
+
+    /// Java the instance launches with, without installing the java runtime
+    /// when it's missing. Only reads files already on disk, failing when the
+    /// game manifest hasn't been downloaded yet.
+    pub async fn resolve_java_path(&self) -> Result<PathBuf> {
+        if let Some(path) = &self.manifest.java_path {
+            return Ok(PathBuf::from(path));
+        }
+
+        if env::get_java_runtime_platform().is_none() {
+            return Ok(PathBuf::from("java"));
+        }
+
+        let assets = AssetManager::new()?.with_offline(true);
+        let game_manifest = self.get_game_manifest(&assets).await?;
+
+        let component = self.java_runtime_component(&game_manifest);
+
+        Ok(assets.java_runtime_executable(component))
+    }
+
+    /// Java runtime component pinned in the manifest, or the one required
+    /// by the version of minecraft
+    fn java_runtime_component<'a>(&'a self, game_manifest: &'a GameManifest) -> &'a str {
+        match &self.manifest.java_runtime {
+            Some(component) => component.as_str(),
+            // older game manifests don't specify a java version
+            None => game_manifest.java_version.as_ref()
+                .map_or("jre-legacy", |v| v.component.as_str())
+        }
+    }
+
+    /// True when the game dir is outside the instance dir, i.e. a vanilla
+    /// profile import sharing the launcher `.minecraft` dir
+    pub fn has_shared_game_dir(&self) -> bool {
+        !self.game_dir().starts_with(&self.dir)
+    }
+
+    /// List files and directories that are recreated at launch or only useful
+    /// for debugging, i.e. natives, logs, crash reports. Files in a shared
+    /// game dir belong to the other launcher too and are left alone.
+    fn junk_paths(&self) -> Result<Vec<PathBuf>> {
+        let game_dir = self.game_dir();
+
+        let mut paths = vec![self.natives_dir()];
+
+        // session logs share the game logs dir when game dir is the instance dir
+        if self.logs_dir() != game_dir.join("logs") {
+            paths.push(self.logs_dir());
+        }
+
+        if !self.has_shared_game_dir() {
+            paths.extend([
+                game_dir.join("logs"),
+                game_dir.join("crash-reports"),
+                game_dir.join(".mixin.out")
+            ]);
+
+            // leftover temp files from interrupted writes
+            if game_dir.exists() {
+                for entry in fs::read_dir(&game_dir)? {
+                    let path = entry?.path();
+                    if path.extension().is_some_and(|ext| ext == "tmp") {
+                        paths.push(path);
+                    }
+                }
+            }
+        }
+
+        Ok(paths.into_iter().filter(|p| p.exists()).collect())
+    }
+
+    /// Delete junk files from the instance, returning paths and sizes in bytes
+    /// of the deleted files; with `dry_run` files are listed but not deleted
+    pub fn clean(&self, dry_run: bool) -> Result<Vec<(PathBuf, u64)>> {
+        let mut result = vec![];
+
+        for path in self.junk_paths()? {
+            let size = super::fs::path_size(&path)?;
+
+            if !dry_run {
+                super::fs::remove_path(&path)?;
+            }
+
+            result.push((path, size));
+        }
+
+        Ok(result)
+    }
+
+    pub fn get_file_type_dir(&self, file_type: &FileType) -> PathBuf {
+        match file_type {
+            FileType::Mod => self.mods_dir(),
+            FileType::Resource => self.resource_pack_dir(),
+            FileType::Shaders => self.shader_pack_dir(),
+            // the game ignores files it doesn't know in the game dir, for the
+            // player to move them where they belong
+            FileType::Unknown(_) => self.game_dir()
+        }
+    }
+
+    pub fn get_file_path(&self, file: &FileDownload) -> PathBuf {
+        self.get_file_type_dir(&file.file_type).join(&file.file_name)
+    }
+
+    /// Copy a manually downloaded file into the instance, after checking it
+    /// matches the CurseForge file
+    pub fn install_file(&self, file: &FileDownload, src_path: &Path) -> Result<()> {
+        if !file.is_valid_file(src_path)? {
+            bail!(Error::FileHashMismatch(file.file_name.clone()));
+        }
+
+        let dest_file = self.get_file_path(file);
+        fs::copy(src_path, dest_file)?;
+        Ok(())
+    }
+
+    /// Copy datapack zip or directory into the `datapacks` directory of the
+    /// world in the saves directory named `world`, returns the copied path
+    pub fn install_datapack(&self, world: &str, src_path: &Path) -> Result<PathBuf> {
+        let world_dir = self.saves_dir().join(world);
+        if !world_dir.is_dir() {
+            bail!(Error::WorldNotFound(world.to_string()));
+        }
+
+        // datapacks are a zip, or a directory with the pack.mcmeta at its root
+        let is_datapack = match src_path.is_dir() {
+            true => src_path.join("pack.mcmeta").exists(),
+            false => src_path.extension().is_some_and(|ext| ext == "zip")
+        };
+
+        let file_name = match src_path.file_name() {
+            Some(file_name) if is_datapack => file_name,
+            _ => bail!(Error::InvalidDatapack(src_path.display().to_string()))
+        };
+
+        let datapacks_dir = world_dir.join("datapacks");
+        fs::create_dir_all(&datapacks_dir)?;
+
+        let dest_path = datapacks_dir.join(file_name);
+
+        if src_path.is_dir() {
+            super::fs::copy_dir_all(src_path, &dest_path)?;
+        } else {
+            fs::copy(src_path, &dest_path)?;
+        }
+
+        Ok(dest_path)
+    }
+
+    /// Write `options.txt` and `servers.dat` into the game directory on first
+    /// launch, from the template directory and the instance manifest
+    fn seed_game_files(&self) -> Result<()> {
+        let template_dir = env::get_template_dir();
+
+        let options_path = self.game_dir().join("options.txt");
+        if !options_path.exists() {
+            let template_path = template_dir.join("options.txt");
+            let template = match template_path.exists() {
+                true => fs::read_to_string(template_path)?,
+                false => String::new()
+            };
+
+            let options = self.manifest.options.clone().unwrap_or_default();
+
+            if !template.is_empty() || !options.is_empty() {
+                fs::write(options_path, merge_options(&template, &options))?;
+            }
+        }
+
+        let servers_path = self.game_dir().join("servers.dat");
+        if !servers_path.exists() {
+            let template_path = template_dir.join("servers.dat");
+
+            if let Some(servers) = &self.manifest.servers {
+                fs::write(servers_path, servers_dat(servers)?)?;
+            } else if template_path.exists() {
+                fs::copy(template_path, servers_path)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Lock the shared data directory and then the instance directory, always
+    /// in that order so concurrent steve processes can't deadlock
+    fn lock(&self, progress: &mut dyn Progress) -> Result<(FileLock, FileLock)> {
+        let data_lock = super::fs::lock_file(&env::get_data_lock_path(), progress)?;
+        let instance_lock = super::fs::lock_file(&self.dir.join(LOCK_FILE), progress)?;
+
+        Ok((data_lock, instance_lock))
+    }
+
+    /// Verify the shared assets and libraries of the instance, downloading
+    /// files that are missing or corrupted, then extract natives and make the
+    /// legacy forge modded jar again; returns paths of the corrupted files
+    pub async fn repair(&self, progress: &mut dyn Progress) -> Result<Vec<PathBuf>> {
+        let _lock = self.lock(progress)?;
+
+        let assets = AssetManager::new()?;
+
+        let game_manifest = self.get_game_manifest(&assets).await?;
+        let asset_manifest = assets.get_asset_manfiest(&game_manifest).await?;
+
+        let loader_manifest = self.get_loader_manifest(&assets).await?;
+
+        let corrupted = assets.remove_corrupted(
+            &game_manifest, &asset_manifest, loader_manifest.as_ref(), progress
+        )?;
+
+        assets.download_assets(&asset_manifest, progress).await?;
+        assets.download_libraries(&game_manifest, progress).await?;
+
+        if let Some(loader_manifest) = &loader_manifest {
+            assets.download_loader_libraries(loader_manifest, progress).await?;
+
+            if let ForgeDistribution::Legacy { jar_mods, .. } = &loader_manifest.dist {
+                let modded_jar_path = asset_manager::modded_jar_path(&loader_manifest.version);
+                if modded_jar_path.exists() {
+                    fs::remove_file(modded_jar_path)?;
+                }
+
+                make_forge_modded_jar(&get_client_jar_path(&game_manifest.id), &loader_manifest.version, jar_mods)?;
+            }
+        }
+
+        // stale natives from a previous version would otherwise be kept
+        let natives_dir = self.natives_dir();
+        if natives_dir.exists() {
+            fs::remove_dir_all(&natives_dir)?;
+        }
+
+        assets.extract_natives(&game_manifest, &natives_dir, progress)?;
+
+        Ok(corrupted)
+    }
+
+    /// Launch instance signed in with the player `session`, from either
+    /// `Account::session` or `Account::offline`
+    pub async fn launch(&mut self,
+        session: &Session,
+        options: &LaunchOptions,
+        progress: &mut dyn Progress
+    ) -> Result<GameProcess> {
+        // held until the game is started, after the shared files are written
+        let _lock = self.lock(progress)?;
+
+        let assets = AssetManager::new()?.with_offline(options.no_update);
+
+        let game_manifest = self.get_game_manifest(&assets).await?;
+        let asset_manifest = assets.get_asset_manfiest(&game_manifest).await?;
+
+        let loader_manifest = self.get_loader_manifest(&assets).await?;
+
+        assets.download_assets(&asset_manifest, progress).await?;
+        assets.download_libraries(&game_manifest, progress).await?;
+
+        if let Some(loader_manifest) = &loader_manifest {
+            assets.download_loader_libraries(loader_manifest, progress).await?;
+        }
+
+        let resources_dir = if asset_manifest.is_virtual.unwrap_or(false) {
+            Some(assets.virtual_assets_dir(&game_manifest.asset_index.id))
+        } else if asset_manifest.map_to_resources.unwrap_or(false) {
+            Some(self.resources_dir())
+        } else {
+            None
+        };
+
+        if let Some(resources_dir) = &resources_dir {
+            assets.copy_resources(&asset_manifest, resources_dir, progress)?;
+        }
+
+        let java_path = self.get_java_path(&assets, &game_manifest, progress).await?;
+
+        let logging_arg = match &game_manifest.logging {
+            Some(logging) => {
+                let config_path = assets.get_logging_config(&logging.client).await?;
+                Some(logging.client.argument.replace("${path}", &config_path.to_string_lossy()))
+            },
+            None => None
+        };
+
+        assets.extract_natives(&game_manifest, &self.natives_dir(), progress)?;
+
+        let mut cmd = LaunchCommand::new(self, &java_path);
+        fs::create_dir_all(self.game_dir())?;
+        self.seed_game_files()?;
+
+        if let Some(logging_arg) = logging_arg {
+            cmd.arg(logging_arg);
+        }
+
+        let mut main_jar: String = get_client_jar_path(&game_manifest.id);
+
+        let resolution = self.manifest.resolution.unwrap_or_else(env::get_default_resolution);
+        let fullscreen = self.manifest.fullscreen.unwrap_or_else(env::get_default_fullscreen);
+
+        let features = options.features();
+
+        // quick play arguments were added in 1.20, older versions can only join servers
+        let quick_play_supported = game_manifest.arguments.as_ref()
+            .is_some_and(|a| a.game.has_feature("has_quick_plays_support"));
+
+        self.check_quick_play(options.quick_play.as_ref(), quick_play_supported)?;
+
+        // older versions don't have argument rules, feature args are added after
+        let mut legacy_args = true;
+
+        if let Some(loader_manifest) = &loader_manifest {
+            match &loader_manifest.dist {
+                // legacy forge distributions required modifying the `minecraft.jar` file
+                ForgeDistribution::Legacy { jar_mods, fml_libs } => {
+                    main_jar = make_forge_modded_jar(&main_jar, &loader_manifest.version, jar_mods)
+                        ?.to_string_lossy().to_string();
+
+                    // forge will throw an error on startup attempting to download
+                    // these libraries (404 not found), unless they already exist
+                    if let Some(fml_libs) = fml_libs {
+                        super::fs::copy_files(
+                            fml_libs.iter()
+                                .map(|l| env::get_libs_dir().join(l.asset_path())),
+                            self.fml_libs_dir()
+                        )?;
+                    }
+
+                    cmd.arg("-Dminecraft.applet.TargetDirectory=${game_directory}");
+                    cmd.arg("-Djava.library.path=${natives_directory}");
+                    cmd.arg("-Dfml.ignoreInvalidMinecraftCertificates=true");
+                    cmd.arg("-Dfml.ignorePatchDiscrepancies=true");
+                    cmd.arg("-cp").arg("${classpath}");
+                    cmd.arg(game_manifest.main_class);
+
+                    if let Some(args) = game_manifest.minecraft_arguments {
+                        cmd.args(args.split(' '));
+                    }
+                },
+                ForgeDistribution::Current { main_class, minecraft_arguments, .. } => {
+                    let minecraft_arguments = minecraft_arguments.clone()
+                        .or(game_manifest.minecraft_arguments);
+
+                    if let Some(args) = minecraft_arguments {
+                        cmd.arg("-Djava.library.path=${natives_directory}");
+                        cmd.arg("-cp").arg("${classpath}");
+                        cmd.arg(main_class);
+                        cmd.args(args.split(' '));
+
+                    // quilt only provides a main class, arguments for newer
+                    // versions of minecraft come from the game manifest
+                    } else if let Some(args) = game_manifest.arguments {
+                        cmd.args(args.jvm.matched_args(&features));
+                        cmd.arg(main_class);
+                        cmd.args(args.game.matched_args(&features));
+                        legacy_args = false;
+                    }
+                }
+            }
+
+            if let Some(tweaks) = &loader_manifest.tweakers {
+                cmd.arg("--tweakClass").arg(tweaks.first().unwrap());
+            }
+
+        // newer versions of minecraft
+        } else if let Some(args) = game_manifest.arguments {
+            cmd.args(args.jvm.matched_args(&features));
+            cmd.arg(game_manifest.main_class);
+            cmd.args(args.game.matched_args(&features));
+            legacy_args = false;
+
+        // older version of minecraft
+        } else if let Some(args) = game_manifest.minecraft_arguments {
+            // older version don't include JVM args in manifest
+            cmd.arg("-Djava.library.path=${natives_directory}");
+            cmd.arg("-cp").arg("${classpath}");
+            cmd.arg(game_manifest.main_class);
+            cmd.args(args.split(' '));
+        }
+
+        if legacy_args {
+            cmd.arg("--width").arg("${resolution_width}");
+            cmd.arg("--height").arg("${resolution_height}");
+
+            if options.demo {
+                cmd.arg("--demo");
+            }
+        }
+
+        if fullscreen {
+            cmd.arg("--fullscreen");
+        }
+
+        if let Some(QuickPlay::Multiplayer(address)) = &options.quick_play {
+            if !quick_play_supported {
+                cmd.args(legacy_server_args(address));
+            }
+        }
+
+        if let Some(args) = &self.manifest.extra_game_args {
+            cmd.args(args.iter().cloned());
+        }
+
+        if let Some(custom_jar) = &self.manifest.custom_jar {
+            main_jar = self.dir.join(custom_jar).to_string_lossy().to_string();
+        }
+
+        let mut libs = vec![main_jar];
+
+        libs.extend(
+            game_manifest.libraries.iter()
+                .filter(|lib| lib.has_rules_match())
+                .filter_map(|lib| lib.downloads.artifact.as_ref())
+                .map(|a| a.path.clone())
         );
 
+        let mut loader_libs = vec![];
+
         if let Some(loader_manifest) = &loader_manifest {
             if let ForgeDistribution::Current { libraries, .. } = &loader_manifest.dist {
-                libs.extend(
+                loader_libs.extend(
                     libraries.iter()
                         .map(|lib| lib.asset_path())
                 );
             }
         }
 
+        let libs = if self.manifest.dedup_libs.unwrap_or(true) {
+            asset_manager::dedup_libs(&libs, &loader_libs)?
+        } else {
+            libs.iter().chain(loader_libs.iter()).collect()
+        };
+
         let classpath = std::env::join_paths(
-            asset_manager::dedup_libs(&libs)?.iter()
+            libs.iter()
                 .map(|p| env::get_libs_dir().join(p))
         )?;
 
         let auth_session = format!("token:{token}:{profileId}",
-            token = account.access_token(), profileId = profile.id);
+            token = session.access_token, profileId = session.uuid);
 
         cmd.arg_ctx("version_name", &self.manifest.mc_version);
         cmd.arg_ctx("version_type", game_manifest.release_type);
@@ -438,12 +2327,24 @@ impl Instance {
         cmd.arg_ctx("assets_index_name", game_manifest.asset_index.id);
         cmd.arg_ctx("classpath", classpath.to_string_lossy());
         cmd.arg_ctx("natives_directory", self.natives_dir().to_string_lossy());
-        cmd.arg_ctx("user_type", "msa");
+        if let Some(quick_play) = &options.quick_play {
+            let (key, value) = match quick_play {
+                QuickPlay::Singleplayer(world) => ("quickPlaySingleplayer", world),
+                QuickPlay::Multiplayer(server) => ("quickPlayMultiplayer", server)
+            };
+
+            cmd.arg_ctx(key, value);
+            cmd.arg_ctx("quickPlayPath", self.game_dir().join("quickPlay").join("log.json").to_string_lossy());
+        }
+
+        cmd.arg_ctx("resolution_width", resolution.width.to_string());
+        cmd.arg_ctx("resolution_height", resolution.height.to_string());
+        cmd.arg_ctx("user_type", session.user_type);
         cmd.arg_ctx("clientid", env::get_msa_client_id());
-        cmd.arg_ctx("auth_access_token", account.access_token());
+        cmd.arg_ctx("auth_access_token", &session.access_token);
         cmd.arg_ctx("auth_session", auth_session);
-        cmd.arg_ctx("auth_player_name", profile.name);
-        cmd.arg_ctx("auth_uuid", profile.id);
+        cmd.arg_ctx("auth_player_name", &session.name);
+        cmd.arg_ctx("auth_uuid", &session.uuid);
         cmd.arg_ctx("launcher_name", env::get_package_name());
         cmd.arg_ctx("launcher_version", env::get_package_version());
         // no idea what this arg does but MC fails to launch unless set to empty json obj
@@ -453,7 +2354,82 @@ impl Instance {
             cmd.arg_ctx("game_assets", path.to_string_lossy());
         }
 
-        Ok(cmd.spawn()?)
+        fs::create_dir_all(self.logs_dir())?;
+
+        let log_path = self.logs_dir()
+            .join(format!("steve-{}.log", Local::now().format("%Y-%m-%d_%H-%M-%S")));
+
+        let process = cmd.spawn(log_path, options.detach)?;
+
+        self.manifest.last_played = Some(Utc::now());
+        self.write_manifest()?;
+
+        // instances created before the registry are added on their next launch
+        InstanceRegistry::register(&self.dir, self.manifest.name.as_deref())?;
+
+        Ok(process)
+    }
+
+    /// Fail early when the quick play target can't be launched, worlds must
+    /// exist and can only be loaded directly by versions with quick play
+    fn check_quick_play(&self, quick_play: Option<&QuickPlay>, supported: bool) -> Result<()> {
+        if let Some(QuickPlay::Singleplayer(world)) = quick_play {
+            if !self.saves_dir().join(world).is_dir() {
+                bail!(Error::WorldNotFound(world.clone()));
+            }
+
+            if !supported {
+                bail!(Error::QuickPlayNotSupported(self.manifest.mc_version.clone()));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Options for a single launch of an instance
+#[derive(Default)]
+pub struct LaunchOptions {
+    /// Write game output only to the session log, for games that outlive steve
+    pub detach: bool,
+
+    /// Launch with the manifests, assets and libraries already downloaded,
+    /// without any network requests for game files
+    pub no_update: bool,
+
+    /// Launch the demo version of the game
+    pub demo: bool,
+
+    /// Load into a world or server directly, skipping the title screen
+    pub quick_play: Option<QuickPlay>
+}
+
+pub enum QuickPlay {
+    /// Name of world directory in `saves`
+    Singleplayer(String),
+
+    /// Server address, `host[:port]` or `[addr]:port` for IPv6
+    Multiplayer(String)
+}
+
+impl LaunchOptions {
+    /// Features enabled in game manifest argument rules
+    fn features(&self) -> Vec<&'static str> {
+        let mut features = vec!["has_custom_resolution"];
+
+        if self.demo {
+            features.push("is_demo_user");
+        }
+
+        if let Some(quick_play) = &self.quick_play {
+            features.push("has_quick_plays_support");
+            features.push(match quick_play {
+                QuickPlay::Singleplayer(_) => "is_quick_play_singleplayer",
+                QuickPlay::Multiplayer(_) => "is_quick_play_multiplayer"
+            });
+        }
+
+        features
     }
 }
 
@@ -464,17 +2440,8 @@ struct LaunchCommand {
 }
 
 impl LaunchCommand {
-    fn new(instance: &Instance) -> Self {
-        // use java override path from instance manifest, or default to "java" in PATH
-        let mut cmd = if let Some(path) = &instance.manifest.java_path {
-            Command::new(path)
-        } else {
-            Command::new("java")
-        };
-
-        if let Some(args) = &instance.manifest.java_args {
-            cmd.args(args);
-        }
+    fn new(instance: &Instance, java_path: &Path) -> Self {
+        let mut cmd = Command::new(java_path);
 
         if let Some(vars) = &instance.manifest.java_env {
             cmd.envs(vars);
@@ -483,11 +2450,29 @@ impl LaunchCommand {
         // set current directory for log output
         cmd.current_dir(instance.game_dir());
 
-        Self {
-            cmd: cmd,
-            ctx: HashMap::new(),
-            args: Vec::new()
+        let mut launch_cmd = Self {
+            cmd,
+            ctx: HashMap::new(),
+            args: Vec::new()
+        };
+
+        launch_cmd.args(env::get_default_java_args());
+
+        if let Some(min) = instance.manifest.min_memory {
+            launch_cmd.arg(format!("-Xms{min}M"));
+        }
+
+        if let Some(max) = instance.manifest.max_memory {
+            launch_cmd.arg(format!("-Xmx{max}M"));
+        }
+
+        // extra args after memory settings, the last -Xmx wins when both are set;
+        // expanded at spawn like the generated args so `${game_directory}` works
+        if let Some(args) = &instance.manifest.java_args {
+            launch_cmd.args(args);
         }
+
+        launch_cmd
     }
 
     fn arg_ctx<S: Into<String>>(&mut self, key: &'static str, val: S) -> &mut Self {
@@ -507,25 +2492,131 @@ impl LaunchCommand {
         self
     }
 
-    fn spawn(&mut self) -> std::io::Result<Child> {
+    fn spawn(&mut self, log_path: PathBuf, detach: bool) -> std::io::Result<GameProcess> {
         for arg in &self.args {
             self.cmd.arg(
                 shellexpand::env_with_context_no_errors(
                     &arg,
-                    |var:&str| self.ctx.get(var)
+                    // fallback to environment for `$HOME` and such in java_args
+                    |var:&str| self.ctx.get(var).cloned().or_else(|| std::env::var(var).ok())
                 ).to_string()
             );
         }
 
-        self.cmd.spawn()
+        GameProcess::spawn(&mut self.cmd, log_path, detach)
+    }
+}
+
+/// Result of installing a modpack
+pub struct PackInstall {
+    /// Files left from a previous version of the pack, the user should be
+    /// prompted to remove them
+    pub remove: Vec<PathBuf>,
+
+    /// Files the user added that are now part of the pack
+    pub conflicts: Vec<PathBuf>,
+
+    /// Config files the user changed since the pack was last installed
+    pub config_conflicts: Vec<PathBuf>,
+
+    /// Files that must be downloaded manually
+    pub blocked: Option<Vec<FileDownload>>
+}
+
+/// Handling of config files the user changed, when a modpack update
+/// includes a different version of the file
+#[derive(Clone, Copy, Default, PartialEq)]
+pub enum ConfigConflicts {
+    /// Leave the changed file as is
+    Keep,
+    /// Replace the changed file with the pack file
+    Overwrite,
+    /// Replace the changed file, keeping a copy with a `.bak` suffix
+    #[default]
+    Backup
+}
+
+impl FromStr for ConfigConflicts {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "keep" => Ok(Self::Keep),
+            "overwrite" => Ok(Self::Overwrite),
+            "backup" => Ok(Self::Backup),
+            _ => Err(Error::InvalidConfigConflicts(s.into()))
+        }
+    }
+}
+
+/// Singleplayer world details from its `level.dat`
+pub struct World {
+    /// Directory name in the saves directory, used to join the world at launch
+    pub dir_name: String,
+    /// Name shown in the game, the directory name when unknown
+    pub name: String,
+    /// Minecraft version the world was last played with, worlds from before
+    /// 1.9 don't have one
+    pub version: Option<String>,
+    pub seed: Option<i64>,
+    pub last_played: Option<DateTime<Utc>>
+}
+
+impl World {
+    fn from_level(dir_name: String, data: Option<&nbt::Tag>) -> Self {
+        let name = data.and_then(|d| d.get("LevelName"))
+            .and_then(nbt::Tag::as_str)
+            .map_or_else(|| dir_name.clone(), String::from);
+
+        let version = data.and_then(|d| d.get("Version"))
+            .and_then(|v| v.get("Name"))
+            .and_then(nbt::Tag::as_str)
+            .map(String::from);
+
+        // the seed moved to world gen settings in 1.16
+        let seed = data.and_then(|d| d.get("WorldGenSettings"))
+            .and_then(|s| s.get("seed"))
+            .or_else(|| data.and_then(|d| d.get("RandomSeed")))
+            .and_then(nbt::Tag::as_i64);
+
+        let last_played = data.and_then(|d| d.get("LastPlayed"))
+            .and_then(nbt::Tag::as_i64)
+            .and_then(|millis| Utc.timestamp_millis_opt(millis).single());
+
+        World { dir_name, name, version, seed, last_played }
     }
 }
 
+/// Game dir files changed by a modpack install, `removed` are the extra files
+/// the install would prompt to remove
+#[derive(Default)]
+pub struct PackChanges {
+    pub added: Vec<PathBuf>,
+    pub replaced: Vec<PathBuf>,
+    pub removed: Vec<PathBuf>
+}
+
 #[derive(Clone)]
 pub enum FileType {
     Mod,
     Resource,
-    Shaders
+    Shaders,
+    /// CurseForge class without a known directory, i.e. worlds (17)
+    Unknown(u64)
+}
+
+impl FileType {
+    /// File type of CurseForge class id
+    pub fn from_class_id(class_id: u64) -> Self {
+        // it feels brittle using hard coded classId, but I don't see anything
+        // else that can differentiate mods|resource pack|etc
+        match class_id {
+            6 => FileType::Mod,
+            12 => FileType::Resource,
+            6552 => FileType::Shaders,
+            x => FileType::Unknown(x)
+        }
+    }
 }
 
 #[derive(Clone)]
@@ -533,19 +2624,16 @@ pub struct FileDownload {
     pub file_name: String,
     pub file_type: FileType,
     pub can_auto_download: bool,
-    pub url: String
+    pub url: String,
+    pub project_id: u64,
+    pub file_id: u64,
+    pub fingerprint: u32,
+    pub sha1: Option<String>
 }
 
 impl FileDownload {
     pub fn new(f: &CurseForgeFile, m: &CurseForgeMod) -> Self {
-        // it feels brittle using hard coded classId, but I don't see anything
-        // else that can differentiate mods|resource pack|etc
-        let file_type = match m.class_id {
-            6 => FileType::Mod,
-            12 => FileType::Resource,
-            6552 => FileType::Shaders,
-            x => panic!("Unimplemented curseforge class_id {x}")
-        };
+        let file_type = FileType::from_class_id(m.class_id);
 
         // url for user to download the file manually
         let user_dl_url = format!("{site_url}/download/{file_id}",
@@ -558,12 +2646,374 @@ impl FileDownload {
             url: match &f.download_url {
                 Some(v) => v.clone(),
                 None => user_dl_url
+            },
+            project_id: m.mod_id,
+            file_id: f.file_id,
+            fingerprint: f.file_fingerprint,
+            sha1: f.sha1().map(String::from)
+        }
+    }
+
+    /// Check the file at `path` against the CurseForge SHA-1, or the
+    /// fingerprint when CurseForge doesn't list a SHA-1
+    pub fn is_valid_file(&self, path: &Path) -> std::io::Result<bool> {
+        match &self.sha1 {
+            Some(sha1) => Ok(hash::sha1(path)?.eq_ignore_ascii_case(sha1)),
+            None => Ok(hash::curseforge_fingerprint(path)? == self.fingerprint)
+        }
+    }
+}
+
+/// Find files CurseForge doesn't allow downloading that are also hosted on
+/// Modrinth, matched by SHA-1. Returns the files with the Modrinth url, and the
+/// files that must still be downloaded manually.
+async fn find_modrinth_downloads(
+    client: &AssetClient,
+    blocked: Vec<FileDownload>
+) -> (Vec<FileDownload>, Vec<FileDownload>) {
+    let sha1_list: Vec<_> = blocked.iter()
+        .filter_map(|f| f.sha1.clone())
+        .collect();
+
+    if sha1_list.is_empty() {
+        return (vec![], blocked);
+    }
+
+    // Modrinth being unavailable leaves the files to the manual download
+    let versions = client.get_modrinth_versions_from_hashes(&sha1_list).await
+        .unwrap_or_default();
+
+    let mut mirrored = vec![];
+    let mut remaining = vec![];
+
+    for mut file in blocked {
+        let url = file.sha1.as_ref()
+            .and_then(|sha1| versions.get(sha1)
+                .and_then(|v| v.files.iter().find(|f| f.hashes.sha1.eq_ignore_ascii_case(sha1))))
+            .map(|f| f.url.clone());
+
+        match url {
+            Some(url) => {
+                file.url = url;
+                file.can_auto_download = true;
+                mirrored.push(file);
+            },
+            None => remaining.push(file)
+        }
+    }
+
+    (mirrored, remaining)
+}
+
+/// Download the CurseForge file, downloading again when the file doesn't
+/// match the CurseForge hash
+async fn download_verified(client: &AssetClient, file: &FileDownload, path: &Path) -> Result<()> {
+    const ATTEMPTS: usize = 2;
+
+    for _ in 0..ATTEMPTS {
+        client.download_file(&file.url, path).await?;
+
+        if file.is_valid_file(path)? {
+            return Ok(());
+        }
+    }
+
+    fs::remove_file(path)?;
+
+    bail!(Error::FileHashMismatch(file.file_name.clone()))
+}
+
+/// Update toml document with values from the manifest, entries that haven't
+/// changed are left untouched to preserve comments and formatting
+fn update_toml_manifest(doc: &mut toml_edit::Document, manifest: &InstanceManifest) -> Result<()> {
+    let old_values: serde_json::Value = toml_edit::de::from_document(doc.clone())?;
+    let new_values = serde_json::to_value(manifest)?;
+    let new_doc = toml_edit::ser::to_document(manifest)?;
+
+    // `None` values aren't serialized, remove them from the document
+    let unset_keys: Vec<_> = doc.iter()
+        .map(|(key, _)| key.to_string())
+        .filter(|key| !new_doc.contains_key(key))
+        .collect();
+
+    for key in unset_keys {
+        doc.remove(&key);
+    }
+
+    for (key, item) in new_doc.iter() {
+        if old_values.get(key) != new_values.get(key) {
+            doc[key] = item.clone();
+        }
+    }
+
+    Ok(())
+}
+
+/// Lines of `options.txt` with `options` replacing values of the same key,
+/// new keys are added at the end
+fn merge_options(template: &str, options: &BTreeMap<String, String>) -> String {
+    let mut remaining = options.clone();
+    let mut lines = vec![];
+
+    for line in template.lines() {
+        let line = match line.split_once(':') {
+            Some((key, _)) => match remaining.remove(key) {
+                Some(value) => format!("{key}:{value}"),
+                None => line.to_string()
+            },
+            None => line.to_string()
+        };
+
+        lines.push(line);
+    }
+
+    lines.extend(remaining.iter().map(|(key, value)| format!("{key}:{value}")));
+
+    lines.iter().map(|line| format!("{line}\n")).collect()
+}
+
+/// Uncompressed NBT of the multiplayer server list
+fn servers_dat(servers: &[InstanceServer]) -> Result<Vec<u8>> {
+    let servers = servers.iter()
+        .map(|server| nbt::Tag::Compound(BTreeMap::from([
+            ("name".to_string(), nbt::Tag::String(server.name.clone())),
+            ("ip".to_string(), nbt::Tag::String(server.ip.clone()))
+        ])))
+        .collect();
+
+    let root = nbt::Tag::Compound(BTreeMap::from([
+        ("servers".to_string(), nbt::Tag::List(servers))
+    ]));
+
+    let mut data = vec![];
+    nbt::write(&mut data, &root)?;
+
+    Ok(data)
+}
+
+/// Match files to CurseForge files by fingerprint, returns map of file path
+/// to fingerprint match; files without a match are excluded
+async fn match_curseforge_files(
+    client: &AssetClient,
+    paths: &[PathBuf],
+    progress: &mut dyn Progress
+) -> Result<HashMap<PathBuf, CurseForgeFingerprintMatch>> {
+    progress.begin("Fingerprinting files...", paths.len());
+
+    let mut fingerprints = Vec::new();
+
+    for (i, path) in paths.iter().enumerate() {
+        progress.advance(i + 1);
+        fingerprints.push((path.clone(), hash::curseforge_fingerprint(path)?));
+    }
+
+    progress.end();
+
+    if fingerprints.is_empty() {
+        return Ok(HashMap::new());
+    }
+
+    let mut fingerprint_list: Vec<_> = fingerprints.iter().map(|(_, f)| *f).collect();
+    fingerprint_list.sort();
+    fingerprint_list.dedup();
+
+    let matches = client.get_curseforge_fingerprint_matches(&fingerprint_list).await?;
+
+    Ok(match_fingerprints(&fingerprints, matches))
+}
+
+/// Pair each path with the match of its fingerprint, copies of the same file
+/// in several places each get the match
+fn match_fingerprints(
+    fingerprints: &[(PathBuf, u32)],
+    matches: Vec<CurseForgeFingerprintMatch>
+) -> HashMap<PathBuf, CurseForgeFingerprintMatch> {
+    let matches: HashMap<_, _> = matches.into_iter()
+        .map(|m| (m.file.file_fingerprint, m))
+        .collect();
+
+    fingerprints.iter()
+        .filter_map(|(path, fingerprint)| matches.get(fingerprint).map(|m| (path.clone(), m.clone())))
+        .collect()
+}
+
+/// Pack manifest files of paths matched to CurseForge project and file ids,
+/// and the paths they reference. A pack installs each file once, so copies
+/// of a file after the first are left out to be added as overrides.
+fn curseforge_pack_files(mut matches: Vec<(PathBuf, u64, u64)>) -> (Vec<CurseForgePackFile>, HashSet<PathBuf>) {
+    matches.sort();
+
+    let mut referenced = HashSet::new();
+    let mut files = vec![];
+    let mut paths = HashSet::new();
+
+    for (path, project_id, file_id) in matches {
+        if !referenced.insert((project_id, file_id)) {
+            continue;
+        }
+
+        files.push(CurseForgePackFile {
+            project_id,
+            file_id,
+            // disabled mods are exported as optional
+            required: !is_disabled_mod(&path)
+        });
+
+        paths.insert(path);
+    }
+
+    files.sort_by_key(|f| f.project_id);
+
+    (files, paths)
+}
+
+/// Absolute path of `path` with symlinks of its nearest existing ancestor
+/// resolved, for paths that may not exist yet
+fn resolve_path(path: &Path) -> std::io::Result<PathBuf> {
+    let path = std::path::absolute(path)?;
+
+    // components after the existing ancestor, i.e. the dirs a copy would create
+    let mut rest = vec![];
+    let mut existing = path.as_path();
+
+    while !existing.exists() {
+        match (existing.parent(), existing.file_name()) {
+            (Some(parent), Some(name)) => {
+                rest.push(name.to_os_string());
+                existing = parent;
+            },
+            _ => break
+        }
+    }
+
+    let mut resolved = fs::canonicalize(existing)?;
+    resolved.extend(rest.iter().rev());
+
+    Ok(resolved)
+}
+
+fn is_disabled_mod(mod_file: &Path) -> bool {
+    mod_file.to_string_lossy().ends_with(DISABLED_SUFFIX)
+}
+
+/// Fetch CurseForge file and mod details needed to download files
+async fn fetch_curseforge_downloads(
+    client: &AssetClient,
+    file_ids: &Vec<u64>,
+    project_ids: &Vec<u64>
+) -> Result<Vec<FileDownload>> {
+    let mut file_list = client.get_curseforge_file_list(file_ids).await?;
+    let mut mod_list = client.get_curseforge_mods(project_ids).await?;
+
+    if file_list.len() != mod_list.len() {
+        bail!(Error::CurseFileListMismatch {
+            file_list_len: file_list.len(),
+            mod_list_len: mod_list.len()
+        });
+    }
+
+    // sort the lists so that we can zip them into list of pairs
+    file_list.sort_by_key(|f| f.mod_id);
+    mod_list.sort_by_key(|m| m.mod_id);
+
+    Ok(file_list.iter()
+        .zip(mod_list)
+        .map(|(f, m)| FileDownload::new(f, &m))
+        .collect())
+}
+
+/// `--server` and `--port` arguments for joining a server in versions
+/// before quick play
+fn legacy_server_args(address: &str) -> [String; 4] {
+    let (host, port) = protocol::split_address(address);
+    ["--server".to_string(), host.to_string(), "--port".to_string(), port.to_string()]
+}
+
+/// Instance directory name for profile name, replacing path separators and
+/// other characters that aren't safe in file names
+fn dir_name(name: &str) -> String {
+    name.chars()
+        .map(|c| if c.is_alphanumeric() || matches!(c, ' ' | '-' | '_' | '.') { c } else { '_' })
+        .collect::<String>()
+        .trim_matches(['.', ' '])
+        .to_string()
+}
+
+/// Files in the mods lock that are missing from the game dir, or where the
+/// file no longer matches the hash recorded when it was installed
+fn stale_lock_files<'a>(game_dir: &Path, lock: &'a ModsLock) -> std::io::Result<Vec<&'a ModsLockFile>> {
+    let mut stale = Vec::new();
+
+    for f in &lock.files {
+        let path = game_dir.join(&f.path);
+        let path = if path.exists() {
+            path
+        } else {
+            let disabled_path = with_disabled_suffix(&path);
+            if !disabled_path.exists() {
+                stale.push(f);
+                continue;
+            }
+            disabled_path
+        };
+
+        let is_valid = match &f.source {
+            ModsLockSource::CurseForge { fingerprint, .. } =>
+                hash::curseforge_fingerprint(&path)? == *fingerprint,
+            ModsLockSource::Url { sha1, .. } =>
+                hash::sha1(&path)?.eq_ignore_ascii_case(sha1)
+        };
+
+        if !is_valid {
+            stale.push(f);
+        }
+    }
+
+    Ok(stale)
+}
+
+fn with_disabled_suffix(path: &Path) -> PathBuf {
+    let mut file_name = path.file_name().unwrap().to_os_string();
+    file_name.push(DISABLED_SUFFIX);
+    path.with_file_name(file_name)
+}
+
+/// Unused backup path for `path`, `<name>.bak` or `<name>.<n>.bak` when
+/// earlier backups exist
+fn backup_path(path: &Path) -> PathBuf {
+    let file_name = path.file_name().unwrap().to_string_lossy();
+
+    (0..)
+        .map(|n| match n {
+            0 => path.with_file_name(format!("{file_name}.bak")),
+            n => path.with_file_name(format!("{file_name}.{n}.bak"))
+        })
+        .find(|p| !p.exists())
+        .unwrap()
+}
+
+/// Directory contains an instance manifest
+pub(crate) fn is_instance_dir(dir: &Path) -> bool {
+    dir.join(MANIFEST_FILE).exists() || dir.join(MANIFEST_TOML_FILE).exists()
+}
+
+/// List files in directory, or empty list if directory doesn't exist
+fn list_files(dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut files = vec![];
+
+    if dir.exists() {
+        for entry in fs::read_dir(dir)? {
+            let entry = entry?;
+            if entry.file_type()?.is_file() {
+                files.push(entry.path());
             }
         }
     }
+
+    Ok(files)
 }
 
-fn list_extra_files(dir: &Path, downloads: &Vec<FileDownload>) -> Result<Vec<PathBuf>> {
+fn list_extra_files(dir: &Path, downloads: &[FileDownload]) -> Result<Vec<PathBuf>> {
     let mut delete_files: Vec<PathBuf> = vec![];
 
     if dir.exists() {
@@ -578,3 +3028,429 @@ fn list_extra_files(dir: &Path, downloads: &Vec<FileDownload>) -> Result<Vec<Pat
 
     Ok(delete_files)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Temporary instance directory with a manifest and empty game dir,
+    /// removed when dropped so a failing assert doesn't leave it behind
+    struct TestDir(PathBuf);
+
+    impl TestDir {
+        fn new(name: &str) -> Self {
+            let dir = std::env::temp_dir().join(format!("steve-{name}-test-{}", std::process::id()));
+            let _ = fs::remove_dir_all(&dir);
+
+            fs::create_dir_all(dir.join("minecraft")).unwrap();
+            fs::write(dir.join(MANIFEST_FILE), r#"{"mc_version": "1.20.1", "game_dir": "minecraft"}"#).unwrap();
+
+            TestDir(dir)
+        }
+
+        fn instance(&self) -> Instance {
+            Instance::load(&self.0).unwrap()
+        }
+    }
+
+    impl std::ops::Deref for TestDir {
+        type Target = Path;
+
+        fn deref(&self) -> &Path {
+            &self.0
+        }
+    }
+
+    impl Drop for TestDir {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    #[test]
+    fn update_toml_keeps_comments() {
+        let mut doc = r#"# vanilla instance
+mc_version = "1.20.1"
+game_dir = "minecraft"
+
+# lots of ram
+java_args = [
+    "-Xmx8G", # max heap
+]
+"#.parse::<toml_edit::Document>().unwrap();
+
+        let mut manifest: InstanceManifest = toml_edit::de::from_document(doc.clone()).unwrap();
+        manifest.mc_version = "1.20.2".to_string();
+
+        update_toml_manifest(&mut doc, &manifest).unwrap();
+
+        let result = doc.to_string();
+        assert!(result.contains("mc_version = \"1.20.2\""));
+        assert!(result.contains("# lots of ram"));
+        assert!(result.contains("# max heap"));
+    }
+
+    #[test]
+    fn world_from_level() {
+        let data = nbt::Tag::Compound(BTreeMap::from([
+            ("LevelName".to_string(), nbt::Tag::String("Survival".to_string())),
+            ("LastPlayed".to_string(), nbt::Tag::Long(1700000000000)),
+            ("RandomSeed".to_string(), nbt::Tag::Long(-42)),
+            ("Version".to_string(), nbt::Tag::Compound(BTreeMap::from([
+                ("Name".to_string(), nbt::Tag::String("1.12.2".to_string()))
+            ])))
+        ]));
+
+        let world = World::from_level("world".to_string(), Some(&data));
+        assert_eq!(world.name, "Survival");
+        assert_eq!(world.version.as_deref(), Some("1.12.2"));
+        assert_eq!(world.seed, Some(-42));
+        assert_eq!(world.last_played.map(|t| t.timestamp()), Some(1700000000));
+
+        let world = World::from_level("broken".to_string(), None);
+        assert_eq!(world.name, "broken");
+        assert!(world.version.is_none());
+    }
+
+    #[test]
+    fn merge_options_replaces_keys() {
+        let options = BTreeMap::from([
+            ("guiScale".to_string(), "2".to_string()),
+            ("lang".to_string(), "de_de".to_string())
+        ]);
+
+        let result = merge_options("version:3465\nlang:en_us\nfov:0.0\n", &options);
+        assert_eq!(result, "version:3465\nlang:de_de\nfov:0.0\nguiScale:2\n");
+    }
+
+    #[test]
+    fn clone_into_self_with_unresolved_dest() {
+        let dir = TestDir::new("clone");
+        let instance = dir.instance();
+
+        // path to a dir inside the instance that only starts with the
+        // instance dir once `..` is resolved
+        let temp_dir = std::env::temp_dir();
+        let dest = temp_dir.join("..")
+            .join(temp_dir.file_name().unwrap())
+            .join(dir.file_name().unwrap())
+            .join("copy");
+
+        let result = instance.clone_to(&dest);
+
+        assert!(matches!(result.err().and_then(|e| e.downcast::<Error>().ok()), Some(Error::CloneIntoSelf)));
+        assert!(!dir.join("copy").exists());
+    }
+
+    fn fingerprint_match(mod_id: u64, file_id: u64, fingerprint: u32) -> CurseForgeFingerprintMatch {
+        serde_json::from_value(serde_json::json!({
+            "id": mod_id,
+            "file": {
+                "id": file_id,
+                "modId": mod_id,
+                "fileName": format!("{file_id}.jar"),
+                "downloadUrl": null,
+                "fileFingerprint": fingerprint
+            }
+        })).unwrap()
+    }
+
+    #[test]
+    fn match_fingerprints_of_copies() {
+        let fingerprints = [
+            (PathBuf::from("mods/a.jar"), 1),
+            (PathBuf::from("mods/a-copy.jar"), 1),
+            (PathBuf::from("mods/b.jar"), 2)
+        ];
+
+        let matches = match_fingerprints(&fingerprints, vec![fingerprint_match(10, 100, 1)]);
+
+        assert_eq!(matches.len(), 2);
+        assert_eq!(matches[Path::new("mods/a.jar")].file.file_id, 100);
+        assert_eq!(matches[Path::new("mods/a-copy.jar")].file.file_id, 100);
+        assert!(!matches.contains_key(Path::new("mods/b.jar")));
+    }
+
+    #[test]
+    fn pack_files_of_matched_files() {
+        let (files, paths) = curseforge_pack_files(vec![
+            (PathBuf::from("mods/b.jar.disabled"), 20, 200),
+            (PathBuf::from("mods/a.jar"), 10, 100),
+            (PathBuf::from("mods/a-copy.jar"), 10, 100)
+        ]);
+
+        let files: Vec<_> = files.iter()
+            .map(|f| (f.project_id, f.file_id, f.required))
+            .collect();
+
+        assert_eq!(files, [(10, 100, true), (20, 200, false)]);
+
+        // only the first copy by path is referenced, the other goes in the overrides
+        assert!(paths.contains(Path::new("mods/a-copy.jar")));
+        assert!(!paths.contains(Path::new("mods/a.jar")));
+        assert!(paths.contains(Path::new("mods/b.jar.disabled")));
+    }
+
+    #[test]
+    fn export_overrides_of_unmatched_files() {
+        let dir = TestDir::new("export");
+        fs::create_dir_all(dir.join("minecraft/mods")).unwrap();
+        fs::create_dir_all(dir.join("minecraft/config")).unwrap();
+
+        for file in ["mods/matched.jar", "mods/unmatched.jar", "config/mod.toml"] {
+            fs::write(dir.join("minecraft").join(file), file).unwrap();
+        }
+
+        let instance = dir.instance();
+
+        let matched = HashSet::from([instance.mods_dir().join("matched.jar")]);
+        let mut overrides: Vec<_> = instance.list_overrides(&matched).unwrap()
+            .into_iter()
+            .map(|(name, _)| name)
+            .collect();
+        overrides.sort();
+
+        let is_export_into_self = |instance: &Instance, path: &Path| matches!(
+            instance.check_export_path(path).err().and_then(|e| e.downcast::<Error>().ok()),
+            Some(Error::ExportIntoSelf)
+        );
+
+        assert_eq!(overrides, ["overrides/config/mod.toml", "overrides/mods/unmatched.jar"]);
+        assert!(is_export_into_self(&instance, &instance.game_dir().join("pack.zip")));
+        assert!(!is_export_into_self(&instance, &std::env::temp_dir().join("pack.zip")));
+
+        // shared game dir outside the instance, i.e. an imported vanilla profile
+        let game_dir = TestDir::new("export-game-dir");
+        let mut instance = instance;
+        instance.manifest.game_dir = game_dir.to_string_lossy().to_string();
+
+        assert!(is_export_into_self(&instance, &game_dir.join("pack.zip")));
+    }
+
+    #[test]
+    fn stale_lock_files_by_hash() {
+        let dir = TestDir::new("mods-lock");
+        fs::create_dir_all(dir.join("mods")).unwrap();
+
+        for file in ["good.jar", "bad.jar", "good-url.jar", "bad-url.jar", "off.jar.disabled"] {
+            fs::write(dir.join("mods").join(file), file).unwrap();
+        }
+
+        let file = |path: &str, source: ModsLockSource| ModsLockFile {
+            path: format!("mods/{path}"),
+            source,
+            disabled: false
+        };
+        let curseforge = |data: &str| ModsLockSource::CurseForge {
+            project_id: 1,
+            file_id: 1,
+            fingerprint: hash::curseforge_fingerprint(&dir.join("mods").join(data)).unwrap()
+        };
+        let url = |sha1: String| ModsLockSource::Url { url: String::new(), sha1 };
+
+        let lock = ModsLock {
+            files: vec![
+                file("good.jar", curseforge("good.jar")),
+                file("bad.jar", curseforge("good.jar")),
+                file("good-url.jar", url(hash::sha1(&dir.join("mods/good-url.jar")).unwrap().to_uppercase())),
+                file("bad-url.jar", url("0000".to_string())),
+                file("off.jar", curseforge("off.jar.disabled")),
+                file("missing.jar", url("0000".to_string()))
+            ],
+            ..Default::default()
+        };
+
+        let stale: Vec<_> = stale_lock_files(&dir, &lock).unwrap().into_iter().map(|f| f.path.as_str()).collect();
+        assert_eq!(stale, ["mods/bad.jar", "mods/bad-url.jar", "mods/missing.jar"]);
+    }
+
+    #[test]
+    fn copy_overrides_config_conflicts() {
+        for mode in [ConfigConflicts::Keep, ConfigConflicts::Overwrite, ConfigConflicts::Backup] {
+            let dir = TestDir::new("overrides");
+            let pack_dir = dir.join("pack");
+            fs::create_dir_all(dir.join("minecraft/config")).unwrap();
+            fs::create_dir_all(pack_dir.join("config")).unwrap();
+
+            let instance = dir.instance();
+            let config_dir = instance.game_dir().join("config");
+
+            // changed.toml was edited by the user, same.toml is as installed,
+            // new.toml has no hash from the previous install
+            for (file, current) in [("changed.toml", "user"), ("same.toml", "v1"), ("new.toml", "user")] {
+                fs::write(pack_dir.join("config").join(file), "v2").unwrap();
+                fs::write(config_dir.join(file), current).unwrap();
+            }
+
+            let installed_hash = hash::sha1(&config_dir.join("same.toml")).unwrap();
+
+            let previous = ModsLock {
+                config_hashes: BTreeMap::from([
+                    ("config/changed.toml".to_string(), installed_hash.clone()),
+                    ("config/same.toml".to_string(), installed_hash)
+                ]),
+                ..Default::default()
+            };
+
+            // an earlier backup is left alone
+            fs::write(config_dir.join("changed.toml.bak"), "old").unwrap();
+
+            let files = ["changed.toml", "same.toml", "new.toml"]
+                .map(|f| Path::new("config").join(f))
+                .to_vec();
+
+            let mut lock = ModsLock::default();
+            let conflicts = instance.copy_overrides(&pack_dir, files, &previous, &mut lock, mode).unwrap();
+
+            let read = |file: &str| fs::read_to_string(config_dir.join(file)).ok();
+            let changed = read("changed.toml");
+            let same = read("same.toml");
+            let new = read("new.toml");
+            let old_backup = read("changed.toml.bak");
+            let backup = read("changed.toml.1.bak");
+
+            assert_eq!(conflicts, [config_dir.join("changed.toml")]);
+            assert_eq!(lock.config_hashes.len(), 3);
+            assert_eq!(same.as_deref(), Some("v2"));
+            assert_eq!(new.as_deref(), Some("v2"));
+            assert_eq!(old_backup.as_deref(), Some("old"));
+
+            match mode {
+                ConfigConflicts::Keep => {
+                    assert_eq!(changed.as_deref(), Some("user"));
+                    assert_eq!(backup, None);
+                },
+                ConfigConflicts::Overwrite => {
+                    assert_eq!(changed.as_deref(), Some("v2"));
+                    assert_eq!(backup, None);
+                },
+                ConfigConflicts::Backup => {
+                    assert_eq!(changed.as_deref(), Some("v2"));
+                    assert_eq!(backup.as_deref(), Some("user"));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn user_files_across_updates() {
+        let dir = TestDir::new("user-files");
+        let instance = dir.instance();
+        let mods_dir = instance.mods_dir();
+
+        let pack_file = |path: &str| ModsLockFile {
+            path: path.to_string(),
+            source: ModsLockSource::Url { url: String::new(), sha1: String::new() },
+            disabled: false
+        };
+        let mod_path = |name: &str| mods_dir.join(name);
+
+        // first install of the pack, the user added mine.jar and removed
+        // gone.jar before the update
+        let previous = ModsLock {
+            files: vec![pack_file("mods/pack-a.jar"), pack_file("mods/pack-b.jar")],
+            user_files: vec!["mods/gone.jar".to_string(), "mods/mine.jar".to_string(), "mods/now-pack.jar".to_string()],
+            ..Default::default()
+        };
+
+        // the update drops pack-b.jar and adds the user's now-pack.jar
+        let mut lock = ModsLock {
+            files: vec![pack_file("mods/now-pack.jar"), pack_file("mods/pack-a.jar")],
+            ..Default::default()
+        };
+
+        let extra_files = vec![mod_path("pack-b.jar"), mod_path("mine.jar"), mod_path("added.jar")];
+
+        let (remove, user) = instance.split_user_files(&previous, extra_files.clone());
+        assert_eq!(remove, [mod_path("pack-b.jar")]);
+        assert_eq!(user, [mod_path("mine.jar"), mod_path("added.jar")]);
+
+        let install = instance.track_user_files(&previous, &mut lock, extra_files, None);
+        assert_eq!(install.remove, [mod_path("pack-b.jar")]);
+        assert_eq!(install.conflicts, [mod_path("now-pack.jar")]);
+        assert_eq!(lock.user_files, ["mods/added.jar", "mods/mine.jar"]);
+
+        instance.write_mods_lock(&lock).unwrap();
+        instance.keep_user_files(&install.conflicts).unwrap();
+        let lock = instance.load_mods_lock().unwrap();
+        assert_eq!(lock.user_files, ["mods/added.jar", "mods/mine.jar", "mods/now-pack.jar"]);
+
+        // a later update that drops now-pack.jar leaves it as the user's file
+        let (remove, user) = instance.split_user_files(&lock, vec![mod_path("now-pack.jar"), mod_path("pack-a.jar")]);
+        assert_eq!(remove, [mod_path("pack-a.jar")]);
+        assert_eq!(user, [mod_path("now-pack.jar")]);
+    }
+
+    #[test]
+    fn quick_play_server_and_world() {
+        assert_eq!(legacy_server_args("example.com"), ["--server", "example.com", "--port", "25565"]);
+        assert_eq!(legacy_server_args("example.com:25566"), ["--server", "example.com", "--port", "25566"]);
+        assert_eq!(legacy_server_args("[::1]:25566"), ["--server", "::1", "--port", "25566"]);
+
+        let options = |quick_play| LaunchOptions { quick_play, ..Default::default() };
+        assert_eq!(options(Some(QuickPlay::Singleplayer("world".to_string()))).features(),
+            ["has_custom_resolution", "has_quick_plays_support", "is_quick_play_singleplayer"]);
+        assert_eq!(options(Some(QuickPlay::Multiplayer("example.com".to_string()))).features(),
+            ["has_custom_resolution", "has_quick_plays_support", "is_quick_play_multiplayer"]);
+        assert_eq!(options(None).features(), ["has_custom_resolution"]);
+    }
+
+    #[test]
+    fn quick_play_version_gating() {
+        // 1.20 and later list quick play arguments, older versions have none
+        let args: crate::json::GameArgs = serde_json::from_str(r#"[
+            "--demo",
+            {
+                "rules": [{"action": "allow", "features": {"has_quick_plays_support": true}}],
+                "value": ["--quickPlayPath", "${quickPlayPath}"]
+            }
+        ]"#).unwrap();
+        assert!(args.has_feature("has_quick_plays_support"));
+
+        let args: crate::json::GameArgs = serde_json::from_str(r#"["--username", "${auth_player_name}"]"#).unwrap();
+        assert!(!args.has_feature("has_quick_plays_support"));
+
+        let dir = TestDir::new("quick-play");
+        fs::create_dir_all(dir.join("minecraft/saves/world")).unwrap();
+
+        let instance = dir.instance();
+
+        let world = QuickPlay::Singleplayer("world".to_string());
+        let missing = QuickPlay::Singleplayer("missing".to_string());
+        let server = QuickPlay::Multiplayer("example.com".to_string());
+
+        let check = |quick_play, supported| instance.check_quick_play(quick_play, supported)
+            .err()
+            .and_then(|e| e.downcast::<Error>().ok());
+
+        let supported_world = check(Some(&world), true);
+        let unsupported_world = check(Some(&world), false);
+        let missing_world = check(Some(&missing), true);
+        let unsupported_server = check(Some(&server), false);
+        let no_quick_play = check(None, false);
+
+        assert!(supported_world.is_none());
+        assert!(matches!(unsupported_world, Some(Error::QuickPlayNotSupported(v)) if v == "1.20.1"));
+        assert!(matches!(missing_world, Some(Error::WorldNotFound(w)) if w == "missing"));
+        // older versions join servers with --server and --port instead
+        assert!(unsupported_server.is_none());
+        assert!(no_quick_play.is_none());
+    }
+
+    #[test]
+    fn junk_paths_of_shared_game_dir() {
+        let dir = TestDir::new("junk");
+        let shared = TestDir::new("junk-shared");
+
+        for path in [dir.join("natives"), dir.join("minecraft/crash-reports"), shared.join("logs"), shared.join("crash-reports")] {
+            fs::create_dir_all(path).unwrap();
+        }
+
+        let mut instance = dir.instance();
+        assert_eq!(instance.junk_paths().unwrap(), [dir.join("natives"), dir.join("minecraft/crash-reports")]);
+
+        instance.manifest.game_dir = shared.to_string_lossy().to_string();
+        assert!(instance.has_shared_game_dir());
+        assert_eq!(instance.junk_paths().unwrap(), [dir.join("natives")]);
+    }
+}
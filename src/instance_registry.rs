@@ -0,0 +1,174 @@
+/*
+ * Steve Launcher - A Minecraft Launcher
+ * Copyright (C) 2023 Josh Kropf <josh@slashdev.ca>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::{collections::{BTreeMap, HashSet}, fs, path::{Path, PathBuf}};
+
+use crate::{asset_manager::AssetManager, env, instance, Instance, Progress};
+
+/// Names and directories of the instances created or launched by steve, used
+/// to find instances by name and shared data no instance refers to
+#[derive(Default, Deserialize, Serialize)]
+pub struct InstanceRegistry {
+    instances: BTreeMap<String, PathBuf>
+}
+
+impl InstanceRegistry {
+    fn registry_path() -> PathBuf {
+        env::get_data_dir().join("instances.json")
+    }
+
+    pub fn load() -> Result<Self> {
+        let path = Self::registry_path();
+
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        Ok(serde_json::from_str(&fs::read_to_string(path)?)?)
+    }
+
+    pub fn save(&self) -> Result<()> {
+        fs::create_dir_all(env::get_data_dir())?;
+        super::fs::write_atomic(&Self::registry_path(), serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// Add instance directory to the registry file with the instance `name`,
+    /// or the directory name, with a number added when the name is taken.
+    /// Returns the registered name.
+    pub fn register(instance_dir: &Path, name: Option<&str>) -> Result<String> {
+        let instance_dir = std::path::absolute(instance_dir)?;
+        let mut registry = Self::load()?;
+
+        if let Some(registered) = registry.name_of(&instance_dir) {
+            // keep registered name unless the instance has been renamed
+            if name.is_none_or(|n| n == registered) {
+                return Ok(registered.to_string());
+            }
+
+            registry.instances.retain(|_, dir| *dir != instance_dir);
+        }
+
+        let base_name = match name {
+            Some(name) => name.to_string(),
+            None => instance_dir.file_name()
+                .map_or(String::from("instance"), |n| n.to_string_lossy().to_string())
+        };
+
+        let mut name = base_name.clone();
+        let mut count = 1;
+
+        while registry.instances.contains_key(&name) {
+            count += 1;
+            name = format!("{base_name}-{count}");
+        }
+
+        registry.instances.insert(name.clone(), instance_dir);
+        registry.save()?;
+
+        Ok(name)
+    }
+
+    /// Remove instance directory from the registry file
+    pub fn unregister(instance_dir: &Path) -> Result<()> {
+        let instance_dir = std::path::absolute(instance_dir)?;
+        let mut registry = Self::load()?;
+
+        let count = registry.instances.len();
+        registry.instances.retain(|_, dir| *dir != instance_dir);
+
+        if registry.instances.len() != count {
+            registry.save()?;
+        }
+
+        Ok(())
+    }
+
+    /// Directory of instance registered with `name`
+    pub fn get(&self, name: &str) -> Option<&PathBuf> {
+        self.instances.get(name)
+    }
+
+    /// Registered instances with names containing `query`, ignoring case
+    pub fn search(&self, query: &str) -> Vec<(&String, &PathBuf)> {
+        let query = query.to_lowercase();
+
+        self.instances()
+            .filter(|(name, _)| name.to_lowercase().contains(&query))
+            .collect()
+    }
+
+    /// Registered name of instance directory
+    pub fn name_of(&self, instance_dir: &Path) -> Option<&str> {
+        self.instances.iter()
+            .find(|(_, dir)| *dir == instance_dir)
+            .map(|(name, _)| name.as_str())
+    }
+
+    /// Names and directories of registered instances that still exist, sorted by name
+    pub fn instances(&self) -> impl Iterator<Item = (&String, &PathBuf)> {
+        self.instances.iter().filter(|(_, dir)| instance::is_instance_dir(dir))
+    }
+
+    /// Registered instance directories that still contain an instance
+    pub fn instance_dirs(&self) -> impl Iterator<Item = &PathBuf> {
+        self.instances().map(|(_, dir)| dir)
+    }
+
+    /// Delete shared libraries, assets and cached files not used by any of
+    /// the registered instances, see `AssetManager::collect_garbage`. Instances
+    /// that no longer exist are removed from the registry. With
+    /// `processor_output`, the output of the Forge installer processors is
+    /// deleted even when in use, and rebuilt at the next launch.
+    pub async fn collect_garbage(&mut self,
+        dry_run: bool,
+        processor_output: bool,
+        progress: &mut dyn Progress
+    ) -> Result<Vec<(PathBuf, u64)>> {
+        // files downloaded by a concurrent launch would be unreferenced
+        let _lock = super::fs::lock_file(&env::get_data_lock_path(), progress)?;
+
+        let assets = AssetManager::new()?;
+
+        let instances: BTreeMap<_, _> = self.instances()
+            .map(|(name, dir)| (name.clone(), dir.clone()))
+            .collect();
+
+        let mut referenced = HashSet::new();
+
+        for dir in instances.values() {
+            let instance = Instance::load(dir)?;
+            referenced.extend(instance.shared_paths(&assets).await?);
+
+            if processor_output {
+                for path in instance.processor_output_paths(&assets).await? {
+                    referenced.remove(&path);
+                }
+            }
+        }
+
+        if !dry_run && instances != self.instances {
+            self.instances = instances;
+            self.save()?;
+        }
+
+        assets.collect_garbage(&referenced, dry_run)
+    }
+}
@@ -0,0 +1,95 @@
+/*
+ * Steve Launcher - A Minecraft Launcher
+ * Copyright (C) 2023 Josh Kropf <josh@slashdev.ca>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use anyhow::Result;
+use std::{fs, path::PathBuf};
+
+use crate::{asset_manager::AssetManager, env, Progress};
+
+/// Mojang java runtime installed in the shared data directory
+pub struct JavaRuntime {
+    /// Runtime component name, i.e. `java-runtime-gamma`
+    pub component: String,
+    pub version: String,
+    pub dir: PathBuf
+}
+
+impl JavaRuntime {
+    /// List runtimes that have completed installing
+    pub fn list_installed() -> Result<Vec<JavaRuntime>> {
+        let runtimes_dir = env::get_runtimes_dir();
+        if !runtimes_dir.exists() {
+            return Ok(vec![]);
+        }
+
+        let mut runtimes = vec![];
+
+        for entry in fs::read_dir(runtimes_dir)? {
+            let dir = entry?.path();
+
+            if let Ok(version) = fs::read_to_string(dir.join(".version")) {
+                runtimes.push(JavaRuntime {
+                    component: dir.file_name().unwrap().to_string_lossy().to_string(),
+                    version,
+                    dir
+                });
+            }
+        }
+
+        runtimes.sort_by(|a, b| a.component.cmp(&b.component));
+
+        Ok(runtimes)
+    }
+
+    /// Install runtime for the major version of java, i.e. 17
+    pub async fn install(major_version: u32, progress: &mut dyn Progress) -> Result<JavaRuntime> {
+        let assets = AssetManager::new()?;
+
+        let component = assets.get_java_runtime_component(major_version).await?;
+        assets.get_java_runtime(&component, progress).await?;
+
+        let dir = assets.java_runtime_dir(&component);
+        let version = fs::read_to_string(dir.join(".version"))?;
+
+        Ok(JavaRuntime { component, version, dir })
+    }
+}
+
+/// Major version from java version name, i.e. `17.0.8` or `8u51` or `1.8.0_51`
+pub fn java_major_version(version: &str) -> Option<u32> {
+    let mut parts = version.split(|c: char| !c.is_ascii_digit());
+
+    match parts.next()?.parse().ok()? {
+        1 => parts.next()?.parse().ok(),
+        major => Some(major)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn major_version_formats() {
+        assert_eq!(java_major_version("17.0.8"), Some(17));
+        assert_eq!(java_major_version("8u51"), Some(8));
+        assert_eq!(java_major_version("1.8.0_51"), Some(8));
+        assert_eq!(java_major_version("21"), Some(21));
+        assert_eq!(java_major_version("beta"), None);
+    }
+}
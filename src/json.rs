@@ -23,7 +23,15 @@ mod forge_manifest;
 mod forge_version_manifest;
 mod game_manifest;
 mod instance_manifest;
+mod java_runtime_manifest;
+mod launcher_profiles;
 mod modpacks_ch;
+mod mojang_profile;
+mod modrinth_pack;
+mod mods_lock;
+mod prism_pack;
+mod server_lists;
+mod server_status;
 mod version_manifest;
 
 pub use account_manifest::*;
@@ -33,7 +41,15 @@ pub use forge_manifest::*;
 pub use forge_version_manifest::*;
 pub use game_manifest::*;
 pub use instance_manifest::*;
+pub use java_runtime_manifest::*;
+pub use launcher_profiles::*;
 pub use modpacks_ch::*;
+pub use mojang_profile::*;
+pub use modrinth_pack::*;
+pub use mods_lock::*;
+pub use prism_pack::*;
+pub use server_lists::*;
+pub use server_status::*;
 pub use version_manifest::*;
 
 use serde::{Deserialize, Deserializer};
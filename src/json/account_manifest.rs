@@ -19,10 +19,78 @@
 use chrono::{DateTime, serde::ts_seconds, Utc};
 use serde::{Deserialize, Serialize};
 
+/// Stored accounts, the account manifests are stored separately
+#[derive(Deserialize, Serialize, Default)]
+pub struct AccountIndex {
+    /// Id of the active account
+    pub active: Option<String>,
+    pub accounts: Vec<AccountIndexEntry>
+}
+
+impl AccountIndex {
+    /// Find account by id or profile name
+    pub fn find(&self, name_or_id: &str) -> Option<&AccountIndexEntry> {
+        self.accounts.iter().find(|a| a.id == name_or_id)
+            .or_else(|| self.accounts.iter().find(|a| a.name == name_or_id))
+    }
+
+    /// Add account, or update the name of the account with the same id
+    pub fn insert(&mut self, id: &str, name: &str) {
+        match self.accounts.iter_mut().find(|a| a.id == id) {
+            Some(entry) => entry.name = name.to_string(),
+            None => self.accounts.push(AccountIndexEntry {
+                id: id.to_string(),
+                name: name.to_string()
+            })
+        }
+    }
+
+    /// Change the id of an account, keeping it active
+    pub fn rekey(&mut self, old_id: &str, id: &str) {
+        for entry in self.accounts.iter_mut().filter(|a| a.id == old_id) {
+            entry.id = id.to_string();
+        }
+
+        if self.active.as_deref() == Some(old_id) {
+            self.active = Some(id.to_string());
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize)]
+#[serde(from = "AccountIndexEntryRepr")]
+pub struct AccountIndexEntry {
+    /// Minecraft profile UUID the account is stored under, or the profile
+    /// name for accounts stored by older versions until their next launch
+    pub id: String,
+    /// Minecraft profile name, updated at each launch
+    pub name: String
+}
+
+/// Older versions listed accounts by name only, with the name as the id
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum AccountIndexEntryRepr {
+    Name(String),
+    Entry { id: String, name: String }
+}
+
+impl From<AccountIndexEntryRepr> for AccountIndexEntry {
+    fn from(repr: AccountIndexEntryRepr) -> Self {
+        match repr {
+            AccountIndexEntryRepr::Name(name) => Self { id: name.clone(), name },
+            AccountIndexEntryRepr::Entry { id, name } => Self { id, name }
+        }
+    }
+}
+
 #[derive(Deserialize, Serialize)]
 pub struct AccountManifest {
     pub msa_token: MicrosoftToken,
-    pub mc_token: MinecraftToken
+    pub mc_token: MinecraftToken,
+    /// Minecraft profile UUID, missing from accounts stored by older versions
+    /// until their next launch
+    pub profile_id: Option<String>
 }
 
 #[derive(Deserialize, Serialize)]
@@ -87,3 +155,28 @@ pub struct MinecraftCape {
 
     pub alias: String
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn index_keyed_by_name_rekeyed_by_id() {
+        let json = r#"{"active":"Alex","accounts":["default","Alex"]}"#;
+        let mut index = serde_json::from_str::<AccountIndex>(json).unwrap();
+
+        assert_eq!(index.find("Alex").map(|a| a.id.as_str()), Some("Alex"));
+
+        index.rekey("Alex", "ec561538f3fd461daff5086b22154bce");
+        index.insert("ec561538f3fd461daff5086b22154bce", "Steve");
+
+        assert_eq!(index.active.as_deref(), Some("ec561538f3fd461daff5086b22154bce"));
+        assert_eq!(index.accounts.len(), 2);
+        assert!(index.find("Alex").is_none());
+        assert_eq!(index.find("Steve").map(|a| a.id.as_str()), Some("ec561538f3fd461daff5086b22154bce"));
+
+        let json = serde_json::to_string(&index).unwrap();
+        let index = serde_json::from_str::<AccountIndex>(&json).unwrap();
+        assert_eq!(index.find("ec561538f3fd461daff5086b22154bce").map(|a| a.name.as_str()), Some("Steve"));
+    }
+}
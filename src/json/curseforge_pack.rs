@@ -16,16 +16,16 @@
  * along with this program.  If not, see <https://www.gnu.org/licenses/>.
  */
 
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
 use crate::{Error, ModLoader};
 
-#[derive(Deserialize)]
+#[derive(Deserialize, Serialize)]
 pub struct CurseForgePack {
     pub minecraft: CurseForgeMinecraft,
-    #[serde(rename(deserialize = "manifestType"))]
+    #[serde(rename(deserialize = "manifestType", serialize = "manifestType"))]
     pub manifest_type: String,
-    #[serde(rename(deserialize = "manifestVersion"))]
+    #[serde(rename(deserialize = "manifestVersion", serialize = "manifestVersion"))]
     pub manifest_version: u8,
     pub name: String,
     pub version: String,
@@ -48,11 +48,17 @@ impl CurseForgePack {
     }
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, Serialize)]
 pub struct CurseForgeMinecraft {
     pub version: String,
-    #[serde(rename(deserialize = "modLoaders"))]
-    pub mod_loaders: Vec<CurseForgeModloader>
+    #[serde(rename(deserialize = "modLoaders", serialize = "modLoaders"))]
+    pub mod_loaders: Vec<CurseForgeModloader>,
+    /// Memory in megabytes the pack author recommends
+    #[serde(
+        rename(deserialize = "recommendedRam", serialize = "recommendedRam"),
+        default, skip_serializing_if = "Option::is_none"
+    )]
+    pub recommended_ram: Option<u32>
 }
 
 impl CurseForgeMinecraft {
@@ -69,17 +75,17 @@ impl CurseForgeMinecraft {
     }
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, Serialize)]
 pub struct CurseForgeModloader {
     pub id: String,
     pub primary: bool
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, Serialize)]
 pub struct CurseForgePackFile {
-    #[serde(rename(deserialize = "projectID"))]
+    #[serde(rename(deserialize = "projectID", serialize = "projectID"))]
     pub project_id: u64,
-    #[serde(rename(deserialize = "fileID"))]
+    #[serde(rename(deserialize = "fileID", serialize = "fileID"))]
     pub file_id: u64,
     pub required: bool
 }
@@ -90,6 +96,28 @@ pub struct CurseForgeResponse<T> {
 }
 
 #[derive(Deserialize)]
+// https://docs.curseforge.com/#search-mods
+pub struct CurseForgeSearchResponse {
+    pub data: Vec<CurseForgeMod>,
+    pub pagination: CurseForgePagination
+}
+
+#[derive(Deserialize)]
+// https://docs.curseforge.com/#tocS_Pagination
+pub struct CurseForgePagination {
+    #[serde(rename(deserialize = "totalCount"))]
+    pub total_count: u32
+}
+
+#[derive(Deserialize)]
+// https://docs.curseforge.com/#tocS_Category
+pub struct CurseForgeCategory {
+    pub id: u32,
+    pub name: String,
+    pub slug: String
+}
+
+#[derive(Clone, Deserialize)]
 // https://docs.curseforge.com/#tocS_File
 pub struct CurseForgeFile {
     #[serde(rename(deserialize = "id"))]
@@ -99,7 +127,76 @@ pub struct CurseForgeFile {
     #[serde(rename(deserialize = "fileName"))]
     pub file_name: String,
     #[serde(rename(deserialize = "downloadUrl"))]
-    pub download_url: Option<String>
+    pub download_url: Option<String>,
+    #[serde(rename(deserialize = "fileFingerprint"))]
+    pub file_fingerprint: u32,
+    #[serde(default)]
+    pub hashes: Vec<CurseForgeFileHash>,
+    #[serde(default)]
+    pub dependencies: Vec<CurseForgeFileDependency>
+}
+
+impl CurseForgeFile {
+    pub fn sha1(&self) -> Option<&str> {
+        self.hashes.iter()
+            .find(|h| h.is_sha1())
+            .map(|h| h.value.as_str())
+    }
+}
+
+#[derive(Clone, Deserialize)]
+// https://docs.curseforge.com/#tocS_FileHash
+pub struct CurseForgeFileHash {
+    pub value: String,
+    pub algo: u8
+}
+
+impl CurseForgeFileHash {
+    pub fn is_sha1(&self) -> bool {
+        // https://docs.curseforge.com/#tocS_HashAlgo
+        self.algo == 1
+    }
+}
+
+#[derive(Clone, Deserialize)]
+// https://docs.curseforge.com/#tocS_FileDependency
+pub struct CurseForgeFileDependency {
+    #[serde(rename(deserialize = "modId"))]
+    pub mod_id: u64,
+    #[serde(rename(deserialize = "relationType"))]
+    pub relation_type: u8
+}
+
+impl CurseForgeFileDependency {
+    pub fn is_required(&self) -> bool {
+        // https://docs.curseforge.com/#tocS_FileRelationType
+        self.relation_type == 3
+    }
+}
+
+#[derive(Deserialize)]
+// https://docs.curseforge.com/#get-mod-file-changelog
+pub struct CurseForgeChangelogResponse {
+    pub data: String
+}
+
+#[derive(Deserialize)]
+pub struct CurseForgeFingerprintResponse {
+    pub data: CurseForgeFingerprintMatches
+}
+
+#[derive(Deserialize)]
+// https://docs.curseforge.com/#tocS_FingerprintsMatchesResult
+pub struct CurseForgeFingerprintMatches {
+    #[serde(rename(deserialize = "exactMatches"))]
+    pub exact_matches: Vec<CurseForgeFingerprintMatch>
+}
+
+#[derive(Clone, Deserialize)]
+pub struct CurseForgeFingerprintMatch {
+    #[serde(rename(deserialize = "id"))]
+    pub mod_id: u64,
+    pub file: CurseForgeFile
 }
 
 #[derive(Deserialize)]
@@ -107,10 +204,36 @@ pub struct CurseForgeFile {
 pub struct CurseForgeMod {
     #[serde(rename(deserialize = "id"))]
     pub mod_id: u64,
+    pub name: String,
     pub slug: String,
+    #[serde(default)]
+    pub summary: String,
     pub links: CurseForgeModLinks,
     #[serde(rename(deserialize = "classId"))]
-    pub class_id: u64
+    pub class_id: u64,
+    #[serde(rename(deserialize = "latestFilesIndexes"))]
+    pub latest_files_indexes: Vec<CurseForgeFileIndex>
+}
+
+impl CurseForgeMod {
+    /// Check if mod has a file for the minecraft version and mod loader type
+    pub fn has_file_for(&self, mc_version: &str, loader_type: Option<u8>) -> bool {
+        self.latest_files_indexes.iter()
+            .filter(|i| i.game_version == mc_version)
+            .any(|i| loader_type.is_none() || i.mod_loader.is_none() || i.mod_loader == loader_type)
+    }
+}
+
+#[derive(Deserialize)]
+// https://docs.curseforge.com/#tocS_FileIndex
+pub struct CurseForgeFileIndex {
+    #[serde(rename(deserialize = "gameVersion"))]
+    pub game_version: String,
+    #[serde(rename(deserialize = "fileId"))]
+    pub file_id: u64,
+    pub filename: String,
+    #[serde(rename(deserialize = "modLoader"))]
+    pub mod_loader: Option<u8>
 }
 
 #[derive(Deserialize)]
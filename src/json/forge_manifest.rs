@@ -23,17 +23,12 @@ use super::{AssetDownload, ForgeVersionRequires};
 
 #[derive(Deserialize)]
 pub struct ForgeManifest {
-    #[serde(rename(deserialize = "+traits"))]
-    pub traits: Option<Vec<String>>,
     #[serde(rename(deserialize = "+tweakers"))]
     pub tweakers: Option<Vec<String>>,
 
     #[serde(flatten)]
     pub dist: ForgeDistribution,
 
-    pub name: String,
-    #[serde(rename(deserialize = "releaseTime"))]
-    pub release_time: String,
     pub requires: Vec<ForgeVersionRequires>,
     pub uid: String,
     pub version: String
@@ -43,7 +38,7 @@ impl ForgeManifest {
     pub fn get_minecraft_version(&self) -> Result<String, Error> {
         self.requires.iter()
             .find(|r| r.uid == "net.minecraft")
-            .map(|r| r.equals.clone())
+            .and_then(|r| r.equals.clone())
             .ok_or(Error::ForgeRequiresNotFound)
     }
 }
@@ -71,7 +66,6 @@ pub enum ForgeDistribution {
 #[serde(untagged)]
 pub enum ForgeLibrary {
     Downloads {
-        name: String,
         downloads: ForgeDownloads
     },
     Url {
@@ -92,12 +86,21 @@ impl ForgeLibrary {
         match self {
             ForgeLibrary::Downloads { downloads, .. } => downloads.artifact.download.url.clone(),
             ForgeLibrary::Url { url, .. } => match url {
-                Some(url) => format!("{url}/{path}", path = self.asset_path()),
+                Some(url) => format!("{url}/{path}",
+                    url = url.trim_end_matches('/'), path = self.asset_path()),
                 None => format!("https://libraries.minecraft.net/{path}", path = self.asset_path())
             }
         }
     }
 
+    /// SHA-1 hash of the library, libraries from a maven url don't have one
+    pub fn sha1(&self) -> Option<&String> {
+        match self {
+            ForgeLibrary::Downloads { downloads, .. } => Some(&downloads.artifact.download.sha1),
+            ForgeLibrary::Url { .. } => None
+        }
+    }
+
     pub fn fml_libs_1_3() -> Vec<Self> {
         serde_json::from_str(include_str!("fml_libs_1.3.json")).unwrap()
     }
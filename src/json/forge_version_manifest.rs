@@ -26,21 +26,23 @@ pub struct ForgeVersionManifest {
 #[derive(Deserialize, Clone)]
 pub struct ForgeVersionManifestEntry {
     pub recommended: bool,
-    #[serde(rename(deserialize = "releaseTime"))]
-    pub release_time: String,
     pub requires: Vec<ForgeVersionRequires>,
-    pub sha256: String,
     pub version: String
 }
 
 impl ForgeVersionManifestEntry {
+    /// Versions without a `net.minecraft` requirement (quilt) work with any version
     pub fn is_for_mc_version(&self, mc_version: &str) -> bool {
-        self.requires.iter().any(|r| r.equals == mc_version)
+        match self.requires.iter().find(|r| r.uid == "net.minecraft") {
+            Some(r) => r.equals.as_deref() == Some(mc_version),
+            None => true
+        }
     }
 }
 
 #[derive(Deserialize, Clone)]
 pub struct ForgeVersionRequires {
-    pub equals: String,
+    // quilt requires `net.fabricmc.intermediary` without a version
+    pub equals: Option<String>,
     pub uid: String
 }
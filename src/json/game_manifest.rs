@@ -19,16 +19,13 @@
 use serde::Deserialize;
 use std::collections::HashMap;
 
-use crate::{env, rules::RulesMatch};
+use crate::{env, rules::{self, RulesMatch}};
 
 #[derive(Deserialize)]
 pub struct GameManifest {
     pub arguments: Option<GameArgsIndex>,
     #[serde(rename(deserialize = "assetIndex"))]
     pub asset_index: GameAssetIndex,
-    pub assets: String,
-    #[serde(rename(deserialize = "complianceLevel"))]
-    pub compliance_level: Option<u8>,
     pub downloads: GameDownloads,
     pub id: String,
     #[serde(rename(deserialize = "javaVersion"))]
@@ -39,11 +36,6 @@ pub struct GameManifest {
     pub main_class: String,
     #[serde(rename(deserialize = "minecraftArguments"))]
     pub minecraft_arguments: Option<String>,
-    #[serde(rename(deserialize = "minimumLauncherVersion"))]
-    pub minimum_launcher_version: u8,
-    #[serde(rename(deserialize = "releaseTime"))]
-    pub release_time: String,
-    pub time: String,
     #[serde(rename(deserialize = "type"))]
     pub release_type: String
 }
@@ -59,9 +51,10 @@ pub struct GameArgsIndex {
 pub struct GameArgs(pub Vec<GameArg>);
 
 impl GameArgs {
-    pub fn matched_args(&self) -> impl Iterator<Item = String> + '_ {
+    /// Arguments with rules matching the host and enabled launch `features`
+    pub fn matched_args<'a>(&'a self, features: &'a [&'a str]) -> impl Iterator<Item = String> + 'a {
         self.0.iter()
-            .filter(|arg| arg.rules.matches())
+            .filter(|arg| arg.rules.matches_features(features))
             .flat_map(|arg| {
                 match &arg.value {
                     GameArgValue::Single(v) => vec![v.clone()],
@@ -71,6 +64,16 @@ impl GameArgs {
     }
 }
 
+impl GameArgs {
+    /// True when any argument has a rule for launch `feature`
+    pub fn has_feature(&self, feature: &str) -> bool {
+        self.0.iter()
+            .flat_map(|arg| &arg.rules)
+            .filter_map(|rule| rule.features.as_ref())
+            .any(|features| features.contains_key(feature))
+    }
+}
+
 #[derive(Deserialize)]
 struct GameArgsRaw(Vec<GameArgTypes>);
 
@@ -132,18 +135,12 @@ impl From<GameArgsRaw> for GameArgs {
 pub struct GameAssetIndex {
     pub id: String,
     #[serde(flatten)]
-    pub download: AssetDownload,
-    #[serde(rename(deserialize = "totalSize"))]
-    pub total_size: u64
+    pub download: AssetDownload
 }
 
 #[derive(Deserialize)]
 pub struct GameDownloads {
-    pub client: AssetDownload,
-    pub client_mappings: Option<AssetDownload>,
-    pub server: Option<AssetDownload>,
-    pub server_mappings: Option<AssetDownload>,
-    pub server_windows: Option<AssetDownload>
+    pub client: AssetDownload
 }
 
 #[derive(Deserialize)]
@@ -155,15 +152,12 @@ pub struct AssetDownload {
 
 #[derive(Deserialize)]
 pub struct GameJavaVersion {
-    pub component: String,
-    #[serde(rename(deserialize = "majorVersion"))]
-    pub major_version: u8
+    pub component: String
 }
 
 #[derive(Deserialize)]
 pub struct GameLibrary {
     pub downloads: GameLibraryDownloads,
-    pub extract: Option<GameLibraryExtract>,
     pub name: String,
     pub natives: Option<HashMap<String, String>>,
     pub rules: Option<Vec<GameLibraryRule>>,
@@ -171,12 +165,17 @@ pub struct GameLibrary {
 
 impl GameLibrary {
     pub fn has_rules_match(&self) -> bool {
-        match &self.rules {
+        let rules_match = match &self.rules {
             Some(rules) => rules.matches(),
 
             // lib matches if rules don't exist
             None => true
-        }
+        };
+
+        // newer manifests list natives for every architecture of the os as
+        // separate libs, i.e. `org.lwjgl:lwjgl:3.3.1:natives-macos-arm64`
+        rules_match && self.name.split(':').nth(3)
+            .is_none_or(rules::natives_classifier_matches)
     }
 
     pub fn natives_artifact(&self) -> Result<Option<&GameLibraryArtifact>, GameLibError> {
@@ -184,19 +183,22 @@ impl GameLibrary {
             Some(natives) => {
                 let host_os = env::get_host_os();
 
+                // older manifests key 32 and 64 bit windows natives with
+                // `natives-windows-${arch}`
                 let natives_key = natives.get(host_os)
                     .ok_or(GameLibError::OsNotFound {
                         lib_name: self.name.to_string(),
                         os_name: host_os.to_string()
-                    })?;
+                    })?
+                    .replace("${arch}", env::get_host_arch_bits());
 
                 let classifiers = self.downloads.classifiers.as_ref()
                     .ok_or(GameLibError::ClassifiersNotFound(self.name.to_string()))?;
 
-                let artifact = classifiers.get(natives_key)
+                let artifact = classifiers.get(&natives_key)
                     .ok_or(GameLibError::ClassifierNativeKeyNotFound {
                         lib_name: self.name.to_string(),
-                        natives_key: natives_key.to_string()
+                        natives_key
                     })?;
 
                 Some(artifact)
@@ -257,11 +259,6 @@ pub struct GameLibraryArtifact {
     pub download: AssetDownload
 }
 
-#[derive(Deserialize)]
-pub struct GameLibraryExtract {
-    pub exclude: Vec<String>
-}
-
 #[derive(Deserialize)]
 pub struct GameLibraryRule {
     pub action: String,
@@ -276,9 +273,7 @@ pub struct GameLogging {
 #[derive(Deserialize)]
 pub struct GameLoggingClient {
     pub argument: String,
-    pub file: GameLoggingArtifact,
-    #[serde(rename(deserialize = "type"))]
-    pub file_type: String
+    pub file: GameLoggingArtifact
 }
 
 #[derive(Deserialize)]
@@ -16,42 +16,140 @@
  * along with this program.  If not, see <https://www.gnu.org/licenses/>.
  */
 
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use std::{collections::HashMap, str::FromStr};
+use std::{collections::{BTreeMap, HashMap}, fmt, str::FromStr};
 
 use crate::Error;
 
 #[derive(Deserialize, Serialize)]
 pub struct InstanceManifest {
+    /// Optional name the instance is registered with, in place of the directory name
+    pub name: Option<String>,
+
     /// Minecraft version
     pub mc_version: String,
 
     /// Minecraft directory, relative to instance manifest
     pub game_dir: String,
 
-    /// Optional absolute path of Java VM, or use "java" in system path
+    /// Optional absolute path of Java VM, or use the mojang java runtime
     pub java_path: Option<String>,
 
+    /// Optional mojang java runtime component (i.e. java-runtime-gamma) to use
+    /// in place of the runtime for the minecraft version
+    pub java_runtime: Option<String>,
+
     /// Optional extra JVM arguments
     pub java_args: Option<Vec<String>>,
 
+    /// Optional initial JVM heap size in megabytes (`-Xms`)
+    pub min_memory: Option<u32>,
+
+    /// Optional maximum JVM heap size in megabytes (`-Xmx`)
+    pub max_memory: Option<u32>,
+
+    /// Optional extra game arguments, appended after the game manifest arguments
+    pub extra_game_args: Option<Vec<String>>,
+
     /// Optional environment variables
     pub java_env: Option<HashMap<String, String>>,
 
     /// Optional mod loader
     pub mod_loader: Option<ModLoader>,
 
+    /// Optional game window size, or the `resolution` config default
+    pub resolution: Option<WindowResolution>,
+
+    /// Optionally launch the game fullscreen, or the `fullscreen` config default
+    pub fullscreen: Option<bool>,
+
+    /// Optional profile UUID or name of account to launch with, in place of
+    /// the active account
+    pub account: Option<String>,
+
+    /// Optional modpack the instance was installed from
+    pub modpack: Option<InstanceModpack>,
+
+    /// Time of the most recent launch
+    pub last_played: Option<DateTime<Utc>>,
+
+    /// Total time played, counted when steve waits for the game to exit
+    pub play_time_seconds: Option<u64>,
+
     /// Optional path to alternate `minecraft.jar`, relative to instance manifest
-    pub custom_jar: Option<String>
+    pub custom_jar: Option<String>,
+
+    /// Optionally keep every library on the classpath, including duplicates
+    pub dedup_libs: Option<bool>,
+
+    /// Optional game options (i.e. `lang`, `guiScale`) written to `options.txt`
+    /// on first launch, replacing the same options from the template
+    pub options: Option<BTreeMap<String, String>>,
+
+    /// Optional servers written to the multiplayer list on first launch, in
+    /// place of the template `servers.dat`
+    pub servers: Option<Vec<InstanceServer>>
 }
 
-#[derive(Deserialize, Serialize)]
+#[derive(Deserialize, Serialize, Clone)]
+pub struct InstanceServer {
+    pub name: String,
+
+    /// Server address as host[:port]
+    pub ip: String
+}
+
+#[derive(Deserialize, Serialize, Clone, Copy)]
+pub struct WindowResolution {
+    pub width: u32,
+    pub height: u32
+}
+
+impl Default for WindowResolution {
+    fn default() -> Self {
+        Self { width: 854, height: 480 }
+    }
+}
+
+impl FromStr for WindowResolution {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.split_once('x')
+            .and_then(|(w, h)| Some(Self { width: w.parse().ok()?, height: h.parse().ok()? }))
+            .ok_or(Error::InvalidResolution(s.to_string()))
+    }
+}
+
+impl fmt::Display for WindowResolution {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}x{}", self.width, self.height)
+    }
+}
+
+#[derive(Deserialize, Serialize, Clone)]
+pub struct InstanceModpack {
+    /// Where the modpack came from, i.e. `curseforge`, `modpacksch` or `zip`
+    pub source: String,
+
+    /// Modpack id, `None` for packs installed from zip file
+    pub id: Option<u32>,
+
+    pub name: String,
+    pub version: String
+}
+
+#[derive(Deserialize, Serialize, Clone)]
 pub enum ModLoaderName {
     #[serde(rename = "forge")]
     Forge,
 
     #[serde(rename = "neoforge")]
-    NeoForge
+    NeoForge,
+
+    #[serde(rename = "quilt")]
+    Quilt
 }
 
 impl FromStr for ModLoaderName {
@@ -61,27 +159,64 @@ impl FromStr for ModLoaderName {
         match s {
             "forge" => Ok(Self::Forge),
             "neoforge" => Ok(Self::NeoForge),
+            "quilt" => Ok(Self::Quilt),
             _ => Err(Error::InvalidModLoaderName(s.into()))
         }
     }
 }
 
-impl ToString for ModLoaderName {
-    fn to_string(&self) -> String {
+impl ModLoaderName {
+    /// Mod loader type id used by the CurseForge API
+    pub fn curseforge_type(&self) -> u8 {
+        match self {
+            Self::Forge => 1,
+            Self::NeoForge => 6,
+            Self::Quilt => 5
+        }
+    }
+
+    /// Component uid used in Prism Launcher `mmc-pack.json`
+    pub fn prism_uid(&self) -> &'static str {
         match self {
-            Self::Forge => String::from("forge"),
-            Self::NeoForge => String::from("neoforge")
+            Self::Forge => "net.minecraftforge",
+            Self::NeoForge => "net.neoforged",
+            Self::Quilt => "org.quiltmc.quilt-loader"
+        }
+    }
+
+    /// Dependency key used in Modrinth modpack index
+    pub fn modrinth_dependency(&self) -> &'static str {
+        match self {
+            Self::Forge => "forge",
+            Self::NeoForge => "neoforge",
+            Self::Quilt => "quilt-loader"
         }
     }
 }
 
-#[derive(Deserialize, Serialize)]
+impl fmt::Display for ModLoaderName {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Forge => write!(f, "forge"),
+            Self::NeoForge => write!(f, "neoforge"),
+            Self::Quilt => write!(f, "quilt")
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Clone)]
 pub struct ModLoader {
     pub name: ModLoaderName,
 
     pub version: String
 }
 
+impl fmt::Display for ModLoader {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{name}-{ver}", name = self.name, ver = self.version)
+    }
+}
+
 impl FromStr for ModLoader {
     type Err = Error;
 
@@ -0,0 +1,64 @@
+/*
+ * Steve Launcher - A Minecraft Launcher
+ * Copyright (C) 2023 Josh Kropf <josh@slashdev.ca>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use serde::Deserialize;
+use std::collections::HashMap;
+
+use super::AssetDownload;
+
+/// Mojang java runtimes, keyed by platform and then by component name
+pub type JavaRuntimeIndex = HashMap<String, HashMap<String, Vec<JavaRuntimeEntry>>>;
+
+#[derive(Deserialize)]
+pub struct JavaRuntimeEntry {
+    pub manifest: AssetDownload,
+    pub version: JavaRuntimeVersion
+}
+
+#[derive(Deserialize)]
+pub struct JavaRuntimeVersion {
+    pub name: String,
+    pub released: String
+}
+
+#[derive(Deserialize)]
+pub struct JavaRuntimeManifest {
+    pub files: HashMap<String, JavaRuntimeFile>
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "type")]
+pub enum JavaRuntimeFile {
+    #[serde(rename = "directory")]
+    Directory,
+    #[serde(rename = "file")]
+    File {
+        downloads: JavaRuntimeFileDownloads,
+        executable: bool
+    },
+    #[serde(rename = "link")]
+    Link {
+        target: String
+    }
+}
+
+#[derive(Deserialize)]
+pub struct JavaRuntimeFileDownloads {
+    pub lzma: Option<AssetDownload>,
+    pub raw: AssetDownload
+}
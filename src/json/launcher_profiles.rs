@@ -0,0 +1,117 @@
+/*
+ * Steve Launcher - A Minecraft Launcher
+ * Copyright (C) 2023 Josh Kropf <josh@slashdev.ca>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use serde::Deserialize;
+use std::collections::HashMap;
+
+use crate::{ModLoader, ModLoaderName};
+
+#[derive(Deserialize)]
+/// Mojang launcher `launcher_profiles.json`
+pub struct LauncherProfiles {
+    pub profiles: HashMap<String, LauncherProfile>
+}
+
+#[derive(Deserialize)]
+pub struct LauncherProfile {
+    #[serde(default)]
+    pub name: String,
+    /// `latest-release`, `latest-snapshot` or `custom`
+    #[serde(rename(deserialize = "type"))]
+    pub profile_type: Option<String>,
+    #[serde(rename(deserialize = "lastVersionId"))]
+    pub last_version_id: String,
+    #[serde(rename(deserialize = "gameDir"))]
+    pub game_dir: Option<String>,
+    #[serde(rename(deserialize = "javaDir"))]
+    pub java_dir: Option<String>,
+    #[serde(rename(deserialize = "javaArgs"))]
+    pub java_args: Option<String>
+}
+
+impl LauncherProfile {
+    /// Profile name, falling back to the profile type for the built in
+    /// latest release and snapshot profiles that have no name
+    pub fn display_name(&self) -> &str {
+        match self.profile_type.as_deref() {
+            Some(t) if self.name.is_empty() => t,
+            _ => &self.name
+        }
+    }
+
+    /// Minecraft version and mod loader from the profile version id. Installers
+    /// name versions `<mc>-forge-<forge>`, `neoforge-<neoforge>` and
+    /// `quilt-loader-<quilt>-<mc>`, anything else is assumed to be a plain
+    /// minecraft version.
+    pub fn parse_version_id(&self) -> (String, Option<ModLoader>) {
+        let id = self.last_version_id.as_str();
+
+        if let Some((mc_version, version)) = id.split_once("-forge-") {
+            let loader = ModLoader { name: ModLoaderName::Forge, version: version.to_string() };
+            return (mc_version.to_string(), Some(loader));
+        }
+
+        if let Some(version) = id.strip_prefix("neoforge-") {
+            // neoforge versions start with the minecraft minor and patch version
+            let mut parts = version.split('.');
+            let mc_version = match (parts.next(), parts.next()) {
+                (Some(minor), Some("0")) => format!("1.{minor}"),
+                (Some(minor), Some(patch)) => format!("1.{minor}.{patch}"),
+                _ => id.to_string()
+            };
+
+            let loader = ModLoader { name: ModLoaderName::NeoForge, version: version.to_string() };
+            return (mc_version, Some(loader));
+        }
+
+        if let Some((version, mc_version)) = id.strip_prefix("quilt-loader-").and_then(|v| v.split_once('-')) {
+            let loader = ModLoader { name: ModLoaderName::Quilt, version: version.to_string() };
+            return (mc_version.to_string(), Some(loader));
+        }
+
+        (id.to_string(), None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(id: &str) -> (String, Option<String>) {
+        let profile = LauncherProfile {
+            name: String::new(),
+            profile_type: None,
+            last_version_id: id.to_string(),
+            game_dir: None,
+            java_dir: None,
+            java_args: None
+        };
+
+        let (mc_version, loader) = profile.parse_version_id();
+        (mc_version, loader.map(|l| l.to_string()))
+    }
+
+    #[test]
+    fn version_id_loaders() {
+        assert_eq!(parse("1.20.1"), ("1.20.1".to_string(), None));
+        assert_eq!(parse("1.20.1-forge-47.2.0"), ("1.20.1".to_string(), Some("forge-47.2.0".to_string())));
+        assert_eq!(parse("neoforge-20.4.80"), ("1.20.4".to_string(), Some("neoforge-20.4.80".to_string())));
+        assert_eq!(parse("neoforge-21.0.10"), ("1.21".to_string(), Some("neoforge-21.0.10".to_string())));
+        assert_eq!(parse("quilt-loader-0.23.1-1.20.1"), ("1.20.1".to_string(), Some("quilt-0.23.1".to_string())));
+    }
+}
@@ -21,16 +21,6 @@ use serde::Deserialize;
 use super::{empty_string_is_none, ModLoader};
 use crate::Error;
 
-// https://api.modpacks.ch/public/modpack/all
-#[derive(Deserialize)]
-pub struct ModpackIndex {
-    #[serde(rename(deserialize = "packs"))]
-    pub pack_ids: Vec<u32>,
-    pub total: u32,
-    pub refreshed: u64,
-    pub status: String
-}
-
 // https://api.modpacks.ch/public/modpack/search/{limit}?term={search term}
 #[derive(Deserialize)]
 pub struct ModpackSearch {
@@ -98,6 +88,12 @@ pub struct ModpackVersionTarget {
     pub updated: u64
 }
 
+// https://api.modpacks.ch/public/modpack/{pack_id}/{version_id}/changelog
+#[derive(Deserialize)]
+pub struct ModpackChangelog {
+    pub content: String
+}
+
 // https://api.modpacks.ch/public/modpack/{pack_id}/{version_id}
 #[derive(Deserialize)]
 pub struct ModpackVersionManifest {
@@ -0,0 +1,85 @@
+/*
+ * Steve Launcher - A Minecraft Launcher
+ * Copyright (C) 2023 Josh Kropf <josh@slashdev.ca>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+#[derive(Deserialize, Serialize)]
+// https://support.modrinth.com/en/articles/8802351-modrinth-modpack-format-mrpack
+pub struct ModrinthPack {
+    #[serde(rename(deserialize = "formatVersion", serialize = "formatVersion"))]
+    pub format_version: u8,
+    pub game: String,
+    #[serde(rename(deserialize = "versionId", serialize = "versionId"))]
+    pub version_id: String,
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub summary: Option<String>,
+    pub files: Vec<ModrinthPackFile>,
+    pub dependencies: BTreeMap<String, String>
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct ModrinthPackFile {
+    pub path: String,
+    pub hashes: ModrinthHashes,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub env: Option<ModrinthPackFileEnv>,
+    pub downloads: Vec<String>,
+    #[serde(rename(deserialize = "fileSize", serialize = "fileSize"))]
+    pub file_size: u64
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct ModrinthHashes {
+    pub sha1: String,
+    pub sha512: String
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct ModrinthPackFileEnv {
+    pub client: ModrinthEnvSupport,
+    pub server: ModrinthEnvSupport
+}
+
+#[derive(Deserialize, Serialize)]
+pub enum ModrinthEnvSupport {
+    #[serde(rename = "required")]
+    Required,
+    #[serde(rename = "optional")]
+    Optional,
+    #[serde(rename = "unsupported")]
+    Unsupported
+}
+
+#[derive(Deserialize)]
+// https://docs.modrinth.com/api/operations/versionsfromhashes/
+pub struct ModrinthVersion {
+    pub id: String,
+    pub project_id: String,
+    pub files: Vec<ModrinthVersionFile>
+}
+
+#[derive(Deserialize)]
+pub struct ModrinthVersionFile {
+    pub hashes: ModrinthHashes,
+    pub url: String,
+    pub filename: String,
+    pub primary: bool,
+    pub size: u64
+}
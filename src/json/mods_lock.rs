@@ -0,0 +1,123 @@
+/*
+ * Steve Launcher - A Minecraft Launcher
+ * Copyright (C) 2023 Josh Kropf <josh@slashdev.ca>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Files installed into the instance game dir from modpacks, used to reinstall
+/// the same files with `mods sync`
+#[derive(Deserialize, Serialize, Default)]
+pub struct ModsLock {
+    pub files: Vec<ModsLockFile>,
+
+    /// Paths of files the user added to the game dir, these are left alone
+    /// when a modpack update removes old files
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub user_files: Vec<String>,
+
+    /// SHA1 of the config files copied from the pack overrides, used to find
+    /// config files the user changed since the pack was installed
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub config_hashes: BTreeMap<String, String>
+}
+
+impl ModsLock {
+    pub fn get(&self, path: &str) -> Option<&ModsLockFile> {
+        self.files.iter().find(|f| f.path == path)
+    }
+
+    pub fn get_mut(&mut self, path: &str) -> Option<&mut ModsLockFile> {
+        self.files.iter_mut().find(|f| f.path == path)
+    }
+
+    /// Add file, replacing the entry with the same path
+    pub fn insert(&mut self, file: ModsLockFile) {
+        self.remove(&file.path);
+        self.files.push(file);
+        self.files.sort_by(|a, b| a.path.cmp(&b.path));
+    }
+
+    pub fn remove(&mut self, path: &str) {
+        self.files.retain(|f| f.path != path);
+    }
+}
+
+#[derive(Deserialize, Serialize, Clone)]
+pub struct ModsLockFile {
+    /// Path relative to the game dir, without `.disabled` suffix
+    pub path: String,
+
+    #[serde(flatten)]
+    pub source: ModsLockSource,
+
+    #[serde(default)]
+    pub disabled: bool
+}
+
+#[derive(Deserialize, Serialize, Clone)]
+#[serde(tag = "source")]
+pub enum ModsLockSource {
+    #[serde(rename = "curseforge")]
+    CurseForge {
+        project_id: u64,
+        file_id: u64,
+        /// CurseForge murmur2 fingerprint
+        fingerprint: u32
+    },
+    #[serde(rename = "url")]
+    Url {
+        url: String,
+        sha1: String
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn read_write() {
+        let json = r#"{
+            "files": [
+                {"path": "mods/jei.jar", "source": "curseforge", "project_id": 238222, "file_id": 4712868, "fingerprint": 1234},
+                {"path": "mods/sodium.jar", "source": "url", "url": "https://example.com/sodium.jar", "sha1": "abcd", "disabled": true}
+            ]
+        }"#;
+
+        let mut lock: ModsLock = serde_json::from_str(json).unwrap();
+        assert!(lock.user_files.is_empty());
+        assert!(lock.config_hashes.is_empty());
+        assert!(matches!(lock.get("mods/jei.jar").unwrap().source,
+            ModsLockSource::CurseForge { project_id: 238222, file_id: 4712868, fingerprint: 1234 }));
+        assert!(lock.get("mods/sodium.jar").unwrap().disabled);
+
+        lock.insert(ModsLockFile {
+            path: "mods/jei.jar".to_string(),
+            source: ModsLockSource::Url { url: "https://example.com/jei.jar".to_string(), sha1: "ef01".to_string() },
+            disabled: false
+        });
+        lock.user_files.push("mods/extra.jar".to_string());
+
+        let lock: ModsLock = serde_json::from_str(&serde_json::to_string(&lock).unwrap()).unwrap();
+        let paths: Vec<_> = lock.files.iter().map(|f| f.path.as_str()).collect();
+        assert_eq!(paths, ["mods/jei.jar", "mods/sodium.jar"]);
+        assert!(matches!(&lock.get("mods/jei.jar").unwrap().source,
+            ModsLockSource::Url { sha1, .. } if sha1 == "ef01"));
+        assert_eq!(lock.user_files, ["mods/extra.jar"]);
+    }
+}
@@ -0,0 +1,64 @@
+/*
+ * Steve Launcher - A Minecraft Launcher
+ * Copyright (C) 2023 Josh Kropf <josh@slashdev.ca>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+pub struct MojangProfile {
+    /// Player UUID without dashes
+    pub id: String,
+    pub name: String,
+
+    /// Only included in session server responses
+    #[serde(default)]
+    pub properties: Vec<MojangProfileProperty>
+}
+
+#[derive(Deserialize)]
+pub struct MojangProfileProperty {
+    pub name: String,
+
+    /// Base64 encoded json, see `MojangTextures` for the `textures` property
+    pub value: String
+}
+
+#[derive(Deserialize)]
+pub struct MojangTextures {
+    pub textures: MojangTextureSet
+}
+
+#[derive(Deserialize)]
+pub struct MojangTextureSet {
+    #[serde(rename = "SKIN")]
+    pub skin: Option<MojangTexture>,
+
+    #[serde(rename = "CAPE")]
+    pub cape: Option<MojangTexture>
+}
+
+#[derive(Deserialize)]
+pub struct MojangTexture {
+    pub url: String,
+    pub metadata: Option<MojangTextureMetadata>
+}
+
+#[derive(Deserialize)]
+pub struct MojangTextureMetadata {
+    /// `slim` for the slim arms skin model
+    pub model: Option<String>
+}
@@ -0,0 +1,43 @@
+/*
+ * Steve Launcher - A Minecraft Launcher
+ * Copyright (C) 2023 Josh Kropf <josh@slashdev.ca>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Deserialize, Serialize)]
+/// Prism Launcher / MultiMC `mmc-pack.json`
+pub struct PrismPack {
+    pub components: Vec<PrismComponent>,
+    #[serde(rename(deserialize = "formatVersion", serialize = "formatVersion"))]
+    pub format_version: u8
+}
+
+impl PrismPack {
+    pub fn get_component(&self, uid: &str) -> Option<&PrismComponent> {
+        self.components.iter().find(|c| c.uid == uid)
+    }
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct PrismComponent {
+    pub uid: String,
+    /// Version is omitted for components only added as dependencies
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub important: bool
+}
@@ -0,0 +1,41 @@
+/*
+ * Steve Launcher - A Minecraft Launcher
+ * Copyright (C) 2023 Josh Kropf <josh@slashdev.ca>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use serde::{Deserialize, Serialize};
+
+/// Entry of a server `whitelist.json`
+#[derive(Deserialize, Serialize, Clone)]
+pub struct WhitelistEntry {
+    /// Player UUID with dashes
+    pub uuid: String,
+    pub name: String
+}
+
+/// Entry of a server `ops.json`
+#[derive(Deserialize, Serialize, Clone)]
+pub struct OpEntry {
+    /// Player UUID with dashes
+    pub uuid: String,
+    pub name: String,
+
+    /// Permission level 1-4, servers default to 4
+    pub level: u8,
+
+    #[serde(rename = "bypassesPlayerLimit")]
+    pub bypasses_player_limit: bool
+}
@@ -0,0 +1,49 @@
+/*
+ * Steve Launcher - A Minecraft Launcher
+ * Copyright (C) 2023 Josh Kropf <josh@slashdev.ca>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use serde::Deserialize;
+
+/// Status response of the Server List Ping protocol
+#[derive(Deserialize)]
+pub struct ServerStatusResponse {
+    pub version: ServerStatusVersion,
+    pub players: Option<ServerStatusPlayers>,
+
+    /// Plain string, or chat component with `text` and `extra` parts
+    pub description: Option<serde_json::Value>
+}
+
+#[derive(Deserialize)]
+pub struct ServerStatusVersion {
+    pub name: String,
+    pub protocol: i32
+}
+
+#[derive(Deserialize)]
+pub struct ServerStatusPlayers {
+    pub max: u32,
+    pub online: u32,
+
+    #[serde(default)]
+    pub sample: Vec<ServerStatusPlayer>
+}
+
+#[derive(Deserialize)]
+pub struct ServerStatusPlayer {
+    pub name: String
+}
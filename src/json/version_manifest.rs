@@ -17,12 +17,20 @@
  */
 
 use serde::Deserialize;
+use std::fmt;
 
 #[derive(Deserialize)]
 pub struct VersionManifest {
+    pub latest: VersionManifestLatest,
     pub versions: Vec<VersionManifestEntry>
 }
 
+#[derive(Deserialize)]
+pub struct VersionManifestLatest {
+    pub release: String,
+    pub snapshot: String
+}
+
 #[derive(Deserialize)]
 pub struct VersionManifestEntry {
     pub id: String,
@@ -37,8 +45,8 @@ pub struct VersionManifestEntry {
     pub compliance_level: u8
 }
 
-impl ToString for VersionManifestEntry {
-    fn to_string(&self) -> String {
-        self.id.to_owned()
+impl fmt::Display for VersionManifestEntry {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.id)
     }
 }
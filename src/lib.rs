@@ -17,29 +17,77 @@
  */
 
 mod account;
+mod api_client;
 mod asset_client;
 mod asset_manager;
+mod browser;
+mod config;
+mod credential_file;
 mod curseforge_zip;
 mod download_watcher;
 pub mod env;
 mod fs;
+mod game_log;
+mod hash;
 mod instance;
+mod instance_registry;
+mod java_runtime;
 mod json;
+mod mojang_api;
+pub mod nbt;
+mod prism_instance;
+mod protocol;
+mod rcon;
 mod rules;
+mod server_players;
 mod zip;
 
+use std::path::PathBuf;
+
 pub use {
     account::Account,
+    account::AccountStatus,
+    account::LoginCancel,
+    account::Session,
+    browser::open_url,
     asset_client::AssetClient,
+    asset_client::CurseForgeSort,
+    config::Config,
     curseforge_zip::CurseForgeZip,
+    download_watcher::DownloadCheck,
     download_watcher::DownloadWatcher,
     download_watcher::WatcherMessage,
+    fs::path_sizes,
+    game_log::GameExit,
+    game_log::GameProcess,
+    game_log::LogParser,
+    instance::ConfigConflicts,
     instance::Instance,
     instance::FileDownload,
+    instance::LaunchOptions,
+    instance::PackChanges,
+    instance::PackInstall,
+    instance::QuickPlay,
+    instance::World,
+    instance_registry::InstanceRegistry,
+    java_runtime::JavaRuntime,
+    json::InstanceModpack,
     json::ModLoader,
     json::ModLoaderName,
     json::ModpackManifest,
-    json::ModpackVersion
+    json::ModpackVersion,
+    json::OpEntry,
+    json::WhitelistEntry,
+    json::WindowResolution,
+    mojang_api::MojangApiClient,
+    mojang_api::PlayerProfile,
+    mojang_api::dashed_uuid,
+    prism_instance::PrismInstance,
+    protocol::ServerStatus,
+    protocol::ping_server,
+    protocol::server_dir_address,
+    rcon::RconClient,
+    server_players::ServerPlayers
 };
 
 #[derive(thiserror::Error, Debug)]
@@ -52,7 +100,7 @@ pub enum Error {
     MinecraftTargetNotFound,
     #[error("Minecraft version '{0}' not found")]
     MinecraftVersionNotFound(String),
-    #[error("Forge version '{0}' not found")]
+    #[error("Mod loader version '{0}' not found")]
     ForgeVersionNotFound(String),
     #[error("Unable to parse '{version}' with lenient_semver")]
     VersionParse {
@@ -68,18 +116,169 @@ pub enum Error {
         file_list_len: usize,
         mod_list_len: usize
     },
-    #[error("Instance directory '{0}' not found or doesn't contain manifest.json file")]
+    #[error("Instance directory '{0}' not found or doesn't contain manifest.json or manifest.toml file")]
     InstanceNotFound(String),
+    #[error("Directory '{0}' already exists")]
+    InstanceDirExists(String),
+    #[error("Files missing for launch without updates:\n{0}")]
+    OfflineFilesMissing(String),
+    #[error("Failed to download libraries:\n{0}")]
+    LibraryDownloadsFailed(String),
+    #[error("Failed to download mods:\n{0}")]
+    ModDownloadsFailed(String),
+    #[error("File '{0}' doesn't match the CurseForge file, it may be incomplete; download it again")]
+    FileHashMismatch(String),
+    #[error("Downloaded file '{0}' doesn't match the hash in mods.lock.json, the file at its url has changed")]
+    LockHashMismatch(String),
+    #[error("Instance name '{0}' is already registered to another instance")]
+    InstanceNameTaken(String),
+    #[error("Unable to clone instance into its own directory")]
+    CloneIntoSelf,
+    #[error("Unable to export instance into its own directory")]
+    ExportIntoSelf,
     #[error("Account credentials not found, run authenticate to save credentials")]
     CredentialNotFound,
+    #[error("Account '{0}' not found")]
+    AccountNotFound(String),
+    #[error("No passphrase or machine id to encrypt account.json, set STEVE_CREDENTIAL_PASSPHRASE")]
+    CredentialKeyNotFound,
+    #[error("Unable to encrypt account.json")]
+    CredentialEncrypt,
+    #[error("Unable to decrypt account.json, the passphrase or machine id has changed")]
+    CredentialDecrypt,
+    #[error("Sign in cancelled")]
+    LoginCancelled,
+    #[error("Sign in code expired before it was entered")]
+    LoginExpired,
+    #[error("Microsoft account doesn't have an Xbox profile, sign in at https://www.xbox.com to create one")]
+    XboxProfileNotFound,
+    #[error("Xbox account is banned from Xbox Live")]
+    XboxAccountBanned,
+    #[error("Xbox Live is not available in the country or region of the Microsoft account")]
+    XboxRegionUnavailable,
+    #[error("Microsoft account needs adult verification, sign in at https://www.xbox.com to verify")]
+    XboxAdultVerification,
+    #[error("Child accounts must be added to a Microsoft family by an adult, see https://account.microsoft.com/family")]
+    XboxChildAccount,
+    #[error("Xbox authorization failed with error code {0}")]
+    XboxAuthorizationFailed(u64),
+    #[error("Account doesn't own Minecraft, buy the game or sign in with the official launcher first")]
+    GameNotOwned,
+    #[error("World '{0}' not found in saves directory")]
+    WorldNotFound(String),
+    #[error("'{0}' is not a datapack zip, or directory containing pack.mcmeta")]
+    InvalidDatapack(String),
+    #[error("Minecraft {0} doesn't support joining a world from the command line")]
+    QuickPlayNotSupported(String),
+    #[error("Unable to load CA certificates from '{0}'")]
+    InvalidCaCertificate(String),
+    #[error("Invalid resolution '{0}'; expected <width>x<height>")]
+    InvalidResolution(String),
+    #[error("Invalid config conflict handling '{0}'; expected keep, overwrite or backup")]
+    InvalidConfigConflicts(String),
+    #[error("Unknown config key '{0}'")]
+    InvalidConfigKey(String),
+    #[error("Invalid value '{value}' for config key '{key}'")]
+    InvalidConfigValue {
+        key: String,
+        value: String
+    },
+    #[error("Mojang launcher profiles '{0}' not found")]
+    LauncherProfilesNotFound(String),
+    #[error("Prism instance '{0}' not found or doesn't contain instance.cfg and mmc-pack.json files")]
+    PrismInstanceNotFound(String),
+    #[error("Prism component '{0}' is not supported")]
+    PrismComponentNotSupported(String),
     #[error("Invalid mod loader name '{0}'")]
     InvalidModLoaderName(String),
     #[error("Invalid mod loader ID format '{0}'; expected [name]-[version]")]
-    InvalidModLoaderId(String)
+    InvalidModLoaderId(String),
+    #[error("Java runtime '{component}' not available for platform '{platform}'")]
+    JavaRuntimeNotFound {
+        component: String,
+        platform: String
+    },
+    #[error("No Java runtime with major version {0} found")]
+    JavaVersionNotFound(u32),
+    #[error("No mod matching '{0}' found")]
+    ModNotFound(String),
+    #[error("More than one mod matches '{name}': {matches}")]
+    ModNameAmbiguous {
+        name: String,
+        matches: String
+    },
+    #[error("{0} files must be downloaded manually")]
+    BlockedDownloads(usize),
+    #[error("Rcon is not enabled, set enable-rcon=true in server.properties")]
+    RconDisabled,
+    #[error("Rcon login failed, check rcon.password in server.properties")]
+    RconAuthFailed,
+    #[error("Invalid rcon packet received from server")]
+    RconInvalidPacket,
+    #[error("Invalid status response received from server")]
+    ServerStatusInvalid,
+    #[error("Invalid offline username '{0}'; expected 3-16 characters of letters, numbers or underscore")]
+    InvalidOfflineUsername(String),
+    #[error("Mod loader '{loader}' is for Minecraft '{loader_mc_version}', instance is Minecraft '{mc_version}'")]
+    ModLoaderVersionMismatch {
+        loader: String,
+        loader_mc_version: String,
+        mc_version: String
+    }
 }
 
+/// Progress of a long running task. Tasks measured in bytes begin with
+/// `begin_bytes` and advance by bytes done, tasks without a known total
+/// begin with `begin_spinner` and end without advancing.
 pub trait Progress {
     fn begin(&mut self, message: &'static str, total: usize);
     fn end(&mut self);
     fn advance(&mut self, current: usize);
+
+    /// Begin task measured in bytes, handlers that don't show byte counts
+    /// can leave the default
+    fn begin_bytes(&mut self, message: &'static str, total: u64) {
+        self.begin(message, total as usize);
+    }
+
+    /// Begin task without a known total, like waiting on an API request
+    fn begin_spinner(&mut self, message: &'static str) {
+        self.begin(message, 0);
+    }
+}
+
+/// Decisions needed while installing a modpack, implemented by the CLI with
+/// prompts and by other front ends however they see fit
+pub trait InstallHandler {
+    /// Instance already exists in the install directory, return `true` to
+    /// install the pack over it
+    fn confirm_existing_instance(&mut self, instance: &Instance) -> anyhow::Result<bool>;
+
+    /// Files the user added that are now part of the pack, return `true` to
+    /// keep them when a later version of the pack removes them
+    fn keep_user_files(&mut self, instance: &Instance, files: &[PathBuf]) -> anyhow::Result<bool>;
+
+    /// Files that must be downloaded manually and installed with
+    /// `Instance::install_file`
+    fn download_blocked(&mut self, instance: &Instance, downloads: Vec<FileDownload>) -> anyhow::Result<()>;
+
+    /// Files left from a previous version of the pack, return the files
+    /// that should be removed
+    fn remove_files(&mut self, instance: &Instance, files: &[PathBuf]) -> anyhow::Result<Vec<PathBuf>>;
+
+    /// Config files the user changed since the pack was installed, handled
+    /// following `env::get_config_conflicts`
+    fn config_conflicts(&mut self, _instance: &Instance, _files: &[PathBuf]) { }
+}
+
+/// Prompts while signing in with a device code, implemented by the CLI and
+/// by other front ends however they see fit
+pub trait LoginHandler {
+    /// Show the url to open and the code to enter there; waiting for the
+    /// code stops with `Error::LoginCancelled` when `cancel` is used
+    fn show_code(&mut self, url: &str, code: &str, cancel: LoginCancel);
+
+    /// Code wasn't entered before `env::get_login_timeout`, return `true`
+    /// to show a new code
+    fn retry_expired(&mut self) -> anyhow::Result<bool>;
 }
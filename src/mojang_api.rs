@@ -0,0 +1,145 @@
+/*
+ * Steve Launcher - A Minecraft Launcher
+ * Copyright (C) 2023 Josh Kropf <josh@slashdev.ca>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use anyhow::Result;
+use base64::{Engine, engine::general_purpose::STANDARD as BASE64};
+use reqwest::{Client, StatusCode};
+
+use crate::{asset_client::http_client, env, json::{MojangProfile, MojangTextures}};
+
+const PROFILE_BY_NAME_URL: &str = "https://api.mojang.com/users/profiles/minecraft";
+const PROFILE_BY_UUID_URL: &str = "https://sessionserver.mojang.com/session/minecraft/profile";
+
+/// Public player profile from the Mojang API
+pub struct PlayerProfile {
+    /// Player UUID without dashes
+    pub uuid: String,
+    pub name: String,
+    pub skin_url: Option<String>,
+
+    /// Skin uses the slim arms model
+    pub slim_skin: bool,
+    pub cape_url: Option<String>
+}
+
+/// Client for the public Mojang player APIs, these don't need an account
+pub struct MojangApiClient {
+    client: Client
+}
+
+impl MojangApiClient {
+    pub fn new() -> Result<Self> {
+        Ok(MojangApiClient { client: http_client()? })
+    }
+
+    /// Find player by name or UUID, `None` when no player has it
+    pub async fn get_profile(&self, name_or_uuid: &str) -> Result<Option<PlayerProfile>> {
+        if is_uuid(name_or_uuid) {
+            self.get_profile_by_uuid(name_or_uuid).await
+        } else {
+            self.get_profile_by_name(name_or_uuid).await
+        }
+    }
+
+    /// Find player UUID by name, then the full profile
+    pub async fn get_profile_by_name(&self, name: &str) -> Result<Option<PlayerProfile>> {
+        match self.get_json(&format!("{PROFILE_BY_NAME_URL}/{name}")).await? {
+            Some(profile) => self.get_profile_by_uuid(&profile.id).await,
+            None => Ok(None)
+        }
+    }
+
+    pub async fn get_profile_by_uuid(&self, uuid: &str) -> Result<Option<PlayerProfile>> {
+        let uuid = uuid.replace('-', "");
+
+        let profile = match self.get_json(&format!("{PROFILE_BY_UUID_URL}/{uuid}")).await? {
+            Some(profile) => profile,
+            None => return Ok(None)
+        };
+
+        let textures = match profile.properties.iter().find(|p| p.name == "textures") {
+            Some(property) => Some(serde_json::from_slice::<MojangTextures>(&BASE64.decode(&property.value)?)?),
+            None => None
+        };
+
+        let (skin, cape) = match textures {
+            Some(textures) => (textures.textures.skin, textures.textures.cape),
+            None => (None, None)
+        };
+
+        Ok(Some(PlayerProfile {
+            uuid: profile.id,
+            name: profile.name,
+            slim_skin: skin.as_ref()
+                .and_then(|s| s.metadata.as_ref())
+                .and_then(|m| m.model.as_deref())
+                .is_some_and(|m| m == "slim"),
+            skin_url: skin.map(|s| s.url),
+            cape_url: cape.map(|c| c.url)
+        }))
+    }
+
+    /// Unknown players get an empty response, or not found, depending on
+    /// the endpoint
+    async fn get_json(&self, url: &str) -> Result<Option<MojangProfile>> {
+        let response = self.client.get(url)
+            .timeout(env::get_http_request_timeout())
+            .send().await?;
+
+        match response.status() {
+            StatusCode::NO_CONTENT | StatusCode::NOT_FOUND => Ok(None),
+            _ => Ok(Some(response.error_for_status()?.json().await?))
+        }
+    }
+}
+
+/// UUID with or without dashes
+pub fn is_uuid(text: &str) -> bool {
+    let hex = text.replace('-', "");
+
+    hex.len() == 32
+        && hex.chars().all(|c| c.is_ascii_hexdigit())
+        && (text.len() == 32 || text.len() == 36)
+}
+
+/// UUID in the dashed 8-4-4-4-12 format, as written in server files
+pub fn dashed_uuid(uuid: &str) -> String {
+    let hex = uuid.replace('-', "");
+
+    if hex.len() != 32 {
+        return uuid.to_string();
+    }
+
+    format!("{}-{}-{}-{}-{}", &hex[0..8], &hex[8..12], &hex[12..16], &hex[16..20], &hex[20..])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn uuid_formats() {
+        assert!(is_uuid("069a79f444e94726a5befca90e38aaf5"));
+        assert!(is_uuid("069a79f4-44e9-4726-a5be-fca90e38aaf5"));
+        assert!(!is_uuid("Notch"));
+        assert!(!is_uuid("069a79f444e94726a5befca90e38aaf"));
+
+        assert_eq!(dashed_uuid("069a79f444e94726a5befca90e38aaf5"), "069a79f4-44e9-4726-a5be-fca90e38aaf5");
+        assert_eq!(dashed_uuid("069a79f4-44e9-4726-a5be-fca90e38aaf5"), "069a79f4-44e9-4726-a5be-fca90e38aaf5");
+    }
+}
@@ -0,0 +1,344 @@
+/*
+ * Steve Launcher - A Minecraft Launcher
+ * Copyright (C) 2023 Josh Kropf <josh@slashdev.ca>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use flate2::{Compression, read::GzDecoder, write::GzEncoder};
+use std::{collections::BTreeMap, io::{self, Read, Write}};
+
+const TAG_END: u8 = 0;
+
+/// Compounds and lists nested deeper than this are rejected, rather than
+/// overflowing the stack on corrupt data
+const MAX_DEPTH: usize = 512;
+
+/// NBT tag value, names of tags are the keys of their compound
+#[derive(Clone, Debug, PartialEq)]
+pub enum Tag {
+    Byte(i8),
+    Short(i16),
+    Int(i32),
+    Long(i64),
+    Float(f32),
+    Double(f64),
+    ByteArray(Vec<i8>),
+    String(String),
+    /// List of tags all of the same type
+    List(Vec<Tag>),
+    Compound(BTreeMap<String, Tag>),
+    IntArray(Vec<i32>),
+    LongArray(Vec<i64>)
+}
+
+impl Tag {
+    /// Tag of compound by name, `None` when the tag isn't a compound
+    pub fn get(&self, name: &str) -> Option<&Tag> {
+        match self {
+            Tag::Compound(tags) => tags.get(name),
+            _ => None
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Tag::String(v) => Some(v),
+            _ => None
+        }
+    }
+
+    /// Value of any of the integer tags
+    pub fn as_i64(&self) -> Option<i64> {
+        match self {
+            Tag::Byte(v) => Some(*v as i64),
+            Tag::Short(v) => Some(*v as i64),
+            Tag::Int(v) => Some(*v as i64),
+            Tag::Long(v) => Some(*v),
+            _ => None
+        }
+    }
+
+    fn id(&self) -> u8 {
+        match self {
+            Tag::Byte(_) => 1,
+            Tag::Short(_) => 2,
+            Tag::Int(_) => 3,
+            Tag::Long(_) => 4,
+            Tag::Float(_) => 5,
+            Tag::Double(_) => 6,
+            Tag::ByteArray(_) => 7,
+            Tag::String(_) => 8,
+            Tag::List(_) => 9,
+            Tag::Compound(_) => 10,
+            Tag::IntArray(_) => 11,
+            Tag::LongArray(_) => 12
+        }
+    }
+
+    fn write_payload(&self, w: &mut impl Write) -> io::Result<()> {
+        match self {
+            Tag::Byte(v) => w.write_all(&v.to_be_bytes()),
+            Tag::Short(v) => w.write_all(&v.to_be_bytes()),
+            Tag::Int(v) => w.write_all(&v.to_be_bytes()),
+            Tag::Long(v) => w.write_all(&v.to_be_bytes()),
+            Tag::Float(v) => w.write_all(&v.to_be_bytes()),
+            Tag::Double(v) => w.write_all(&v.to_be_bytes()),
+            Tag::ByteArray(values) => {
+                write_len(w, values.len())?;
+                values.iter().try_for_each(|v| w.write_all(&v.to_be_bytes()))
+            },
+            Tag::String(v) => write_string(w, v),
+            Tag::List(tags) => {
+                // empty lists have the end tag as their type
+                w.write_all(&[tags.first().map_or(TAG_END, Tag::id)])?;
+                write_len(w, tags.len())?;
+                tags.iter().try_for_each(|t| t.write_payload(w))
+            },
+            Tag::Compound(tags) => {
+                for (name, tag) in tags {
+                    write_named(w, name, tag)?;
+                }
+                w.write_all(&[TAG_END])
+            },
+            Tag::IntArray(values) => {
+                write_len(w, values.len())?;
+                values.iter().try_for_each(|v| w.write_all(&v.to_be_bytes()))
+            },
+            Tag::LongArray(values) => {
+                write_len(w, values.len())?;
+                values.iter().try_for_each(|v| w.write_all(&v.to_be_bytes()))
+            }
+        }
+    }
+
+    fn read_payload(r: &mut impl Read, id: u8, depth: usize) -> io::Result<Self> {
+        if depth > MAX_DEPTH {
+            return Err(invalid_data("NBT nested too deep"));
+        }
+
+        Ok(match id {
+            1 => Tag::Byte(i8::from_be_bytes(read_bytes(r)?)),
+            2 => Tag::Short(i16::from_be_bytes(read_bytes(r)?)),
+            3 => Tag::Int(i32::from_be_bytes(read_bytes(r)?)),
+            4 => Tag::Long(i64::from_be_bytes(read_bytes(r)?)),
+            5 => Tag::Float(f32::from_be_bytes(read_bytes(r)?)),
+            6 => Tag::Double(f64::from_be_bytes(read_bytes(r)?)),
+            7 => Tag::ByteArray((0..read_len(r)?)
+                .map(|_| Ok(i8::from_be_bytes(read_bytes(r)?)))
+                .collect::<io::Result<_>>()?),
+            8 => Tag::String(read_string(r)?),
+            9 => {
+                let [item_id] = read_bytes(r)?;
+                let len = read_len(r)?;
+
+                if item_id == TAG_END && len > 0 {
+                    return Err(invalid_data("NBT list of end tags"));
+                }
+
+                Tag::List((0..len)
+                    .map(|_| Self::read_payload(r, item_id, depth + 1))
+                    .collect::<io::Result<_>>()?)
+            },
+            10 => {
+                let mut tags = BTreeMap::new();
+
+                while let Some((name, tag)) = read_named(r, depth + 1)? {
+                    tags.insert(name, tag);
+                }
+
+                Tag::Compound(tags)
+            },
+            11 => Tag::IntArray((0..read_len(r)?)
+                .map(|_| Ok(i32::from_be_bytes(read_bytes(r)?)))
+                .collect::<io::Result<_>>()?),
+            12 => Tag::LongArray((0..read_len(r)?)
+                .map(|_| Ok(i64::from_be_bytes(read_bytes(r)?)))
+                .collect::<io::Result<_>>()?),
+            _ => return Err(invalid_data(&format!("Unknown NBT tag type {id}")))
+        })
+    }
+}
+
+/// Read NBT data, gzip compressed or not, returning the root tag
+pub fn read(mut r: impl Read) -> io::Result<Tag> {
+    let mut data = vec![];
+    r.read_to_end(&mut data)?;
+
+    // level.dat is compressed, servers.dat is not
+    if data.starts_with(&[0x1f, 0x8b]) {
+        read_uncompressed(&mut GzDecoder::new(data.as_slice()))
+    } else {
+        read_uncompressed(&mut data.as_slice())
+    }
+}
+
+fn read_uncompressed(r: &mut impl Read) -> io::Result<Tag> {
+    match read_named(r, 0)? {
+        Some((_, root)) => Ok(root),
+        None => Err(invalid_data("NBT data is empty"))
+    }
+}
+
+/// Write uncompressed NBT data, the root compound has an empty name
+pub fn write(w: &mut impl Write, root: &Tag) -> io::Result<()> {
+    write_named(w, "", root)
+}
+
+/// Write gzip compressed NBT data, the root compound has an empty name
+pub fn write_gzip(w: &mut impl Write, root: &Tag) -> io::Result<()> {
+    let mut encoder = GzEncoder::new(w, Compression::default());
+    write(&mut encoder, root)?;
+    encoder.finish()?;
+    Ok(())
+}
+
+/// Read type and name of tag followed by its payload, `None` for the end tag
+fn read_named(r: &mut impl Read, depth: usize) -> io::Result<Option<(String, Tag)>> {
+    let [id] = read_bytes(r)?;
+
+    if id == TAG_END {
+        return Ok(None);
+    }
+
+    let name = read_string(r)?;
+    let tag = Tag::read_payload(r, id, depth)?;
+
+    Ok(Some((name, tag)))
+}
+
+fn write_named(w: &mut impl Write, name: &str, tag: &Tag) -> io::Result<()> {
+    w.write_all(&[tag.id()])?;
+    write_string(w, name)?;
+    tag.write_payload(w)
+}
+
+fn read_bytes<const N: usize>(r: &mut impl Read) -> io::Result<[u8; N]> {
+    let mut buf = [0; N];
+    r.read_exact(&mut buf)?;
+    Ok(buf)
+}
+
+fn read_len(r: &mut impl Read) -> io::Result<usize> {
+    usize::try_from(i32::from_be_bytes(read_bytes(r)?))
+        .map_err(|_| invalid_data("Negative NBT length"))
+}
+
+fn write_len(w: &mut impl Write, len: usize) -> io::Result<()> {
+    let len = i32::try_from(len)
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "NBT list too long"))?;
+    w.write_all(&len.to_be_bytes())
+}
+
+/// Strings are java's modified UTF-8; nul is two bytes and characters
+/// outside the basic multilingual plane are encoded as surrogate pairs
+fn read_string(r: &mut impl Read) -> io::Result<String> {
+    let len = u16::from_be_bytes(read_bytes(r)?) as usize;
+
+    let mut data = vec![0; len];
+    r.read_exact(&mut data)?;
+
+    // plain UTF-8 for most strings, skip decoding them by hand
+    if let Ok(value) = std::str::from_utf8(&data) {
+        return Ok(value.to_string());
+    }
+
+    let mut units = vec![];
+    let mut bytes = data.iter().map(|b| *b as u16);
+
+    while let Some(b) = bytes.next() {
+        let unit = if b & 0x80 == 0 {
+            b
+        } else if b & 0xe0 == 0xc0 {
+            let b2 = bytes.next().ok_or_else(|| invalid_data("Truncated NBT string"))?;
+            (b & 0x1f) << 6 | (b2 & 0x3f)
+        } else {
+            let b2 = bytes.next().ok_or_else(|| invalid_data("Truncated NBT string"))?;
+            let b3 = bytes.next().ok_or_else(|| invalid_data("Truncated NBT string"))?;
+            (b & 0x0f) << 12 | (b2 & 0x3f) << 6 | (b3 & 0x3f)
+        };
+
+        units.push(unit);
+    }
+
+    Ok(String::from_utf16_lossy(&units))
+}
+
+fn write_string(w: &mut impl Write, value: &str) -> io::Result<()> {
+    let mut data = vec![];
+
+    for unit in value.encode_utf16() {
+        match unit {
+            0x01..=0x7f => data.push(unit as u8),
+            0x00 | 0x80..=0x7ff => data.extend([0xc0 | (unit >> 6) as u8, 0x80 | (unit & 0x3f) as u8]),
+            _ => data.extend([
+                0xe0 | (unit >> 12) as u8,
+                0x80 | ((unit >> 6) & 0x3f) as u8,
+                0x80 | (unit & 0x3f) as u8
+            ])
+        }
+    }
+
+    let len = u16::try_from(data.len())
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "NBT string too long"))?;
+    w.write_all(&len.to_be_bytes())?;
+    w.write_all(&data)
+}
+
+fn invalid_data(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn write_compound() {
+        let root = Tag::Compound(BTreeMap::from([
+            ("servers".to_string(), Tag::List(vec![
+                Tag::Compound(BTreeMap::from([("ip".to_string(), Tag::String("a".to_string()))]))
+            ]))
+        ]));
+
+        let mut data = vec![];
+        write(&mut data, &root).unwrap();
+
+        assert_eq!(data, [
+            10, 0, 0,
+                9, 0, 7, b's', b'e', b'r', b'v', b'e', b'r', b's', 10, 0, 0, 0, 1,
+                    8, 0, 2, b'i', b'p', 0, 1, b'a',
+                0,
+            0
+        ]);
+    }
+
+    #[test]
+    fn gzip_round_trip() {
+        let root = Tag::Compound(BTreeMap::from([
+            ("Data".to_string(), Tag::Compound(BTreeMap::from([
+                ("LastPlayed".to_string(), Tag::Long(1700000000000)),
+                ("LevelName".to_string(), Tag::String("nul\0 and \u{1f600}".to_string())),
+                ("Empty".to_string(), Tag::List(vec![])),
+                ("Scale".to_string(), Tag::Double(0.5)),
+                ("Seeds".to_string(), Tag::LongArray(vec![-1, 2]))
+            ])))
+        ]));
+
+        let mut data = vec![];
+        write_gzip(&mut data, &root).unwrap();
+
+        assert_eq!(read(data.as_slice()).unwrap(), root);
+    }
+}
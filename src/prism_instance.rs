@@ -0,0 +1,189 @@
+/*
+ * Steve Launcher - A Minecraft Launcher
+ * Copyright (C) 2023 Josh Kropf <josh@slashdev.ca>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use anyhow::{bail, Result};
+use std::{collections::HashMap, fmt::Write, fs, path::{Path, PathBuf}};
+
+use crate::{
+    Error, json::{ModLoader, ModLoaderName, PrismComponent, PrismPack}
+};
+
+const INSTANCE_CFG_FILE: &str = "instance.cfg";
+const MMC_PACK_FILE: &str = "mmc-pack.json";
+
+const MINECRAFT_UID: &str = "net.minecraft";
+
+/// Component uids of mod loaders that steve doesn't support
+const UNSUPPORTED_LOADER_UIDS: &[&str] = &[
+    "net.fabricmc.fabric-loader", "com.mumfrey.liteloader"
+];
+
+/// Prism Launcher (or MultiMC) instance directory
+pub struct PrismInstance {
+    pub dir: PathBuf,
+    pub config: HashMap<String, String>,
+    pub pack: PrismPack
+}
+
+impl PrismInstance {
+    pub fn load(instance_dir: &Path) -> Result<Self> {
+        let cfg_path = instance_dir.join(INSTANCE_CFG_FILE);
+        let pack_path = instance_dir.join(MMC_PACK_FILE);
+
+        if !cfg_path.exists() || !pack_path.exists() {
+            bail!(Error::PrismInstanceNotFound(instance_dir.to_string_lossy().to_string()));
+        }
+
+        let config = parse_instance_cfg(&fs::read_to_string(cfg_path)?);
+        let pack = serde_json::from_reader(fs::File::open(pack_path)?)?;
+
+        Ok(Self {
+            dir: instance_dir.to_path_buf(),
+            config,
+            pack
+        })
+    }
+
+    pub fn mc_version(&self) -> Result<String> {
+        let version = self.pack.get_component(MINECRAFT_UID)
+            .and_then(|c| c.version.clone());
+
+        match version {
+            Some(version) => Ok(version),
+            None => bail!(Error::MinecraftTargetNotFound)
+        }
+    }
+
+    pub fn mod_loader(&self) -> Result<Option<ModLoader>> {
+        for c in &self.pack.components {
+            if UNSUPPORTED_LOADER_UIDS.contains(&c.uid.as_str()) {
+                bail!(Error::PrismComponentNotSupported(c.uid.clone()));
+            }
+
+            let name = [ModLoaderName::Forge, ModLoaderName::NeoForge, ModLoaderName::Quilt]
+                .into_iter()
+                .find(|n| n.prism_uid() == c.uid);
+
+            if let (Some(name), Some(version)) = (name, &c.version) {
+                return Ok(Some(ModLoader { name, version: version.clone() }));
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Minecraft directory relative to instance dir, prism uses `minecraft`
+    /// when it exists and `.minecraft` otherwise
+    pub fn game_dir(&self) -> &'static str {
+        if self.dir.join("minecraft").exists() {
+            "minecraft"
+        } else {
+            ".minecraft"
+        }
+    }
+
+    /// Config value, ignoring values prism only applies with override setting enabled
+    pub fn get_override(&self, override_key: &str, key: &str) -> Option<&str> {
+        if self.get(override_key) == Some("true") {
+            self.get(key).filter(|v| !v.is_empty())
+        } else {
+            None
+        }
+    }
+
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.config.get(key).map(|v| v.as_str())
+    }
+}
+
+/// Mmc pack for minecraft version and optional mod loader, prism resolves the
+/// remaining components (lwjgl, intermediary) on its own
+pub fn make_mmc_pack(mc_version: &str, mod_loader: Option<&ModLoader>) -> PrismPack {
+    let minecraft = PrismComponent {
+        uid: MINECRAFT_UID.to_string(),
+        version: Some(mc_version.to_string()),
+        important: true
+    };
+
+    let loader = mod_loader.map(|l| PrismComponent {
+        uid: l.name.prism_uid().to_string(),
+        version: Some(l.version.clone()),
+        important: false
+    });
+
+    PrismPack {
+        components: std::iter::once(minecraft).chain(loader).collect(),
+        format_version: 1
+    }
+}
+
+/// Write `instance.cfg` contents from list of key value pairs
+pub fn make_instance_cfg(values: &[(&str, String)]) -> String {
+    let mut cfg = String::from("[General]\n");
+
+    for (key, value) in values {
+        writeln!(cfg, "{key}={}", quote(value)).unwrap();
+    }
+
+    cfg
+}
+
+/// Parse `instance.cfg` (Qt ini format) into key value pairs,
+/// ignoring sections since prism keeps all settings in `[General]`
+fn parse_instance_cfg(data: &str) -> HashMap<String, String> {
+    data.lines()
+        .map(|l| l.trim())
+        .filter(|l| !l.is_empty() && !l.starts_with('[') && !l.starts_with(';') && !l.starts_with('#'))
+        .filter_map(|l| l.split_once('='))
+        .map(|(k, v)| (k.trim().to_string(), unquote(v.trim())))
+        .collect()
+}
+
+fn unquote(value: &str) -> String {
+    match value.strip_prefix('"').and_then(|v| v.strip_suffix('"')) {
+        Some(v) => v.replace("\\\"", "\"").replace("\\\\", "\\"),
+        None => value.to_string()
+    }
+}
+
+fn quote(value: &str) -> String {
+    // Qt quotes values containing characters with special meaning in ini files
+    if value.contains([';', ',', '=', '"', '#']) || value.starts_with(' ') || value.ends_with(' ') {
+        format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn instance_cfg_round_trip() {
+        let cfg = make_instance_cfg(&[
+            ("name", "My Pack".to_string()),
+            ("JvmArgs", "-Xmx4G -Dfoo=\"bar\"".to_string())
+        ]);
+
+        let values = parse_instance_cfg(&cfg);
+
+        assert_eq!(values["name"], "My Pack");
+        assert_eq!(values["JvmArgs"], "-Xmx4G -Dfoo=\"bar\"");
+    }
+}
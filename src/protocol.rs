@@ -0,0 +1,319 @@
+/*
+ * Steve Launcher - A Minecraft Launcher
+ * Copyright (C) 2023 Josh Kropf <josh@slashdev.ca>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use anyhow::{bail, Context, Result};
+use chrono::Utc;
+use std::{
+    fs, io::{Read, Write}, net::{TcpStream, ToSocketAddrs}, path::Path, time::{Duration, Instant}
+};
+
+use crate::{env, Error, json::ServerStatusResponse, rcon::parse_properties};
+
+const DEFAULT_PORT: u16 = 25565;
+
+/// Protocol version sent in the handshake, servers answer status requests
+/// from any version
+const HANDSHAKE_PROTOCOL: i32 = -1;
+
+const PACKET_HANDSHAKE: i32 = 0;
+const PACKET_STATUS: i32 = 0;
+const PACKET_PING: i32 = 1;
+
+/// Status reported by a server, the same details shown in the multiplayer
+/// server list
+pub struct ServerStatus {
+    /// Version name, servers behind proxies may report a range of versions
+    pub version: String,
+    pub protocol: i32,
+
+    /// Message of the day without formatting codes
+    pub motd: String,
+    pub players_online: u32,
+    pub players_max: u32,
+
+    /// Names of some of the players online, servers may leave this out
+    pub players: Vec<String>,
+
+    /// Round trip time of the ping packet
+    pub latency: Duration
+}
+
+/// Query server status with the Server List Ping protocol, `address` is
+/// `host[:port]` or `[addr]:port` for IPv6, with port 25565 when not given
+/// https://wiki.vg/Server_List_Ping
+pub fn ping_server(address: &str) -> Result<ServerStatus> {
+    let (host, port) = split_address(address);
+
+    let mut stream = connect(host, port)
+        .with_context(|| format!("Unable to connect to server at {host}:{port}"))?;
+
+    stream.set_read_timeout(Some(Duration::from_secs(10)))?;
+
+    let mut handshake = Vec::new();
+    write_varint(&mut handshake, HANDSHAKE_PROTOCOL);
+    write_string(&mut handshake, host);
+    handshake.extend(port.to_be_bytes());
+    // next state, 1 for status
+    write_varint(&mut handshake, 1);
+
+    write_packet(&mut stream, PACKET_HANDSHAKE, &handshake)?;
+    write_packet(&mut stream, PACKET_STATUS, &[])?;
+
+    let (packet_id, data) = read_packet(&mut stream)?;
+    if packet_id != PACKET_STATUS {
+        bail!(Error::ServerStatusInvalid);
+    }
+
+    let mut data = data.as_slice();
+    let json = read_string(&mut data)?;
+    let response: ServerStatusResponse = serde_json::from_str(&json)
+        .map_err(|_| Error::ServerStatusInvalid)?;
+
+    // any payload works, the server sends it back
+    let start = Instant::now();
+    write_packet(&mut stream, PACKET_PING, &Utc::now().timestamp_millis().to_be_bytes())?;
+
+    // some servers close the connection rather than answering the ping,
+    // the status is still good without the latency
+    let latency = match read_packet(&mut stream) {
+        Ok((PACKET_PING, _)) => start.elapsed(),
+        _ => Duration::ZERO
+    };
+
+    let players = response.players.as_ref();
+
+    Ok(ServerStatus {
+        version: response.version.name,
+        protocol: response.version.protocol,
+        motd: response.description.as_ref().map_or(String::new(), chat_text),
+        players_online: players.map_or(0, |p| p.online),
+        players_max: players.map_or(0, |p| p.max),
+        players: players.map_or(Vec::new(), |p| p.sample.iter().map(|s| s.name.clone()).collect()),
+        latency
+    })
+}
+
+/// Address players join the server in `server_dir` with, from `server.properties`
+pub fn server_dir_address(server_dir: &Path) -> Result<String> {
+    let props_path = server_dir.join("server.properties");
+    let props = fs::read_to_string(&props_path)
+        .with_context(|| format!("Unable to read {}", props_path.display()))?;
+
+    let props = parse_properties(&props);
+
+    let host = props.get("server-ip")
+        .filter(|ip| !ip.is_empty())
+        .map_or("localhost", |ip| ip.as_str());
+
+    let port = props.get("server-port")
+        .map_or("25565", |port| port.as_str());
+
+    Ok(format!("{host}:{port}"))
+}
+
+/// Host and port of `host[:port]`, IPv6 addresses with a port are written
+/// in brackets `[addr]:port`, without brackets the address is host only
+pub(crate) fn split_address(address: &str) -> (&str, u16) {
+    if let Some(rest) = address.strip_prefix('[') {
+        if let Some((host, rest)) = rest.split_once(']') {
+            let port = rest.strip_prefix(':').and_then(|port| port.parse().ok());
+            return (host, port.unwrap_or(DEFAULT_PORT));
+        }
+    }
+
+    match address.split_once(':') {
+        Some((host, port)) if !port.contains(':') => match port.parse() {
+            Ok(port) => (host, port),
+            Err(_) => (address, DEFAULT_PORT)
+        },
+        _ => (address, DEFAULT_PORT)
+    }
+}
+
+/// Connect to the first address `host` resolves to that accepts the
+/// connection within the HTTP connect timeout
+fn connect(host: &str, port: u16) -> std::io::Result<TcpStream> {
+    let timeout = env::get_http_connect_timeout();
+    let mut last_err = None;
+
+    for addr in (host, port).to_socket_addrs()? {
+        match TcpStream::connect_timeout(&addr, timeout) {
+            Ok(stream) => return Ok(stream),
+            Err(e) => last_err = Some(e)
+        }
+    }
+
+    Err(last_err.unwrap_or_else(|| std::io::Error::new(std::io::ErrorKind::NotFound, "no addresses found")))
+}
+
+/// Plain text of chat component, dropping `§` formatting codes
+fn chat_text(component: &serde_json::Value) -> String {
+    let mut text = String::new();
+    append_chat_text(component, &mut text);
+
+    let mut result = String::with_capacity(text.len());
+    let mut chars = text.chars();
+
+    while let Some(c) = chars.next() {
+        if c == '§' {
+            chars.next();
+        } else {
+            result.push(c);
+        }
+    }
+
+    result
+}
+
+fn append_chat_text(component: &serde_json::Value, text: &mut String) {
+    match component {
+        serde_json::Value::String(s) => text.push_str(s),
+        serde_json::Value::Array(parts) => {
+            for part in parts {
+                append_chat_text(part, text);
+            }
+        },
+        serde_json::Value::Object(obj) => {
+            if let Some(s) = obj.get("text") {
+                append_chat_text(s, text);
+            }
+
+            if let Some(extra) = obj.get("extra") {
+                append_chat_text(extra, text);
+            }
+        },
+        _ => { }
+    }
+}
+
+fn write_varint(buf: &mut Vec<u8>, value: i32) {
+    let mut value = value as u32;
+
+    loop {
+        if value & !0x7f == 0 {
+            buf.push(value as u8);
+            return;
+        }
+
+        buf.push((value & 0x7f | 0x80) as u8);
+        value >>= 7;
+    }
+}
+
+fn read_varint(reader: &mut impl Read) -> Result<i32> {
+    let mut value: u32 = 0;
+
+    for i in 0..5 {
+        let mut byte = [0];
+        reader.read_exact(&mut byte)?;
+
+        value |= ((byte[0] & 0x7f) as u32) << (7 * i);
+
+        if byte[0] & 0x80 == 0 {
+            return Ok(value as i32);
+        }
+    }
+
+    bail!(Error::ServerStatusInvalid)
+}
+
+fn write_string(buf: &mut Vec<u8>, s: &str) {
+    write_varint(buf, s.len() as i32);
+    buf.extend(s.as_bytes());
+}
+
+fn read_string(reader: &mut impl Read) -> Result<String> {
+    let length = read_varint(reader)?;
+
+    if length < 0 {
+        bail!(Error::ServerStatusInvalid);
+    }
+
+    let mut bytes = vec![0; length as usize];
+    reader.read_exact(&mut bytes)?;
+
+    Ok(String::from_utf8_lossy(&bytes).to_string())
+}
+
+fn write_packet(writer: &mut impl Write, packet_id: i32, data: &[u8]) -> Result<()> {
+    let mut body = Vec::with_capacity(data.len() + 5);
+    write_varint(&mut body, packet_id);
+    body.extend(data);
+
+    let mut packet = Vec::with_capacity(body.len() + 5);
+    write_varint(&mut packet, body.len() as i32);
+    packet.extend(body);
+
+    writer.write_all(&packet)?;
+    Ok(())
+}
+
+fn read_packet(reader: &mut impl Read) -> Result<(i32, Vec<u8>)> {
+    let length = read_varint(reader)?;
+
+    // status json is limited to 32767 characters, up to 4 bytes each
+    if !(1..=131071).contains(&length) {
+        bail!(Error::ServerStatusInvalid);
+    }
+
+    let mut packet = vec![0; length as usize];
+    reader.read_exact(&mut packet)?;
+
+    let mut packet = packet.as_slice();
+    let packet_id = read_varint(&mut packet)?;
+
+    Ok((packet_id, packet.to_vec()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn varint_round_trip() {
+        for value in [0, 1, 127, 128, 25565, i32::MAX, -1] {
+            let mut buf = Vec::new();
+            write_varint(&mut buf, value);
+            assert_eq!(read_varint(&mut buf.as_slice()).unwrap(), value);
+        }
+
+        let mut buf = Vec::new();
+        write_varint(&mut buf, -1);
+        assert_eq!(buf, [0xff, 0xff, 0xff, 0xff, 0x0f]);
+    }
+
+    #[test]
+    fn motd_plain_text() {
+        let component = serde_json::json!({
+            "text": "§aA Minecraft",
+            "extra": [" Server", { "text": " §lHere" }]
+        });
+
+        assert_eq!(chat_text(&component), "A Minecraft Server Here");
+    }
+
+    #[test]
+    fn address_host_port() {
+        assert_eq!(split_address("example.com"), ("example.com", 25565));
+        assert_eq!(split_address("localhost:25566"), ("localhost", 25566));
+        assert_eq!(split_address("[::1]:25566"), ("::1", 25566));
+        assert_eq!(split_address("[2001:db8::1]"), ("2001:db8::1", 25565));
+        assert_eq!(split_address("2001:db8::1"), ("2001:db8::1", 25565));
+        assert_eq!(split_address("::1"), ("::1", 25565));
+    }
+}
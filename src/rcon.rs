@@ -0,0 +1,204 @@
+/*
+ * Steve Launcher - A Minecraft Launcher
+ * Copyright (C) 2023 Josh Kropf <josh@slashdev.ca>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use anyhow::{bail, Context, Result};
+use std::{
+    collections::HashMap, fs, io::{Read, Write}, net::TcpStream, path::Path, time::Duration
+};
+
+use crate::Error;
+
+const PACKET_LOGIN: i32 = 3;
+const PACKET_COMMAND: i32 = 2;
+const PACKET_RESPONSE: i32 = 0;
+
+/// Minecraft server rcon client, using the Source RCON protocol
+/// https://wiki.vg/RCON
+pub struct RconClient {
+    stream: TcpStream,
+    next_id: i32
+}
+
+impl RconClient {
+    /// Connect and login to the server at `address`, i.e. `localhost:25575`
+    pub fn connect(address: &str, password: &str) -> Result<Self> {
+        let stream = TcpStream::connect(address)
+            .with_context(|| format!("Unable to connect to rcon at {address}"))?;
+
+        stream.set_read_timeout(Some(Duration::from_secs(10)))?;
+
+        let mut client = RconClient { stream, next_id: 1 };
+
+        let id = client.send(PACKET_LOGIN, password)?;
+        let (response_id, _) = client.read()?;
+
+        // server responds with id -1 when the password is wrong
+        if response_id != id {
+            bail!(Error::RconAuthFailed);
+        }
+
+        Ok(client)
+    }
+
+    /// Connect using the rcon settings in `server.properties` of `server_dir`
+    pub fn connect_server_dir(server_dir: &Path) -> Result<Self> {
+        let props_path = server_dir.join("server.properties");
+        let props = fs::read_to_string(&props_path)
+            .with_context(|| format!("Unable to read {}", props_path.display()))?;
+
+        let props = parse_properties(&props);
+
+        if props.get("enable-rcon").map(String::as_str) != Some("true") {
+            bail!(Error::RconDisabled);
+        }
+
+        let host = props.get("server-ip")
+            .filter(|ip| !ip.is_empty())
+            .map_or("localhost", |ip| ip.as_str());
+
+        let port = props.get("rcon.port")
+            .map_or("25575", |port| port.as_str());
+
+        let password = props.get("rcon.password")
+            .map_or("", |password| password.as_str());
+
+        Self::connect(&format!("{host}:{port}"), password)
+    }
+
+    /// Run command and return the server output
+    pub fn command(&mut self, command: &str) -> Result<String> {
+        let id = self.send(PACKET_COMMAND, command)?;
+
+        // long output is split over multiple packets, the response to an
+        // invalid packet marks the end of the command output
+        let end_id = self.send(PACKET_RESPONSE, "")?;
+
+        let mut output = String::new();
+
+        loop {
+            let (response_id, body) = self.read()?;
+
+            if response_id == end_id {
+                break;
+            } else if response_id == id {
+                output.push_str(&body);
+            }
+        }
+
+        Ok(output)
+    }
+
+    fn send(&mut self, packet_type: i32, body: &str) -> Result<i32> {
+        let id = self.next_id;
+        self.next_id += 1;
+
+        self.stream.write_all(&encode_packet(id, packet_type, body))?;
+
+        Ok(id)
+    }
+
+    fn read(&mut self) -> Result<(i32, String)> {
+        let (id, _, body) = decode_packet(&mut self.stream)?;
+        Ok((id, body))
+    }
+}
+
+fn encode_packet(id: i32, packet_type: i32, body: &str) -> Vec<u8> {
+    // id, type, body and two null bytes
+    let length = (4 + 4 + body.len() + 2) as i32;
+
+    let mut packet = Vec::with_capacity(length as usize + 4);
+    packet.extend(length.to_le_bytes());
+    packet.extend(id.to_le_bytes());
+    packet.extend(packet_type.to_le_bytes());
+    packet.extend(body.as_bytes());
+    packet.extend([0, 0]);
+
+    packet
+}
+
+fn decode_packet(reader: &mut impl Read) -> Result<(i32, i32, String)> {
+    let mut int = [0; 4];
+
+    reader.read_exact(&mut int)?;
+    let length = i32::from_le_bytes(int);
+
+    if !(10..=4096 + 10).contains(&length) {
+        bail!(Error::RconInvalidPacket);
+    }
+
+    let mut packet = vec![0; length as usize];
+    reader.read_exact(&mut packet)?;
+
+    let id = i32::from_le_bytes(packet[0..4].try_into().unwrap());
+    let packet_type = i32::from_le_bytes(packet[4..8].try_into().unwrap());
+    let body = String::from_utf8_lossy(&packet[8..packet.len() - 2]).to_string();
+
+    Ok((id, packet_type, body))
+}
+
+/// Parse java properties file, enough of the format for `server.properties`
+pub(crate) fn parse_properties(text: &str) -> HashMap<String, String> {
+    text.lines()
+        .map(|line| line.trim_start())
+        .filter(|line| !line.is_empty() && !line.starts_with('#') && !line.starts_with('!'))
+        .filter_map(|line| line.split_once(['=', ':']))
+        .map(|(key, value)| (unescape(key.trim()), unescape(value.trim())))
+        .collect()
+}
+
+fn unescape(s: &str) -> String {
+    let mut result = String::with_capacity(s.len());
+    let mut chars = s.chars();
+
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            if let Some(c) = chars.next() {
+                result.push(c);
+            }
+        } else {
+            result.push(c);
+        }
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn packet_round_trip() {
+        let packet = encode_packet(7, PACKET_COMMAND, "list");
+        assert_eq!(packet.len(), 4 + 4 + 4 + 4 + 2);
+
+        let (id, packet_type, body) = decode_packet(&mut packet.as_slice()).unwrap();
+        assert_eq!(id, 7);
+        assert_eq!(packet_type, PACKET_COMMAND);
+        assert_eq!(body, "list");
+    }
+
+    #[test]
+    fn properties_rcon_settings() {
+        let props = parse_properties("#Minecraft server properties\nenable-rcon=true\nrcon.password=a\\=b\nrcon.port=25575\n");
+        assert_eq!(props.get("enable-rcon").unwrap(), "true");
+        assert_eq!(props.get("rcon.password").unwrap(), "a=b");
+        assert_eq!(props.get("rcon.port").unwrap(), "25575");
+    }
+}
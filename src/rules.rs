@@ -16,41 +16,75 @@
  * along with this program.  If not, see <https://www.gnu.org/licenses/>.
  */
 
+use regex::Regex;
+
 use crate::json::{GameLibraryRule, OsProperties, GameArgRule};
 
 pub trait RulesMatch {
-    fn matches(&self) -> bool;
+    fn matches(&self) -> bool {
+        self.matches_features(&[])
+    }
+
+    /// Match rules with launch features enabled, i.e. `has_custom_resolution`
+    fn matches_features(&self, features: &[&str]) -> bool;
 }
 
 impl RulesMatch for Vec<GameLibraryRule> {
-    fn matches(&self) -> bool {
-        _match_lib_rules(self, &RulesContext::new())
+    fn matches_features(&self, features: &[&str]) -> bool {
+        _match_lib_rules(self, &RulesContext::new(features))
     }
 }
 
 impl RulesMatch for Vec<GameArgRule> {
-    fn matches(&self) -> bool {
-        _match_arg_rules(self, &RulesContext::new())
+    fn matches_features(&self, features: &[&str]) -> bool {
+        _match_arg_rules(self, &RulesContext::new(features))
     }
 }
 
-#[allow(dead_code)]
-struct RulesContext {
+struct RulesContext<'a> {
     host_os: &'static str,
     host_version: &'static str,
-    host_arch: &'static str
+    host_arch: &'static str,
+    features: &'a [&'a str]
 }
 
-impl RulesContext {
-    fn new() -> Self {
+impl<'a> RulesContext<'a> {
+    fn new(features: &'a [&'a str]) -> Self {
         RulesContext {
             host_os: crate::env::get_host_os(),
-            host_version: "1.0", // FIXME add OS version
-            host_arch: std::env::consts::ARCH
+            host_version: crate::env::get_host_os_version(),
+            host_arch: crate::env::get_host_arch(),
+            features
         }
     }
 }
 
+/// Architecture suffix of natives classifiers and the matching host arch,
+/// natives without a suffix are for x86_64
+const NATIVES_ARCH_SUFFIXES: &[(&str, &str)] = &[
+    ("-arm64", "arm64"),
+    ("-arm32", "arm"),
+    ("-x86", "x86")
+];
+
+/// Natives library classifier, i.e. `natives-macos-arm64`, is built for the
+/// host architecture. Other classifiers always match.
+pub fn natives_classifier_matches(classifier: &str) -> bool {
+    _match_natives_arch(classifier, crate::env::get_host_arch())
+}
+
+fn _match_natives_arch(classifier: &str, host_arch: &str) -> bool {
+    if !classifier.starts_with("natives-") {
+        return true;
+    }
+
+    let arch = NATIVES_ARCH_SUFFIXES.iter()
+        .find(|(suffix, _)| classifier.ends_with(suffix))
+        .map_or("x86_64", |(_, arch)| arch);
+
+    arch == host_arch
+}
+
 fn _match_lib_rules(rules: &Vec<GameLibraryRule>, ctx: &RulesContext) -> bool {
     let mut result = false;
 
@@ -80,9 +114,9 @@ fn _match_lib_rules(rules: &Vec<GameLibraryRule>, ctx: &RulesContext) -> bool {
 fn _match_arg_rules(rules: &Vec<GameArgRule>, ctx: &RulesContext) -> bool {
     for rule in rules {
         if rule.action == "allow" {
-            if let Some(_features) = &rule.features {
-                // FIXME not implemented
-                return false;
+            if let Some(features) = &rule.features {
+                return features.iter()
+                    .all(|(name, enabled)| ctx.features.contains(&name.as_str()) == *enabled);
             }
 
             if let Some(os) = &rule.os {
@@ -96,16 +130,30 @@ fn _match_arg_rules(rules: &Vec<GameArgRule>, ctx: &RulesContext) -> bool {
 }
 
 fn _match_os_properties(os: &OsProperties, ctx: &RulesContext) -> bool {
-    os.name.as_ref().map_or(true, |v| v == ctx.host_os) &&
-    // FIXME is it worth it to add os_info and regex crates just for this?
-    // os.version.as_ref().map_or(true, |v| v == ) &&
-    os.arch.as_ref().map_or(true, |v| v == ctx.host_arch)
+    os.name.as_ref().is_none_or(|v| v == ctx.host_os) &&
+    // version is a regex, i.e. "^10\\." to match windows 10
+    os.version.as_ref().is_none_or(|v| {
+        Regex::new(v).is_ok_and(|re| re.is_match(ctx.host_version))
+    }) &&
+    os.arch.as_ref().is_none_or(|v| v == ctx.host_arch)
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{_match_lib_rules, RulesContext};
-    use crate::json::{GameLibraryRule, OsProperties};
+    use std::collections::HashMap;
+
+    use super::{_match_arg_rules, _match_lib_rules, _match_natives_arch, RulesContext};
+    use crate::json::{GameArgRule, GameLibraryRule, OsProperties};
+
+    #[test]
+    fn natives_arch_match() {
+        assert!(_match_natives_arch("natives-macos-arm64", "arm64"));
+        assert!(!_match_natives_arch("natives-macos", "arm64"));
+        assert!(_match_natives_arch("natives-macos", "x86_64"));
+        assert!(!_match_natives_arch("natives-windows-x86", "x86_64"));
+        assert!(_match_natives_arch("natives-linux-arm32", "arm"));
+        assert!(_match_natives_arch("sources", "arm64"));
+    }
 
     #[test]
     fn basic_allow_true() {
@@ -121,10 +169,11 @@ mod tests {
         let ctx = RulesContext {
             host_os: "linux",
             host_version: "",
-            host_arch: "x86_64"
+            host_arch: "x86_64",
+            features: &[]
         };
 
-        assert_eq!(_match_lib_rules(&rules, &ctx), true);
+        assert!(_match_lib_rules(&rules, &ctx));
     }
 
     #[test]
@@ -141,10 +190,11 @@ mod tests {
         let ctx = RulesContext {
             host_os: "windows",
             host_version: "",
-            host_arch: "x86_64"
+            host_arch: "x86_64",
+            features: &[]
         };
 
-        assert_eq!(_match_lib_rules(&rules, &ctx), false);
+        assert!(!_match_lib_rules(&rules, &ctx));
     }
 
     #[test]
@@ -165,10 +215,11 @@ mod tests {
         let ctx = RulesContext {
             host_os: "linux",
             host_version: "",
-            host_arch: "x86_64"
+            host_arch: "x86_64",
+            features: &[]
         };
 
-        assert_eq!(_match_lib_rules(&rules, &ctx), true);
+        assert!(_match_lib_rules(&rules, &ctx));
     }
 
     #[test]
@@ -189,9 +240,57 @@ mod tests {
         let ctx = RulesContext {
             host_os: "osx",
             host_version: "",
-            host_arch: "x86_64"
+            host_arch: "x86_64",
+            features: &[]
+        };
+
+        assert!(!_match_lib_rules(&rules, &ctx));
+    }
+
+    #[test]
+    fn arg_features_match() {
+        let rules = vec![
+            GameArgRule {
+                action: "allow".to_string(),
+                features: Some(HashMap::from([("has_custom_resolution".to_string(), true)])),
+                os: None
+            }
+        ];
+        let mut ctx = RulesContext {
+            host_os: "linux",
+            host_version: "",
+            host_arch: "x86_64",
+            features: &[]
         };
 
-        assert_eq!(_match_lib_rules(&rules, &ctx), false);
+        assert!(!_match_arg_rules(&rules, &ctx));
+
+        ctx.features = &["has_custom_resolution"];
+        assert!(_match_arg_rules(&rules, &ctx));
+    }
+
+    #[test]
+    fn os_version_regex() {
+        let rules = vec![
+            GameLibraryRule {
+                action: "allow".to_string(),
+                os: Some(OsProperties {
+                    name: Some("osx".to_string()),
+                    version: Some("^10\\.5\\.\\d$".to_string()),
+                    arch: None
+                })
+            }
+        ];
+        let mut ctx = RulesContext {
+            host_os: "osx",
+            host_version: "10.5.8",
+            host_arch: "x86_64",
+            features: &[]
+        };
+
+        assert!(_match_lib_rules(&rules, &ctx));
+
+        ctx.host_version = "14.1.2";
+        assert!(!_match_lib_rules(&rules, &ctx));
     }
 }
@@ -0,0 +1,133 @@
+/*
+ * Steve Launcher - A Minecraft Launcher
+ * Copyright (C) 2023 Josh Kropf <josh@slashdev.ca>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use anyhow::Result;
+use serde::{de::DeserializeOwned, Serialize};
+use std::{fs, path::{Path, PathBuf}};
+
+use crate::{json::{OpEntry, WhitelistEntry}, mojang_api::{dashed_uuid, PlayerProfile}};
+
+const WHITELIST_FILE: &str = "whitelist.json";
+const OPS_FILE: &str = "ops.json";
+
+/// Whitelist and operators of a server, in `whitelist.json` and `ops.json`
+/// of the server directory. A running server only reads the files at start,
+/// or `whitelist reload` for the whitelist.
+pub struct ServerPlayers {
+    dir: PathBuf
+}
+
+impl ServerPlayers {
+    pub fn new(server_dir: &Path) -> Self {
+        ServerPlayers { dir: server_dir.to_path_buf() }
+    }
+
+    pub fn whitelist(&self) -> Result<Vec<WhitelistEntry>> {
+        self.read_list(WHITELIST_FILE)
+    }
+
+    /// Add player to the whitelist, returns `false` when already on the list
+    pub fn whitelist_add(&self, profile: &PlayerProfile) -> Result<bool> {
+        let mut list = self.whitelist()?;
+        let uuid = dashed_uuid(&profile.uuid);
+
+        if list.iter().any(|e| e.uuid == uuid) {
+            return Ok(false);
+        }
+
+        list.push(WhitelistEntry { uuid, name: profile.name.clone() });
+
+        self.write_list(WHITELIST_FILE, &list)?;
+        Ok(true)
+    }
+
+    /// Remove player by name or UUID, returns `false` when not on the list
+    pub fn whitelist_remove(&self, name_or_uuid: &str) -> Result<bool> {
+        let mut list = self.whitelist()?;
+        let len = list.len();
+
+        list.retain(|e| !is_player(&e.name, &e.uuid, name_or_uuid));
+
+        if list.len() == len {
+            return Ok(false);
+        }
+
+        self.write_list(WHITELIST_FILE, &list)?;
+        Ok(true)
+    }
+
+    pub fn ops(&self) -> Result<Vec<OpEntry>> {
+        self.read_list(OPS_FILE)
+    }
+
+    /// Make player an operator with permission `level`, replacing the level
+    /// of an existing operator
+    pub fn op_add(&self, profile: &PlayerProfile, level: u8) -> Result<()> {
+        let mut list = self.ops()?;
+        let uuid = dashed_uuid(&profile.uuid);
+
+        match list.iter_mut().find(|e| e.uuid == uuid) {
+            Some(entry) => entry.level = level,
+            None => list.push(OpEntry {
+                uuid,
+                name: profile.name.clone(),
+                level,
+                bypasses_player_limit: false
+            })
+        }
+
+        self.write_list(OPS_FILE, &list)
+    }
+
+    /// Remove operator by name or UUID, returns `false` when not an operator
+    pub fn op_remove(&self, name_or_uuid: &str) -> Result<bool> {
+        let mut list = self.ops()?;
+        let len = list.len();
+
+        list.retain(|e| !is_player(&e.name, &e.uuid, name_or_uuid));
+
+        if list.len() == len {
+            return Ok(false);
+        }
+
+        self.write_list(OPS_FILE, &list)?;
+        Ok(true)
+    }
+
+    /// Read player list file, missing until the server first starts
+    fn read_list<T: DeserializeOwned>(&self, file_name: &str) -> Result<Vec<T>> {
+        let path = self.dir.join(file_name);
+
+        if !path.exists() {
+            return Ok(Vec::new());
+        }
+
+        Ok(serde_json::from_str(&fs::read_to_string(path)?)?)
+    }
+
+    fn write_list<T: Serialize>(&self, file_name: &str, list: &[T]) -> Result<()> {
+        fs::write(self.dir.join(file_name), serde_json::to_string_pretty(list)?)?;
+        Ok(())
+    }
+}
+
+/// Player names are case insensitive, UUIDs may be given without dashes
+fn is_player(name: &str, uuid: &str, name_or_uuid: &str) -> bool {
+    name.eq_ignore_ascii_case(name_or_uuid)
+        || uuid.eq_ignore_ascii_case(&dashed_uuid(name_or_uuid))
+}
@@ -1,4 +1,4 @@
-use std::{fs::{self, File}, io::{self, Result, Read, Seek, Write}, path::Path};
+use std::{fs::{self, File}, io::{self, Result, Read, Seek, Write}, path::{Path, PathBuf}};
 use walkdir::{DirEntry, WalkDir};
 use zip::{result::ZipResult, write::FileOptions, ZipArchive, ZipWriter};
 
@@ -77,6 +77,31 @@ where
     ZipResult::Ok(())
 }
 
+/// Create zip from list of files paired with their name in the zip, and
+/// entries with contents written from memory
+pub fn create_zip_from(
+    zip_file: File,
+    files: &[(String, PathBuf)],
+    data: &[(&str, &[u8])]
+) -> Result<()> {
+    let mut zip = ZipWriter::new(zip_file);
+    let options = FileOptions::default();
+
+    for (name, contents) in data {
+        zip.start_file(*name, options)?;
+        zip.write_all(contents)?;
+    }
+
+    for (name, path) in files {
+        zip.start_file(name, options)?;
+        io::copy(&mut File::open(path)?, &mut zip)?;
+    }
+
+    zip.finish()?;
+
+    Ok(())
+}
+
 pub fn make_modded_jar<P, I>(output_jar: P, mc_jar: P, jar_mods: I) -> Result<()>
     where P: AsRef<Path>, I: Iterator, I::Item: AsRef<Path>
 {
@@ -16,9 +16,12 @@
  * along with this program.  If not, see <https://www.gnu.org/licenses/>.
  */
 
-pub use clap::Parser;
-use clap::Subcommand;
-use std::path::PathBuf;
+pub use clap::{CommandFactory, Parser};
+use clap::{Subcommand, ValueEnum};
+use clap_complete::engine::{ArgValueCompleter, CompletionCandidate, PathCompleter, ValueCompleter};
+use std::{ffi::OsStr, path::PathBuf};
+
+use steve::{CurseForgeSort, InstanceRegistry};
 
 #[derive(Parser)]
 #[command(author, version, about, long_about = None)]
@@ -28,6 +31,20 @@ pub struct Cli {
     #[arg(short, verbatim_doc_comment)]
     pub data_dir: Option<PathBuf>,
 
+    /// Number of assets to download at the same time
+    /// [default: $STEVE_DOWNLOAD_JOBS or 8]
+    #[arg(short, long, verbatim_doc_comment)]
+    pub jobs: Option<usize>,
+
+    /// Directory to watch for manually downloaded mods, repeat to watch several
+    /// [default: $STEVE_DOWNLOADS_DIR, $XDG_DOWNLOAD_DIR or ~/Downloads]
+    #[arg(long = "downloads-dir", value_name = "DIR", verbatim_doc_comment)]
+    pub downloads_dirs: Vec<PathBuf>,
+
+    /// Print progress, prompts and errors as JSON lines instead of progress bars
+    #[arg(long, global = true)]
+    pub json: bool,
+
     #[command(subcommand)]
     pub command: Commands
 }
@@ -46,22 +63,100 @@ pub enum Commands {
         #[arg(long)]
         snapshots: bool,
 
-        /// Mod laoder <forge|neoforge>[-<version>], prompt for version when not specified
+        /// Mod loader <forge|neoforge|quilt>[-<version>], prompt for version when not specified
         #[arg(long)]
-        loader: Option<String>
+        loader: Option<String>,
+
+        /// Name to register the instance with [default: directory name]
+        #[arg(long)]
+        name: Option<String>
     },
 
     /// Download instance assets and launch
     Launch {
-        /// Path to directory of instance
+        /// Path to instance directory, or name of registered instance
+        #[arg(add = ArgValueCompleter::new(complete_instance))]
         dir: PathBuf,
 
         /// Allow steve to exit while the java process is running
         #[arg(short)]
-        detach: bool
+        detach: bool,
+
+        /// Launch without signing in, using this player name (single player,
+        /// LAN and offline-mode servers only)
+        #[arg(long, visible_alias = "offline", value_name = "NAME")]
+        offline_user: Option<String>,
+
+        /// Launch with the game files already downloaded, without checking for
+        /// updates or downloading anything missing
+        #[arg(long)]
+        no_update: bool,
+
+        /// Launch the demo version of Minecraft
+        #[arg(long)]
+        demo: bool,
+
+        /// Join server directly, skipping the title screen
+        /// (IPv6 addresses with a port as [ADDR]:PORT)
+        #[arg(long, value_name = "HOST[:PORT]", conflicts_with = "world")]
+        server: Option<String>,
+
+        /// Load singleplayer world directly, skipping the title screen
+        /// (Minecraft 1.20 or newer)
+        #[arg(long, value_name = "SAVE")]
+        world: Option<String>
+    },
+
+    /// Copy instance to a new directory
+    Clone {
+        /// Path to instance directory, or name of registered instance
+        #[arg(add = ArgValueCompleter::new(complete_instance))]
+        dir: PathBuf,
+
+        /// Path of new instance directory
+        dest: PathBuf
+    },
+
+    /// Delete instance directory, including worlds
+    Delete {
+        /// Path to instance directory, or name of registered instance
+        #[arg(add = ArgValueCompleter::new(complete_instance))]
+        dir: PathBuf,
+
+        /// Don't ask for confirmation
+        #[arg(short)]
+        yes: bool
+    },
+
+    /// Print instance details, resolved java, mods, disk usage and play time
+    Info {
+        /// Path to instance directory, or name of registered instance
+        #[arg(add = ArgValueCompleter::new(complete_instance))]
+        dir: PathBuf
+    },
+
+    /// Print the game output of the most recent launch
+    Logs {
+        /// Path to instance directory, or name of registered instance
+        #[arg(add = ArgValueCompleter::new(complete_instance))]
+        dir: PathBuf,
+
+        /// Keep printing output as it is written to the log
+        #[arg(short)]
+        follow: bool
     },
 
-    /// Authenticate with your Microsoft account and save account details
+    /// List instances in a directory
+    List {
+        /// Directory containing instances [default: $STEVE_INSTANCES_DIR or current directory]
+        dir: Option<PathBuf>,
+
+        /// List the instances steve has created or launched, from any directory
+        #[arg(short, long, conflicts_with = "dir")]
+        all: bool
+    },
+
+    /// Authenticate with a Microsoft account and save account details
     Auth {
         #[clap(subcommand)]
         command: Option<AuthCommands>
@@ -73,7 +168,58 @@ pub enum Commands {
         dir: PathBuf,
 
         /// Path to CurseForge modpack zip
-        zip_file: PathBuf
+        #[arg(required_unless_present_any = ["prism", "vanilla"])]
+        zip_file: Option<PathBuf>,
+
+        /// Create instance from the Prism Launcher or MultiMC instance in <DIR>
+        #[arg(long, conflicts_with_all = ["zip_file", "vanilla"])]
+        prism: bool,
+
+        /// Create instances for the Mojang launcher profiles in the .minecraft <DIR>
+        #[arg(long, conflicts_with = "zip_file")]
+        vanilla: bool,
+
+        /// List files the pack would add, replace or remove without changing the instance
+        #[arg(long, requires = "zip_file")]
+        dry_run: bool,
+
+        /// Handling of config files changed since the pack was installed [default: backup]
+        #[arg(long, requires = "zip_file", value_parser = ["keep", "overwrite", "backup"])]
+        config_conflicts: Option<String>,
+
+        /// Write urls of files that must be downloaded manually to <FILE> and exit
+        #[arg(long, value_name = "FILE", requires = "zip_file")]
+        blocked_out: Option<PathBuf>,
+
+        /// Don't ask for confirmation, files left from the previous pack version are removed
+        #[arg(short)]
+        yes: bool
+    },
+
+    /// Export instance as CurseForge modpack zip, Modrinth mrpack, or Prism instance zip
+    Export {
+        /// Path to instance directory, or name of registered instance
+        #[arg(add = ArgValueCompleter::new(complete_instance))]
+        dir: PathBuf,
+
+        /// Path of modpack file to create
+        zip_file: PathBuf,
+
+        /// Modpack format
+        #[arg(long, value_enum, default_value_t = PackFormat::Curseforge)]
+        format: PackFormat,
+
+        /// Modpack name [default: instance directory name]
+        #[arg(long)]
+        name: Option<String>,
+
+        /// Modpack version
+        #[arg(long, default_value = "1.0.0")]
+        pack_version: String,
+
+        /// Modpack author [default: $USER]
+        #[arg(long)]
+        author: Option<String>
     },
 
     /// Search and install FTB or CurseForge modpack into new or existing instance
@@ -84,22 +230,471 @@ pub enum Commands {
         /// Modpack search term
         search: String,
 
-        /// Maximum number of search results
-        #[arg(short, long, default_value_t = 5, value_parser = clap::value_parser!(u8).range(1..50))]
-        search_limit: u8
+        /// Maximum number of search results, FTB searches return at most 50
+        #[arg(short, long, default_value_t = 5, value_parser = clap::value_parser!(u16).range(1..=1000))]
+        search_limit: u16,
+
+        /// Search CurseForge directly, paging through results beyond the first 50
+        #[arg(long)]
+        curseforge: bool,
+
+        /// Order of CurseForge search results [default: popularity]
+        #[arg(long, requires = "curseforge", value_enum)]
+        sort: Option<CurseForgeSort>,
+
+        /// Only show CurseForge modpacks in category, by name or slug
+        #[arg(long, requires = "curseforge")]
+        category: Option<String>,
+
+        /// List files the pack would add, replace or remove without changing the instance
+        #[arg(long)]
+        dry_run: bool,
+
+        /// Handling of config files changed since the pack was installed [default: backup]
+        #[arg(long, value_parser = ["keep", "overwrite", "backup"])]
+        config_conflicts: Option<String>,
+
+        /// Write urls of files that must be downloaded manually to <FILE> and exit
+        #[arg(long, value_name = "FILE")]
+        blocked_out: Option<PathBuf>,
+
+        /// Don't ask for confirmation, files left from the previous pack version are removed
+        #[arg(short)]
+        yes: bool
+    },
+
+    /// Delete natives, logs, crash reports and other temporary files from instance
+    Clean {
+        /// Path to instance directory, or name of registered instance
+        #[arg(add = ArgValueCompleter::new(complete_instance))]
+        dir: PathBuf,
+
+        /// List files that would be deleted without deleting them
+        #[arg(long)]
+        dry_run: bool
     },
 
-    /// Output bash completion code
+    /// Re-hash shared assets and libraries used by instance, downloading corrupted
+    /// files again, then extract natives and make the modded jar again
+    Repair {
+        /// Path to instance directory, or name of registered instance
+        #[arg(add = ArgValueCompleter::new(complete_instance))]
+        dir: PathBuf
+    },
+
+    /// Print disk usage of instances and shared data
+    Du {
+        /// Directory containing instances [default: instances steve has created or launched]
+        dir: Option<PathBuf>
+    },
+
+    /// Delete shared libraries, assets and cached files not used by any instance
+    /// steve has created or launched
+    Gc {
+        /// List files that would be deleted without deleting them
+        #[arg(long)]
+        dry_run: bool,
+
+        /// Also delete the patched jars Forge builds at first launch, even when in use
+        #[arg(long)]
+        forge_cache: bool
+    },
+
+    /// Manage Java runtimes
+    Java {
+        #[clap(subcommand)]
+        command: JavaCommands
+    },
+
+    /// Change mod loader of an existing instance
+    Loader {
+        #[clap(subcommand)]
+        command: LoaderCommands
+    },
+
+    /// Manage instance mods
+    Mods {
+        #[clap(subcommand)]
+        command: ModsCommands
+    },
+
+    /// Look up a player profile by name or UUID
+    Profile {
+        /// Player name, or UUID with or without dashes
+        name: String
+    },
+
+    /// Administer a running Minecraft server
+    Server {
+        #[clap(subcommand)]
+        command: ServerCommands
+    },
+
+    /// Change versions of an existing instance
+    Upgrade {
+        #[clap(subcommand)]
+        command: UpgradeCommands
+    },
+
+    /// Copy game and mod loader manifests into instance, pinning the versions used at launch
+    Vendor {
+        /// Path to instance directory, or name of registered instance
+        #[arg(add = ArgValueCompleter::new(complete_instance))]
+        dir: PathBuf
+    },
+
+    /// List singleplayer worlds of an instance
+    Worlds {
+        #[clap(subcommand)]
+        command: WorldsCommands
+    },
+
+    /// Get or set launcher defaults in config.toml
+    Config {
+        #[clap(subcommand)]
+        command: ConfigCommands
+    },
+
+    /// Output shell code registering completions, including registered instance names
     ///
-    /// eval "$(steve completion)"
-    Completion
+    /// source <(steve completion bash)
+    Completion {
+        shell: CompletionShell
+    }
 }
 
 #[derive(Subcommand)]
 pub enum AuthCommands {
-    /// Print information about the stored account details
+    /// Print information about the active account details
     Status,
 
+    /// List stored accounts
+    List,
+
+    /// Make account active, used to launch instances without a pinned account
+    Switch {
+        /// Name of account
+        name: String
+    },
+
     /// Delete stored account details
+    Remove {
+        /// Name of account
+        name: String
+    },
+
+    /// Launch instance with a specific account, in place of the active account
+    Pin {
+        /// Path to instance directory, or name of registered instance
+        #[arg(add = ArgValueCompleter::new(complete_instance))]
+        dir: PathBuf,
+
+        /// Name of account
+        name: String
+    },
+
+    /// Launch instance with the active account
+    Unpin {
+        /// Path to instance directory, or name of registered instance
+        #[arg(add = ArgValueCompleter::new(complete_instance))]
+        dir: PathBuf
+    },
+
+    /// Delete all stored account details
     Clear
 }
+
+#[derive(Subcommand)]
+pub enum UpgradeCommands {
+    /// Change version of minecraft and select a compatible mod loader version
+    McVersion {
+        /// Path to instance directory, or name of registered instance
+        #[arg(add = ArgValueCompleter::new(complete_instance))]
+        dir: PathBuf,
+
+        /// Version of minecraft or prompt to select from list when not specified
+        mc_version: Option<String>,
+
+        /// Version of the instance mod loader, prompt to select from list when not specified
+        #[arg(long)]
+        loader_version: Option<String>,
+
+        /// Enable snapshots in prompt
+        #[arg(long)]
+        snapshots: bool,
+
+        /// Don't ask for confirmation when mods may be incompatible
+        #[arg(short)]
+        yes: bool
+    }
+}
+
+#[derive(Subcommand)]
+pub enum LoaderCommands {
+    /// Change version of the instance mod loader
+    Upgrade {
+        /// Path to instance directory, or name of registered instance
+        #[arg(add = ArgValueCompleter::new(complete_instance))]
+        dir: PathBuf,
+
+        /// Mod loader version or prompt to select from list when not specified
+        version: Option<String>
+    },
+
+    /// Switch instance to a different mod loader
+    Switch {
+        /// Path to instance directory, or name of registered instance
+        #[arg(add = ArgValueCompleter::new(complete_instance))]
+        dir: PathBuf,
+
+        /// Mod loader <forge|neoforge|quilt>[-<version>], prompt for version when not specified
+        loader: String
+    }
+}
+
+#[derive(Subcommand)]
+pub enum WorldsCommands {
+    /// List worlds with their name, game version and when they were last played
+    List {
+        /// Path to instance directory, or name of registered instance
+        #[arg(add = ArgValueCompleter::new(complete_instance))]
+        dir: PathBuf,
+
+        /// Also show the world seeds
+        #[arg(long)]
+        seed: bool
+    },
+
+    /// Copy datapacks into the datapacks directory of a world
+    Datapack {
+        /// Path to instance directory, or name of registered instance
+        #[arg(add = ArgValueCompleter::new(complete_instance))]
+        dir: PathBuf,
+
+        /// Datapack zip files or directories
+        #[arg(required = true)]
+        files: Vec<PathBuf>,
+
+        /// Directory name of the world in saves [default: prompt to select]
+        #[arg(long)]
+        world: Option<String>
+    }
+}
+
+#[derive(Subcommand)]
+pub enum JavaCommands {
+    /// List installed Java runtimes
+    List,
+
+    /// Install Java runtime
+    Install {
+        /// Major version of Java, i.e. 17
+        version: u32
+    },
+
+    /// Launch instance with a specific Java runtime
+    Pin {
+        /// Path to instance directory, or name of registered instance
+        #[arg(add = ArgValueCompleter::new(complete_instance))]
+        dir: PathBuf,
+
+        /// Major version of Java, i.e. 17
+        version: u32
+    },
+
+    /// Launch instance with the Java runtime for its version of Minecraft
+    Unpin {
+        /// Path to instance directory, or name of registered instance
+        #[arg(add = ArgValueCompleter::new(complete_instance))]
+        dir: PathBuf
+    }
+}
+
+#[derive(Subcommand)]
+pub enum ServerCommands {
+    /// Send command to server over rcon, using settings from server.properties
+    Cmd {
+        /// Path to server directory
+        dir: PathBuf,
+
+        /// Server command, i.e. "whitelist add Steve"
+        #[arg(required = true, trailing_var_arg = true)]
+        command: Vec<String>
+    },
+
+    /// Print server version, message of the day and players online
+    Status {
+        /// Path to server directory, or server address as host[:port] [default: current directory]
+        target: Option<String>
+    },
+
+    /// Edit the players allowed to join in whitelist.json
+    Whitelist {
+        #[clap(subcommand)]
+        command: WhitelistCommands
+    },
+
+    /// Edit the server operators in ops.json
+    Ops {
+        #[clap(subcommand)]
+        command: OpsCommands
+    }
+}
+
+#[derive(Subcommand)]
+pub enum WhitelistCommands {
+    /// Add player to the whitelist
+    Add {
+        /// Path to server directory
+        dir: PathBuf,
+
+        /// Player name or UUID
+        name: String
+    },
+
+    /// Remove player from the whitelist
+    Remove {
+        /// Path to server directory
+        dir: PathBuf,
+
+        /// Player name or UUID
+        name: String
+    },
+
+    /// List whitelisted players
+    List {
+        /// Path to server directory
+        dir: PathBuf
+    }
+}
+
+#[derive(Subcommand)]
+pub enum OpsCommands {
+    /// Make player a server operator
+    Add {
+        /// Path to server directory
+        dir: PathBuf,
+
+        /// Player name or UUID
+        name: String,
+
+        /// Operator permission level
+        #[arg(long, default_value_t = 4, value_parser = clap::value_parser!(u8).range(1..=4))]
+        level: u8
+    },
+
+    /// Remove player from the server operators
+    Remove {
+        /// Path to server directory
+        dir: PathBuf,
+
+        /// Player name or UUID
+        name: String
+    }
+}
+
+#[derive(Subcommand)]
+pub enum ModsCommands {
+    /// Delete mod file, warning when other mods depend on it
+    Remove {
+        /// Path to instance directory, or name of registered instance
+        #[arg(add = ArgValueCompleter::new(complete_instance))]
+        dir: PathBuf,
+
+        /// Mod file name, or part of the name
+        name: String,
+
+        /// Don't ask for confirmation
+        #[arg(short)]
+        yes: bool
+    },
+
+    /// Stop loading mod without deleting it
+    Disable {
+        /// Path to instance directory, or name of registered instance
+        #[arg(add = ArgValueCompleter::new(complete_instance))]
+        dir: PathBuf,
+
+        /// Mod file name, or part of the name
+        name: String
+    },
+
+    /// Load mod that was disabled
+    Enable {
+        /// Path to instance directory, or name of registered instance
+        #[arg(add = ArgValueCompleter::new(complete_instance))]
+        dir: PathBuf,
+
+        /// Mod file name, or part of the name
+        name: String
+    },
+
+    /// Download files listed in mods lockfile that are missing
+    Sync {
+        /// Path to instance directory, or name of registered instance
+        #[arg(add = ArgValueCompleter::new(complete_instance))]
+        dir: PathBuf,
+
+        /// Write urls of files that must be downloaded manually to <FILE> and exit
+        #[arg(long, value_name = "FILE")]
+        blocked_out: Option<PathBuf>
+    }
+}
+
+#[derive(Subcommand)]
+pub enum ConfigCommands {
+    /// Print config value, or all values when key is omitted
+    Get {
+        key: Option<String>
+    },
+
+    /// Set config value, lists like java_args are separated by whitespace
+    Set {
+        key: String,
+
+        #[arg(allow_hyphen_values = true)]
+        value: String
+    },
+
+    /// Remove config value, restoring the default
+    Unset {
+        key: String
+    }
+}
+
+#[derive(Clone, ValueEnum)]
+pub enum PackFormat {
+    /// CurseForge modpack zip
+    Curseforge,
+
+    /// Modrinth .mrpack
+    Modrinth,
+
+    /// Prism Launcher instance zip
+    Prism
+}
+
+#[derive(Clone, ValueEnum)]
+pub enum CompletionShell {
+    Bash,
+    Zsh,
+    Fish,
+    Powershell
+}
+
+/// Complete names of registered instances, and directories for instances
+/// outside the registry
+fn complete_instance(current: &OsStr) -> Vec<CompletionCandidate> {
+    let mut candidates = Vec::new();
+
+    if let (Some(current), Ok(registry)) = (current.to_str(), InstanceRegistry::load()) {
+        candidates.extend(registry.instances()
+            .filter(|(name, _)| name.starts_with(current))
+            .map(|(name, dir)| CompletionCandidate::new(name)
+                .help(Some(dir.display().to_string().into()))));
+    }
+
+    candidates.extend(PathCompleter::dir().complete(current));
+
+    candidates
+}
@@ -17,30 +17,111 @@
  */
 
 mod auth;
+mod clean;
+mod config;
 mod create;
+mod du;
+mod export;
+mod gc;
+mod import;
+mod info;
+mod java;
 mod launch;
+mod list;
+mod loader;
+mod logs;
+mod manage;
 mod modpack;
+mod mods;
+mod profile;
+mod repair;
+mod server;
+mod upgrade;
+mod vendor;
+mod worlds;
 
 pub use {
     auth::clear_credentials,
+    auth::list_accounts,
     auth::msal_login,
+    auth::pin_account,
     auth::print_account_status,
+    auth::remove_account,
+    auth::switch_account,
+    auth::unpin_account,
+    clean::clean_instance,
+    config::config_get,
+    config::config_set,
+    config::config_unset,
     create::create_instance,
+    du::print_disk_usage,
+    export::modpack_mrpack_export,
+    export::modpack_zip_export,
+    export::prism_zip_export,
+    gc::collect_garbage,
+    import::prism_import,
+    import::vanilla_import,
+    info::print_instance_info,
+    java::java_install,
+    java::java_list,
+    java::java_pin,
+    java::java_unpin,
     launch::launch_instance,
-    modpack::modpack_search_and_install,
-    modpack::modpack_zip_install
+    list::list_instances,
+    list::list_registered_instances,
+    list::select_registered_instance,
+    loader::loader_switch,
+    loader::loader_upgrade,
+    logs::print_log,
+    manage::clone_instance,
+    manage::delete_instance,
+    modpack::{CurseForgeSearch, PackSearch, modpack_search_and_install},
+    modpack::modpack_zip_install,
+    mods::mods_disable,
+    mods::mods_enable,
+    mods::mods_remove,
+    mods::mods_sync,
+    profile::print_player_profile,
+    repair::repair_instance,
+    server::ops_add,
+    server::ops_remove,
+    server::server_command,
+    server::server_status,
+    server::whitelist_add,
+    server::whitelist_list,
+    server::whitelist_remove,
+    upgrade::upgrade_mc_version,
+    vendor::vendor_manifests,
+    worlds::install_datapacks,
+    worlds::list_worlds
 };
 
+use anyhow::{bail, Result};
 use dialoguer::{Confirm, theme::ColorfulTheme};
 
 fn console_theme() -> ColorfulTheme {
     ColorfulTheme::default()
 }
 
-fn prompt_confirm<S: Into<String>>(prompt: S) -> dialoguer::Result<bool> {
-    Confirm::with_theme(&console_theme())
+/// Fail before showing `prompt` when output is read by another program,
+/// there's no one to answer it; `hint` tells how to skip the prompt
+fn require_terminal(prompt: &str, hint: &str) -> Result<()> {
+    if crate::json_output() {
+        crate::print_json_event(serde_json::json!({ "event": "prompt", "message": prompt }));
+        bail!("{hint} when using --json");
+    }
+
+    Ok(())
+}
+
+fn prompt_confirm<S: Into<String>>(prompt: S, hint: &str) -> Result<bool> {
+    let prompt = prompt.into();
+
+    require_terminal(&prompt, hint)?;
+
+    Ok(Confirm::with_theme(&console_theme())
         .with_prompt(prompt)
         .wait_for_newline(true)
         .default(false)
-        .interact()
+        .interact()?)
 }
@@ -17,13 +17,33 @@
  */
 
 use anyhow::Result;
+use chrono::{DateTime, Local, Utc};
+use serde_json::json;
+use std::path::Path;
 
-use steve::Account;
+use super::prompt_confirm;
+use steve::{Account, Instance, LoginCancel, LoginHandler, open_url};
+
+struct PromptLoginHandler;
+
+impl LoginHandler for PromptLoginHandler {
+    // ctrl-c is enough to cancel from the terminal
+    fn show_code(&mut self, url: &str, code: &str, _cancel: LoginCancel) {
+        outln!("Open the URL in your browser and enter the code: {code}\n\t{url}");
+
+        // the url is printed in case there's no browser to open it
+        let _ = open_url(url);
+    }
+
+    fn retry_expired(&mut self) -> Result<bool> {
+        prompt_confirm("Code expired before it was entered, sign in with a new code?", "Sign in again for a new code")
+    }
+}
 
 pub async fn msal_login() -> Result<()> {
-    Account::login(|url, code| {
-        println!("Open the URL in your browser and enter the code: {code}\n\t{url}");
-    }).await?;
+    let account = Account::login(&mut PromptLoginHandler).await?;
+
+    outln!("Account '{}' saved and active", account.name());
 
     Ok(())
 }
@@ -33,11 +53,86 @@ pub fn clear_credentials() -> Result<()> {
 }
 
 pub fn print_account_status() -> Result<()> {
-    let account = Account::load()?;
+    let status = Account::load(None)?.status()?;
+
+    if crate::json_output() {
+        println!("{}", json!({
+            "name": status.name,
+            "uuid": status.uuid,
+            "active": status.active,
+            "mc_token_expires": status.mc_token_expires,
+            "msa_token_expires": status.msa_token_expires,
+            "needs_refresh": status.needs_refresh
+        }));
+        return Ok(());
+    }
+
+    let local_time = |time: DateTime<Utc>| time.with_timezone(&Local).format("%Y-%m-%d %H:%M");
+
+    outln!("Account '{}' credentials exist", status.name);
+    outln!("                      UUID {}", status.uuid.as_deref().unwrap_or("unknown until next launch"));
+    outln!("   Mojang token refresh at {}", local_time(status.mc_token_expires));
+    outln!("      MSA token refresh at {}", local_time(status.msa_token_expires));
+
+    if status.needs_refresh {
+        outln!("Tokens have expired and are refreshed at the next launch");
+    }
+
+    Ok(())
+}
+
+pub fn list_accounts() -> Result<()> {
+    let active = Account::active()?;
+
+    for name in Account::list()? {
+        let marker = if active.as_ref() == Some(&name) { "*" } else { " " };
+        outln!("{marker} {name}");
+    }
+
+    Ok(())
+}
+
+pub fn switch_account(name: &str) -> Result<()> {
+    Account::switch(name)?;
+
+    outln!("Account '{name}' is active");
+
+    Ok(())
+}
+
+pub fn remove_account(name: &str) -> Result<()> {
+    Account::remove(name)?;
+
+    match Account::active()? {
+        Some(active) => outln!("Account '{name}' removed, '{active}' is active"),
+        None => outln!("Account '{name}' removed")
+    }
+
+    Ok(())
+}
+
+pub fn pin_account(instance_dir: &Path, name: &str) -> Result<()> {
+    let mut instance = Instance::load(instance_dir)?;
+
+    let account = Account::load(Some(name))?;
+
+    // pinned by id, the profile name changes when the player renames it
+    instance.set_account(Some(account.id().to_string()))?;
+
+    outln!("Instance pinned to account '{}'", account.name());
+
+    Ok(())
+}
+
+pub fn unpin_account(instance_dir: &Path) -> Result<()> {
+    let mut instance = Instance::load(instance_dir)?;
+
+    instance.set_account(None)?;
 
-    println!("Account credentials exist");
-    println!("   Mojang token refresh at {}", account.mc_token_expires());
-    println!("      MSA token refresh at {}", account.msa_token_expires());
+    match Account::active()? {
+        Some(active) => outln!("Instance uses the active account '{active}'"),
+        None => outln!("Instance uses the active account")
+    }
 
     Ok(())
 }
@@ -0,0 +1,54 @@
+/*
+ * Steve Launcher - A Minecraft Launcher
+ * Copyright (C) 2023 Josh Kropf <josh@slashdev.ca>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use anyhow::Result;
+use indicatif::HumanBytes;
+use std::path::Path;
+
+use steve::Instance;
+
+pub fn clean_instance(instance_dir: &Path, dry_run: bool) -> Result<()> {
+    let instance = Instance::load(instance_dir)?;
+
+    let files = instance.clean(dry_run)?;
+
+    if instance.has_shared_game_dir() {
+        outln!("Game dir {} is shared with another launcher, its logs and crash reports are left alone",
+            instance.game_dir().display());
+    }
+
+    if files.is_empty() {
+        outln!("Nothing to clean");
+        return Ok(());
+    }
+
+    for (path, size) in &files {
+        let path = path.strip_prefix(&instance.dir).unwrap_or(path);
+        outln!("{:>10} {}", HumanBytes(*size).to_string(), path.display());
+    }
+
+    let total: u64 = files.iter().map(|(_, size)| size).sum();
+
+    if dry_run {
+        outln!("{} would be freed", HumanBytes(total));
+    } else {
+        outln!("{} freed", HumanBytes(total));
+    }
+
+    Ok(())
+}
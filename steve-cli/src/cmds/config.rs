@@ -0,0 +1,50 @@
+/*
+ * Steve Launcher - A Minecraft Launcher
+ * Copyright (C) 2023 Josh Kropf <josh@slashdev.ca>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use anyhow::Result;
+
+use steve::Config;
+
+pub fn config_get(key: Option<&str>) -> Result<()> {
+    let config = Config::load()?;
+
+    match key {
+        Some(key) => {
+            if let Some(value) = config.get(key)? {
+                outln!("{value}");
+            }
+        },
+        None => {
+            for key in Config::keys() {
+                if let Some(value) = config.get(key)? {
+                    outln!("{key} = {value}");
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+pub fn config_set(key: &str, value: &str) -> Result<()> {
+    Config::load()?.set(key, value)
+}
+
+pub fn config_unset(key: &str) -> Result<()> {
+    Config::load()?.unset(key)
+}
@@ -16,17 +16,19 @@
  * along with this program.  If not, see <https://www.gnu.org/licenses/>.
  */
 
-use anyhow::Result;
+use anyhow::{bail, Result};
 use dialoguer::{FuzzySelect, Select};
 use std::path::Path;
 
+use crate::ProgressHandler;
 use steve::{AssetClient, Instance, ModLoader, ModLoaderName};
 
 pub async fn create_instance(
     instance_dir: &Path,
     mc_version: Option<String>,
     snapshots: bool,
-    mod_loader: Option<String>
+    mod_loader: Option<String>,
+    name: Option<String>
 ) -> Result<()> {
     let mc_version = match mc_version {
         Some(v) => v,
@@ -34,36 +36,54 @@ pub async fn create_instance(
     };
 
     let mod_loader = if let Some(mod_loader_id) = mod_loader {
-        if let Ok(mod_loader) = mod_loader_id.parse() {
-            Some(mod_loader)
-        } else {
-            let name = mod_loader_id.parse::<ModLoaderName>()?;
-            let version = prompt_loader_version(&mc_version, &name).await?;
-            Some(ModLoader { name, version })
-        }
+        Some(resolve_mod_loader(&mc_version, &mod_loader_id).await?)
     } else {
         None
     };
 
-    Instance::create(instance_dir, &mc_version, mod_loader)
+    let mut progress = ProgressHandler::new();
+
+    let mut instance = Instance::create(instance_dir, &mc_version, mod_loader, &mut progress)
         .await?;
 
+    if let Some(name) = name {
+        instance.rename(&name)?;
+    }
+
     Ok(())
 }
 
-async fn prompt_loader_version(mc_version: &str, loader: &ModLoaderName) -> Result<String> {
-    let client = AssetClient::new();
+/// Parse mod loader id `<name>[-<version>]`, prompting to select version when not specified
+pub async fn resolve_mod_loader(mc_version: &str, mod_loader_id: &str) -> Result<ModLoader> {
+    if let Ok(mod_loader) = mod_loader_id.parse() {
+        Ok(mod_loader)
+    } else {
+        let name = mod_loader_id.parse::<ModLoaderName>()?;
+        let version = prompt_loader_version(mc_version, &name).await?;
+        Ok(ModLoader { name, version })
+    }
+}
+
+pub async fn prompt_loader_version(mc_version: &str, loader: &ModLoaderName) -> Result<String> {
+    let client = AssetClient::new()?;
 
     // fetch loader versions for the version of minecraft
     let versions = client.get_loader_versions(mc_version, loader).await?;
 
+    if versions.is_empty() {
+        bail!("No {loader} versions found for Minecraft {mc_version}");
+    }
+
     // find the index with recommended flag set to `true`
     let recommend_index = versions.iter()
         .position(|v| v.recommended)
         .unwrap_or(0);
 
+    let prompt = format!("Select {} version (* recommended version)", loader);
+    super::require_terminal(&prompt, "Pass the mod loader version")?;
+
     let selection = Select::with_theme(&super::console_theme())
-        .with_prompt(format!("Select {} version (* recommended version)", loader.to_string()))
+        .with_prompt(prompt)
         .items(&versions)
         .default(recommend_index)
         .interact()?;
@@ -72,8 +92,8 @@ async fn prompt_loader_version(mc_version: &str, loader: &ModLoaderName) -> Resu
     Ok(versions[selection].sversion.to_owned())
 }
 
-async fn prompt_mc_version(snapshots: bool) -> Result<String> {
-    let client = AssetClient::new();
+pub async fn prompt_mc_version(snapshots: bool) -> Result<String> {
+    let client = AssetClient::new()?;
 
     let manifest = client.get_mc_version_manifest().await?;
 
@@ -81,6 +101,8 @@ async fn prompt_mc_version(snapshots: bool) -> Result<String> {
         .filter(|v| snapshots || v.release_type == "release")
         .collect();
 
+    super::require_terminal("Select Minecraft version", "Pass the Minecraft version")?;
+
     let selection = FuzzySelect::with_theme(&super::console_theme())
         .with_prompt("Select Minecraft version")
         .items(&versions)
@@ -0,0 +1,86 @@
+/*
+ * Steve Launcher - A Minecraft Launcher
+ * Copyright (C) 2023 Josh Kropf <josh@slashdev.ca>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use anyhow::Result;
+use indicatif::HumanBytes;
+use std::path::{Path, PathBuf};
+
+use steve::{env, Instance, InstanceRegistry, path_sizes};
+
+/// Number of modpack instances listed in largest modpacks
+const LARGEST_MODPACKS: usize = 5;
+
+pub fn print_disk_usage(root_dir: Option<&Path>) -> Result<()> {
+    let dirs = match root_dir {
+        Some(root_dir) => Instance::list_dirs(root_dir)?,
+        None => InstanceRegistry::load()?.instance_dirs().cloned().collect()
+    };
+
+    let mut instances = vec![];
+
+    for dir in dirs {
+        match Instance::load(&dir) {
+            Ok(instance) => instances.push(instance),
+            Err(e) => eprintln!("Unable to load instance '{}': {e}", dir.display())
+        }
+    }
+
+    let shared = [
+        ("assets", env::get_assets_dir()),
+        ("libraries", env::get_libs_dir()),
+        ("cache", env::get_cache_dir()),
+        ("runtimes", env::get_runtimes_dir())
+    ];
+
+    let mut paths: Vec<PathBuf> = instances.iter().map(|i| i.game_dir()).collect();
+    paths.extend(shared.iter().map(|(_, path)| path.clone()));
+
+    let sizes = path_sizes(&paths)?;
+    let (instance_sizes, shared_sizes) = sizes.split_at(instances.len());
+
+    outln!("Instances");
+    for (instance, size) in instances.iter().zip(instance_sizes) {
+        print_size(*size, &instance.dir.display().to_string());
+    }
+
+    let mut modpacks: Vec<_> = instances.iter().zip(instance_sizes)
+        .filter_map(|(i, size)| i.manifest.modpack.as_ref().map(|pack| (pack, &i.dir, *size)))
+        .collect();
+
+    if !modpacks.is_empty() {
+        modpacks.sort_by_key(|(_, _, size)| std::cmp::Reverse(*size));
+
+        outln!("\nLargest modpacks");
+        for (pack, dir, size) in modpacks.iter().take(LARGEST_MODPACKS) {
+            print_size(*size, &format!("{} {} ({})", pack.name, pack.version, dir.display()));
+        }
+    }
+
+    outln!("\nShared data in {}", env::get_data_dir().display());
+    for ((name, _), size) in shared.iter().zip(shared_sizes) {
+        print_size(*size, name);
+    }
+
+    outln!("\n{} total", HumanBytes(sizes.iter().sum()));
+
+    Ok(())
+}
+
+fn print_size(size: u64, name: &str) {
+    outln!("{:>10} {name}", HumanBytes(size).to_string());
+}
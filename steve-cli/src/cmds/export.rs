@@ -0,0 +1,98 @@
+/*
+ * Steve Launcher - A Minecraft Launcher
+ * Copyright (C) 2023 Josh Kropf <josh@slashdev.ca>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use anyhow::Result;
+use std::path::Path;
+
+use crate::ProgressHandler;
+use steve::{env, Instance};
+
+pub async fn modpack_zip_export(
+    instance_dir: &Path,
+    zip_file: &Path,
+    name: Option<String>,
+    version: &str,
+    author: Option<String>
+) -> Result<()> {
+    let mut progress = ProgressHandler::new();
+
+    let instance = Instance::load(instance_dir)?;
+
+    let name = name.unwrap_or_else(|| {
+        instance.dir.file_name().unwrap().to_string_lossy().to_string()
+    });
+
+    let author = author.unwrap_or_else(env::get_user_name);
+
+    let unmatched = instance.export_pack_zip(zip_file, &name, version, &author, &mut progress)
+        .await?;
+
+    if !unmatched.is_empty() {
+        outln!("Files below were not found on CurseForge and have been added as overrides");
+
+        for path in unmatched {
+            let path = path.strip_prefix(instance.game_dir()).unwrap_or(&path);
+            outln!("  {}", path.display());
+        }
+    }
+
+    Ok(())
+}
+
+pub async fn modpack_mrpack_export(
+    instance_dir: &Path,
+    mrpack_file: &Path,
+    name: Option<String>,
+    version: &str
+) -> Result<()> {
+    let mut progress = ProgressHandler::new();
+
+    let instance = Instance::load(instance_dir)?;
+
+    let name = name.unwrap_or_else(|| {
+        instance.dir.file_name().unwrap().to_string_lossy().to_string()
+    });
+
+    let unmatched = instance.export_pack_mrpack(mrpack_file, &name, version, &mut progress)
+        .await?;
+
+    if !unmatched.is_empty() {
+        outln!("Files below were not found on Modrinth and have been added as overrides");
+
+        for path in unmatched {
+            let path = path.strip_prefix(instance.game_dir()).unwrap_or(&path);
+            outln!("  {}", path.display());
+        }
+    }
+
+    Ok(())
+}
+
+pub fn prism_zip_export(
+    instance_dir: &Path,
+    zip_file: &Path,
+    name: Option<String>
+) -> Result<()> {
+    let instance = Instance::load(instance_dir)?;
+
+    let name = name.unwrap_or_else(|| {
+        instance.dir.file_name().unwrap().to_string_lossy().to_string()
+    });
+
+    instance.export_prism_zip(zip_file, &name)
+}
@@ -0,0 +1,58 @@
+/*
+ * Steve Launcher - A Minecraft Launcher
+ * Copyright (C) 2023 Josh Kropf <josh@slashdev.ca>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use anyhow::Result;
+use indicatif::HumanBytes;
+
+use crate::ProgressHandler;
+use steve::{env, InstanceRegistry};
+
+pub async fn collect_garbage(dry_run: bool, processor_output: bool) -> Result<()> {
+    let mut registry = InstanceRegistry::load()?;
+
+    // everything would be unreferenced, most likely instances are from before the registry
+    if registry.instance_dirs().next().is_none() {
+        outln!("No instances registered, instances are registered when created or launched");
+        return Ok(());
+    }
+
+    let mut progress = ProgressHandler::new();
+    let files = registry.collect_garbage(dry_run, processor_output, &mut progress).await?;
+
+    if files.is_empty() {
+        outln!("Nothing to collect");
+        return Ok(());
+    }
+
+    let data_dir = env::get_data_dir();
+
+    for (path, size) in &files {
+        let path = path.strip_prefix(&data_dir).unwrap_or(path);
+        outln!("{:>10} {}", HumanBytes(*size).to_string(), path.display());
+    }
+
+    let total: u64 = files.iter().map(|(_, size)| size).sum();
+
+    if dry_run {
+        outln!("{} would be freed", HumanBytes(total));
+    } else {
+        outln!("{} freed", HumanBytes(total));
+    }
+
+    Ok(())
+}
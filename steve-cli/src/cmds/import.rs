@@ -0,0 +1,62 @@
+/*
+ * Steve Launcher - A Minecraft Launcher
+ * Copyright (C) 2023 Josh Kropf <josh@slashdev.ca>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use anyhow::Result;
+use std::path::Path;
+
+use crate::ProgressHandler;
+use steve::{env, Instance, PrismInstance};
+use super::prompt_confirm;
+
+pub async fn prism_import(instance_dir: &Path, skip_confirm: bool) -> Result<()> {
+    let prism = PrismInstance::load(instance_dir)?;
+
+    let prompt = "Instance already exists, are you sure you want to replace the manifest?";
+    if Instance::exists(instance_dir) && !skip_confirm && !prompt_confirm(prompt, "Pass -y")? {
+        return Ok(());
+    }
+
+    let mut progress = ProgressHandler::new();
+
+    let instance = Instance::import_prism(&prism, &mut progress).await?;
+
+    outln!("Created instance for Minecraft {} in {}",
+        instance.manifest.mc_version,
+        instance.dir.display()
+    );
+
+    Ok(())
+}
+
+pub async fn vanilla_import(minecraft_dir: &Path) -> Result<()> {
+    let mut progress = ProgressHandler::new();
+
+    let instances_dir = env::get_instances_dir();
+
+    let results = Instance::import_vanilla(minecraft_dir, &instances_dir, &mut progress)
+        .await?;
+
+    for (name, result) in results {
+        match result {
+            Ok(instance) => outln!("Created instance '{name}' in {}", instance.dir.display()),
+            Err(e) => eprintln!("Skipped profile '{name}': {e}")
+        }
+    }
+
+    Ok(())
+}
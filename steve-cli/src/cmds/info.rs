@@ -0,0 +1,127 @@
+/*
+ * Steve Launcher - A Minecraft Launcher
+ * Copyright (C) 2023 Josh Kropf <josh@slashdev.ca>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use anyhow::Result;
+use chrono::Local;
+use indicatif::HumanBytes;
+use serde_json::json;
+use std::path::Path;
+
+use steve::{Instance, path_sizes};
+
+pub async fn print_instance_info(instance_dir: &Path) -> Result<()> {
+    let instance = Instance::load(instance_dir)?;
+    let manifest = &instance.manifest;
+
+    // an instance that was never launched might not have the game manifest
+    // needed to pick the java runtime, that shouldn't hide everything else
+    let java_path = instance.resolve_java_path().await
+        .map(|p| p.display().to_string());
+
+    let (mods, disabled_mods) = instance.count_mods()?;
+    let disk_usage = path_sizes(&[&instance.dir])?[0];
+
+    if crate::json_output() {
+        println!("{}", json!({
+            "dir": instance.dir,
+            "mc_version": manifest.mc_version,
+            "mod_loader": manifest.mod_loader.as_ref().map(|l| json!({
+                "name": l.name.to_string(),
+                "version": l.version
+            })),
+            "modpack": manifest.modpack,
+            "java_path": java_path.as_ref().ok(),
+            "java_args": manifest.java_args,
+            "min_memory": manifest.min_memory,
+            "max_memory": manifest.max_memory,
+            "mods": mods,
+            "disabled_mods": disabled_mods,
+            "disk_usage": disk_usage,
+            "account": manifest.account,
+            "last_played": manifest.last_played,
+            "play_time_seconds": manifest.play_time_seconds.unwrap_or(0)
+        }));
+
+        return Ok(());
+    }
+
+    outln!("        Directory: {}", instance.dir.display());
+    outln!("        Minecraft: {}", manifest.mc_version);
+
+    if let Some(loader) = &manifest.mod_loader {
+        outln!("       Mod loader: {} {}", loader.name, loader.version);
+    }
+
+    if let Some(pack) = &manifest.modpack {
+        outln!("          Modpack: {} {}", pack.name, pack.version);
+
+        match pack.id {
+            Some(id) => outln!("   Modpack source: {} {id}", pack.source),
+            None => outln!("   Modpack source: {}", pack.source)
+        }
+    }
+
+    match &java_path {
+        Ok(path) => outln!("             Java: {path}"),
+        Err(_) => outln!("             Java: unknown")
+    }
+
+    if let Some(args) = &manifest.java_args {
+        outln!("        Java args: {}", args.join(" "));
+    }
+
+    match (manifest.min_memory, manifest.max_memory) {
+        (Some(min), Some(max)) => outln!("           Memory: {min} - {max} MB"),
+        (None, Some(max)) => outln!("           Memory: up to {max} MB"),
+        (Some(min), None) => outln!("           Memory: at least {min} MB"),
+        (None, None) => outln!("           Memory: java default")
+    }
+
+    if disabled_mods > 0 {
+        outln!("             Mods: {mods} ({disabled_mods} disabled)");
+    } else {
+        outln!("             Mods: {mods}");
+    }
+
+    outln!("       Disk usage: {}", HumanBytes(disk_usage));
+
+    if let Some(account) = &manifest.account {
+        outln!("          Account: {account}");
+    }
+
+    match manifest.last_played {
+        Some(time) => outln!("      Last played: {}",
+            time.with_timezone(&Local).format("%Y-%m-%d %H:%M")),
+        None => outln!("      Last played: never")
+    }
+
+    outln!("  Total play time: {}", format_play_time(manifest.play_time_seconds.unwrap_or(0)));
+
+    Ok(())
+}
+
+fn format_play_time(seconds: u64) -> String {
+    let hours = seconds / 3600;
+    let minutes = (seconds % 3600) / 60;
+
+    if hours > 0 {
+        format!("{hours}h {minutes}m")
+    } else {
+        format!("{minutes}m")
+    }
+}
@@ -0,0 +1,73 @@
+/*
+ * Steve Launcher - A Minecraft Launcher
+ * Copyright (C) 2023 Josh Kropf <josh@slashdev.ca>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use anyhow::Result;
+use std::path::Path;
+
+use crate::ProgressHandler;
+use steve::{Instance, JavaRuntime};
+
+pub fn java_list() -> Result<()> {
+    let runtimes = JavaRuntime::list_installed()?;
+
+    if runtimes.is_empty() {
+        outln!("No Java runtimes installed");
+    }
+
+    for runtime in runtimes {
+        outln!("{} {} {}", runtime.component, runtime.version, runtime.dir.display());
+    }
+
+    Ok(())
+}
+
+pub async fn java_install(major_version: u32) -> Result<()> {
+    let runtime = install_runtime(major_version).await?;
+
+    outln!("Installed {} {}", runtime.component, runtime.version);
+
+    Ok(())
+}
+
+pub async fn java_pin(instance_dir: &Path, major_version: u32) -> Result<()> {
+    let mut instance = Instance::load(instance_dir)?;
+
+    let runtime = install_runtime(major_version).await?;
+
+    instance.set_java_runtime(Some(runtime.component.clone()))?;
+
+    outln!("Instance pinned to {} {}", runtime.component, runtime.version);
+
+    Ok(())
+}
+
+pub fn java_unpin(instance_dir: &Path) -> Result<()> {
+    let mut instance = Instance::load(instance_dir)?;
+
+    instance.set_java_runtime(None)?;
+
+    outln!("Instance uses the Java runtime for Minecraft {}", instance.manifest.mc_version);
+
+    Ok(())
+}
+
+async fn install_runtime(major_version: u32) -> Result<JavaRuntime> {
+    let mut progress = ProgressHandler::new();
+
+    JavaRuntime::install(major_version, &mut progress).await
+}
@@ -20,17 +20,44 @@ use anyhow::Result;
 use std::path::Path;
 
 use crate::ProgressHandler;
-use steve::Instance;
+use steve::{Account, Instance, LaunchOptions};
 
-pub async fn launch_instance(instance_dir: &Path, detach: bool) -> Result<()> {
+pub async fn launch_instance(
+    instance_dir: &Path,
+    offline_user: Option<&str>,
+    options: LaunchOptions
+) -> Result<()> {
     let mut progress = ProgressHandler::new();
 
-    let instance = Instance::load(instance_dir)?;
-    let mut result = instance.launch(&mut progress)
+    let mut instance = Instance::load(instance_dir)?;
+
+    let session = match offline_user {
+        Some(name) => Account::offline(name)?,
+        None => {
+            let account = instance.manifest.account.as_deref();
+            Account::load_with_tokens(account).await?.session().await?
+        }
+    };
+
+    let process = instance.launch(&session, &options, &mut progress)
         .await?;
 
-    if !detach {
-        result.wait()?;
+    if options.detach {
+        outln!("Game output is written to {}", process.log_path.display());
+    } else {
+        let exit = instance.wait_for_exit(process)?;
+
+        if exit.crashed() {
+            eprintln!("Game exited with {}", exit.status);
+
+            match exit.crash_report {
+                Some(path) => eprintln!("Crash report {}", path.display()),
+                None => eprintln!("Game output {}", exit.log_path.display())
+            }
+
+            // a crash report can be written by a game that still exits cleanly
+            std::process::exit(exit.status.code().filter(|code| *code != 0).unwrap_or(1));
+        }
     }
 
     Ok(())
@@ -0,0 +1,120 @@
+/*
+ * Steve Launcher - A Minecraft Launcher
+ * Copyright (C) 2023 Josh Kropf <josh@slashdev.ca>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use anyhow::Result;
+use chrono::Local;
+use dialoguer::FuzzySelect;
+use std::path::{Path, PathBuf};
+
+use steve::{Instance, InstanceRegistry};
+
+const HEADER: [&str; 5] = ["NAME", "MINECRAFT", "LOADER", "MODPACK", "LAST PLAYED"];
+
+pub fn list_instances(root_dir: &Path) -> Result<()> {
+    let instances = Instance::list_dirs(root_dir)?
+        .into_iter()
+        .map(|dir| (dir.file_name().unwrap().to_string_lossy().to_string(), dir));
+
+    print_instances(instances)
+}
+
+pub fn list_registered_instances() -> Result<()> {
+    let registry = InstanceRegistry::load()?;
+
+    let instances = registry.instances()
+        .map(|(name, dir)| (name.clone(), dir.clone()));
+
+    print_instances(instances)
+}
+
+/// Directory of the registered instance named `query`, or the only instance
+/// with a name containing `query`; prompts to select when several match
+pub fn select_registered_instance(registry: &InstanceRegistry, query: &str) -> Result<Option<PathBuf>> {
+    if let Some(dir) = registry.get(query) {
+        return Ok(Some(dir.clone()));
+    }
+
+    let matches = registry.search(query);
+
+    match matches.len() {
+        0 => Ok(None),
+        1 => Ok(Some(matches[0].1.clone())),
+        _ => {
+            super::require_terminal("Select instance", &format!("'{query}' matches several instances, pass the full name"))?;
+
+            let names: Vec<_> = matches.iter().map(|(name, _)| name).collect();
+
+            let selection = FuzzySelect::with_theme(&super::console_theme())
+                .with_prompt("Select instance")
+                .items(&names)
+                .interact()?;
+
+            Ok(Some(matches[selection].1.clone()))
+        }
+    }
+}
+
+fn print_instances(instances: impl Iterator<Item = (String, PathBuf)>) -> Result<()> {
+    let mut rows = vec![HEADER.map(String::from)];
+
+    for (name, dir) in instances {
+        match Instance::load(&dir) {
+            Ok(instance) => rows.push(format_row(name, &instance)),
+            Err(e) => eprintln!("Unable to load instance '{name}': {e}")
+        }
+    }
+
+    let mut widths = [0; HEADER.len()];
+    for row in &rows {
+        for (width, col) in widths.iter_mut().zip(row) {
+            *width = (*width).max(col.len());
+        }
+    }
+
+    for row in rows {
+        let line: Vec<_> = row.iter().zip(widths)
+            .map(|(col, width)| format!("{col:width$}"))
+            .collect();
+
+        outln!("{}", line.join("  ").trim_end());
+    }
+
+    Ok(())
+}
+
+fn format_row(name: String, instance: &Instance) -> [String; HEADER.len()] {
+    let manifest = &instance.manifest;
+
+    let loader = manifest.mod_loader.as_ref()
+        .map_or(String::from("-"), |l| l.to_string());
+
+    let modpack = match &manifest.modpack {
+        Some(pack) => match pack.id {
+            Some(id) => format!("{} {} ({}:{id})", pack.name, pack.version, pack.source),
+            None => format!("{} {}", pack.name, pack.version)
+        },
+        None => String::from("-")
+    };
+
+    let last_played = manifest.last_played
+        .map_or(String::from("never"), |t| {
+            t.with_timezone(&Local).format("%Y-%m-%d %H:%M").to_string()
+        });
+
+    [name, manifest.mc_version.clone(), loader, modpack, last_played]
+}
@@ -0,0 +1,61 @@
+/*
+ * Steve Launcher - A Minecraft Launcher
+ * Copyright (C) 2023 Josh Kropf <josh@slashdev.ca>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use anyhow::{bail, Result};
+use std::path::Path;
+
+use crate::ProgressHandler;
+use steve::{Instance, ModLoader};
+use super::create::{prompt_loader_version, resolve_mod_loader};
+
+pub async fn loader_upgrade(instance_dir: &Path, version: Option<String>) -> Result<()> {
+    let instance = Instance::load(instance_dir)?;
+
+    let name = match &instance.manifest.mod_loader {
+        Some(mod_loader) => mod_loader.name.clone(),
+        None => bail!("Instance does not have a mod loader, use `loader switch` to add one")
+    };
+
+    let version = match version {
+        Some(v) => v,
+        None => prompt_loader_version(&instance.manifest.mc_version, &name).await?
+    };
+
+    change_loader(instance, ModLoader { name, version }).await
+}
+
+pub async fn loader_switch(instance_dir: &Path, loader: &str) -> Result<()> {
+    let instance = Instance::load(instance_dir)?;
+
+    let mod_loader = resolve_mod_loader(&instance.manifest.mc_version, loader).await?;
+
+    change_loader(instance, mod_loader).await
+}
+
+async fn change_loader(mut instance: Instance, mod_loader: ModLoader) -> Result<()> {
+    let mut progress = ProgressHandler::new();
+
+    let loader_id = mod_loader.to_string();
+
+    instance.change_mod_loader(mod_loader, &mut progress)
+        .await?;
+
+    outln!("Instance mod loader changed to {loader_id}");
+
+    Ok(())
+}
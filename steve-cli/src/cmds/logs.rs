@@ -0,0 +1,62 @@
+/*
+ * Steve Launcher - A Minecraft Launcher
+ * Copyright (C) 2023 Josh Kropf <josh@slashdev.ca>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use anyhow::Result;
+use std::{fs::File, io::{BufRead, BufReader}, path::Path, thread, time::Duration};
+
+use steve::{Instance, LogParser};
+
+pub fn print_log(instance_dir: &Path, follow: bool) -> Result<()> {
+    let instance = Instance::load(instance_dir)?;
+
+    let log_path = match instance.latest_log()? {
+        Some(path) => path,
+        None => {
+            outln!("No session logs in {}", instance.logs_dir().display());
+            return Ok(());
+        }
+    };
+
+    let mut reader = BufReader::new(File::open(log_path)?);
+    let mut parser = LogParser::default();
+    let mut line = String::new();
+
+    loop {
+        let at_end = reader.read_line(&mut line)? == 0;
+
+        // keep partial line written by the game until the rest arrives
+        if follow && !line.ends_with('\n') {
+            if at_end {
+                thread::sleep(Duration::from_millis(500));
+            }
+            continue;
+        }
+
+        if at_end && line.is_empty() {
+            break;
+        }
+
+        if let Some(output) = parser.parse_line(line.trim_end_matches(['\r', '\n'])) {
+            outln!("{output}");
+        }
+
+        line.clear();
+    }
+
+    Ok(())
+}
@@ -0,0 +1,48 @@
+/*
+ * Steve Launcher - A Minecraft Launcher
+ * Copyright (C) 2023 Josh Kropf <josh@slashdev.ca>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use anyhow::Result;
+use std::path::Path;
+
+use steve::Instance;
+use super::prompt_confirm;
+
+pub fn clone_instance(instance_dir: &Path, dest_dir: &Path) -> Result<()> {
+    let instance = Instance::load(instance_dir)?;
+
+    let clone = instance.clone_to(dest_dir)?;
+
+    outln!("Instance cloned to {}", clone.dir.display());
+
+    Ok(())
+}
+
+pub fn delete_instance(instance_dir: &Path, skip_confirm: bool) -> Result<()> {
+    let instance = Instance::load(instance_dir)?;
+
+    let prompt = format!("Delete {} and all of its worlds?", instance.dir.display());
+    if !skip_confirm && !prompt_confirm(prompt, "Pass -y")? {
+        return Ok(());
+    }
+
+    instance.delete()?;
+
+    outln!("Instance deleted");
+
+    Ok(())
+}
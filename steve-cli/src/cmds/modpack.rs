@@ -16,31 +16,117 @@
  * along with this program.  If not, see <https://www.gnu.org/licenses/>.
  */
 
-use anyhow::Result;
+use anyhow::{bail, Result};
 use console::Term;
 use dialoguer::{MultiSelect, Select};
+use serde_json::json;
 use std::{
-    env, fs, io::Result as IoResult, path::Path, process::{Command, Stdio},
+    collections::HashMap, env, fs, io::Result as IoResult, path::{Path, PathBuf},
     sync::{Arc, atomic::{AtomicBool, Ordering}, mpsc::{self, Sender}},
     thread::{self, Scope}
 };
 
 use crate::ProgressHandler;
 use steve::{
-    AssetClient, CurseForgeZip, DownloadWatcher, FileDownload, Instance,
-    ModpackManifest, ModpackVersion, Progress, WatcherMessage
+    AssetClient, ConfigConflicts, CurseForgeSort, CurseForgeZip, DownloadWatcher, Error, FileDownload, InstallHandler,
+    Instance, InstanceModpack, ModLoader, ModpackManifest, ModpackVersion, PackChanges, Progress,
+    WatcherMessage, open_url
 };
-use super::{console_theme, prompt_confirm};
+use super::{console_theme, prompt_confirm, require_terminal};
+
+/// Most results returned by a modpacks.ch search
+const MODPACKS_CH_SEARCH_LIMIT: usize = 50;
+
+pub struct PackSearch {
+    pub term: String,
+    pub limit: usize,
+    /// Search CurseForge directly instead of modpacks.ch
+    pub curseforge: Option<CurseForgeSearch>
+}
+
+pub struct CurseForgeSearch {
+    pub sort: CurseForgeSort,
+    /// Category name or slug
+    pub category: Option<String>
+}
 
 pub async fn modpack_search_and_install(
     instance_dir: &Path,
-    search: &str,
-    limit: u8
+    search: &PackSearch,
+    dry_run: bool,
+    blocked_out: Option<&Path>,
+    skip_confirm: bool
 ) -> Result<()> {
     let mut progress = ProgressHandler::new();
-    let client = AssetClient::new();
+    let client = AssetClient::new()?;
+
+    let selected_pack = match &search.curseforge {
+        Some(curseforge) => select_curseforge_modpack(&client, search, curseforge).await?,
+        None => select_modpack(&client, search, &mut progress).await?
+    };
+
+    require_terminal("Select modpack version", "Selecting a modpack needs a terminal, run without --json")?;
+
+    let selection = Select::with_theme(&console_theme())
+        .with_prompt("Select modpack version")
+        .items(&format_modpack_versions(selected_pack.versions.iter()))
+        .default(0)
+        .interact()?;
+
+    let selected_version = &selected_pack.versions[selection];
+
+    let pack = if selected_pack.provider == "curseforge" {
+        client.get_curse_modpack(selected_pack.pack_id, selected_version.version_id).await?
+    } else {
+        client.get_ftb_modpack(selected_pack.pack_id, selected_version.version_id).await?
+    };
+
+    if dry_run {
+        let mc_version = pack.get_minecraft_version()?;
+        let mod_loader = pack.get_mod_loader()?;
+
+        if !Instance::exists(instance_dir) {
+            print_new_instance(instance_dir, &mc_version, mod_loader);
+            return Ok(());
+        }
+
+        print_changelog(&client, &selected_pack, selected_version).await;
+
+        let instance = Instance::load(instance_dir)?;
+        let changes = instance.pack_changes(&pack).await?;
 
-    let results = client.search_modpacks(search, limit).await?;
+        print_pack_changes(&instance, &mc_version, mod_loader, &changes);
+
+        return Ok(());
+    }
+
+    if Instance::exists(instance_dir) {
+        print_changelog(&client, &selected_pack, selected_version).await;
+    }
+
+    let modpack = InstanceModpack {
+        source: selected_pack.provider.clone(),
+        id: Some(selected_pack.pack_id),
+        name: selected_pack.name.clone(),
+        version: selected_version.name.clone()
+    };
+
+    let mut handler = PromptInstallHandler { blocked_out, skip_confirm, blocked: None };
+
+    Instance::install_modpack(instance_dir, &pack, modpack, &mut handler, &mut progress)
+        .await?;
+
+    handler.finish()
+}
+
+/// Search FTB and CurseForge modpacks through modpacks.ch
+async fn select_modpack(
+    client: &AssetClient,
+    search: &PackSearch,
+    progress: &mut dyn Progress
+) -> Result<ModpackManifest> {
+    let limit = search.limit.min(MODPACKS_CH_SEARCH_LIMIT) as u8;
+    let results = client.search_modpacks(&search.term, limit).await?;
 
     let mut search_results = Vec::new();
 
@@ -67,117 +153,202 @@ pub async fn modpack_search_and_install(
 
     progress.end();
 
-    let selection = Select::with_theme(&console_theme())
-        .items(&format_modpack_results(search_results.iter()))
-        .default(0)
-        .interact()?;
+    if search_results.is_empty() {
+        bail!("No modpacks found matching '{}'", search.term);
+    }
 
-    let selected_pack = &search_results[selection];
+    require_terminal("Select modpack", "Selecting a modpack needs a terminal, run without --json")?;
 
     let selection = Select::with_theme(&console_theme())
-        .with_prompt("Select modpack version")
-        .items(&format_modpack_versions(selected_pack.versions.iter()))
+        .items(&format_modpack_results(search_results.iter()))
         .default(0)
         .interact()?;
 
-    let selected_version = &selected_pack.versions[selection];
+    Ok(search_results.swap_remove(selection))
+}
 
-    let pack = if selected_pack.provider == "curseforge" {
-        client.get_curse_modpack(selected_pack.pack_id, selected_version.version_id).await?
-    } else {
-        client.get_ftb_modpack(selected_pack.pack_id, selected_version.version_id).await?
+/// Search CurseForge modpacks, fetching versions of the selected pack only
+/// since the results can run to hundreds of packs
+async fn select_curseforge_modpack(
+    client: &AssetClient,
+    search: &PackSearch,
+    curseforge: &CurseForgeSearch
+) -> Result<ModpackManifest> {
+    let category_id = match &curseforge.category {
+        Some(category) => {
+            let categories = client.get_curseforge_modpack_categories().await?;
+
+            match categories.iter().find(|c| c.slug == *category || c.name.eq_ignore_ascii_case(category)) {
+                Some(c) => Some(c.id),
+                None => {
+                    let mut names: Vec<_> = categories.iter()
+                        .map(|c| c.slug.as_str())
+                        .collect();
+                    names.sort();
+                    bail!("Unknown category '{category}', expected one of: {}", names.join(", "));
+                }
+            }
+        },
+        None => None
     };
 
-    let instance = if Instance::exists(instance_dir) {
-        if !prompt_confirm("Instance already exists, are you sure you want to install the pack here?")? {
-            return Ok(())
-        }
-
-        let mut instance = Instance::load(instance_dir)?;
+    let results = client.search_curseforge_modpacks(
+        &search.term, curseforge.sort, category_id, search.limit
+    ).await?;
 
-        instance.set_mc_version(pack.get_minecraft_version()?)?;
-        instance.set_mod_loader(pack.get_mod_loader()?)?;
-
-        instance
-    } else {
-        Instance::create(
-            instance_dir,
-            &pack.get_minecraft_version()?,
-            pack.get_mod_loader()?
-        ).await?
-    };
+    if results.is_empty() {
+        bail!("No modpacks found matching '{}'", search.term);
+    }
 
-    let (remove, downloads) = instance.install_pack(&pack, &mut progress)
-        .await?;
+    let items: Vec<_> = results.iter()
+        .map(|m| format!("{}\n  {}", m.name, m.summary))
+        .collect();
 
-    if let Some(downloads) = downloads {
-        download_blocked(instance, downloads)?;
-    }
+    require_terminal("Select modpack", "Selecting a modpack needs a terminal, run without --json")?;
 
-    if !remove.is_empty() {
-        remove_files_prompt(&remove)?;
-    }
+    let selection = Select::with_theme(&console_theme())
+        .items(&items)
+        .default(0)
+        .max_length(10)
+        .interact()?;
 
-    Ok(())
+    client.get_curse_modpack_versions(results[selection].mod_id as u32).await
 }
 
 pub async fn modpack_zip_install(
     instance_dir: &Path,
-    zip_file: &Path
+    zip_file: &Path,
+    dry_run: bool,
+    blocked_out: Option<&Path>,
+    skip_confirm: bool
 ) -> Result<()> {
     let mut progress = ProgressHandler::new();
 
     let pack = CurseForgeZip::load_zip(zip_file)?;
 
-    let instance = if Instance::exists(instance_dir) {
-        if !prompt_confirm("Instance already exists, are you sure you want to install the pack here?")? {
-            return Ok(())
+    if dry_run {
+        let mc_version = &pack.manifest.minecraft.version;
+        let mod_loader = pack.manifest.minecraft.get_mod_loader()?;
+
+        if !Instance::exists(instance_dir) {
+            print_new_instance(instance_dir, mc_version, mod_loader);
+            return Ok(());
         }
 
-        let mut instance = Instance::load(instance_dir)?;
+        let instance = Instance::load(instance_dir)?;
+        let changes = instance.pack_zip_changes(&pack).await?;
 
-        instance.set_mc_version(pack.manifest.minecraft.version.clone())?;
-        instance.set_mod_loader(pack.manifest.minecraft.get_mod_loader()?)?;
+        print_pack_changes(&instance, mc_version, mod_loader, &changes);
 
-        instance
-    } else {
-        Instance::create(
-            instance_dir,
-            &pack.manifest.minecraft.version,
-            pack.manifest.minecraft.get_mod_loader()?
-        ).await?
-    };
+        return Ok(());
+    }
+
+    let mut handler = PromptInstallHandler { blocked_out, skip_confirm, blocked: None };
 
-    let (remove, downloads) = instance.install_pack_zip(&pack, &mut progress)
+    Instance::install_modpack_zip(instance_dir, &pack, &mut handler, &mut progress)
         .await?;
 
-    if let Some(downloads) = downloads {
-        download_blocked(instance, downloads)?;
+    handler.finish()
+}
+
+/// Install decisions prompted on the console
+struct PromptInstallHandler<'a> {
+    blocked_out: Option<&'a Path>,
+    /// Answer yes to every prompt
+    skip_confirm: bool,
+    /// Manual downloads left for the user, reported once the rest of the
+    /// install is done
+    blocked: Option<anyhow::Error>
+}
+
+impl PromptInstallHandler<'_> {
+    fn finish(self) -> Result<()> {
+        match self.blocked {
+            Some(e) => Err(e),
+            None => Ok(())
+        }
     }
+}
 
-    if !remove.is_empty() {
-        remove_files_prompt(&remove)?;
+impl InstallHandler for PromptInstallHandler<'_> {
+    fn confirm_existing_instance(&mut self, _instance: &Instance) -> Result<bool> {
+        let prompt = "Instance already exists, are you sure you want to install the pack here?";
+        Ok(self.skip_confirm || prompt_confirm(prompt, "Pass -y")?)
     }
 
-    Ok(())
+    fn keep_user_files(&mut self, instance: &Instance, files: &[PathBuf]) -> Result<bool> {
+        keep_user_files_prompt(instance, files, self.skip_confirm)
+    }
+
+    fn download_blocked(&mut self, instance: &Instance, downloads: Vec<FileDownload>) -> Result<()> {
+        match download_blocked(instance, downloads, self.blocked_out) {
+            Err(e) if matches!(e.downcast_ref(), Some(Error::BlockedDownloads(_))) => {
+                self.blocked = Some(e);
+                Ok(())
+            },
+            result => result
+        }
+    }
+
+    fn remove_files(&mut self, _instance: &Instance, files: &[PathBuf]) -> Result<Vec<PathBuf>> {
+        match self.skip_confirm {
+            true => Ok(files.to_vec()),
+            false => remove_files_prompt(files)
+        }
+    }
+
+    fn config_conflicts(&mut self, instance: &Instance, files: &[PathBuf]) {
+        print_config_conflicts(instance, files);
+    }
 }
 
-fn download_blocked(instance: Instance, downloads: Vec<FileDownload>) -> Result<()> {
+pub fn download_blocked(
+    instance: &Instance,
+    downloads: Vec<FileDownload>,
+    blocked_out: Option<&Path>
+) -> Result<()> {
+    let files: HashMap<_, _> = downloads.iter()
+        .map(|f| (f.file_name.clone(), f.clone()))
+        .collect();
+
+    // a file with the same name that doesn't match is still being written,
+    // or is an older copy; keep waiting for the right file
+    let check = Box::new(move |file_name: &str, path: &Path| {
+        files.get(file_name)
+            .is_some_and(|f| f.is_valid_file(path).unwrap_or(false))
+    });
+
     let watcher = DownloadWatcher::new(
         downloads.iter()
-            .map(|f| f.file_name.as_str())
+            .map(|f| f.file_name.as_str()),
+        check
     );
 
+    let mut failed = vec![];
+
     // copy any downloads already in watch dir
     for f in &downloads {
-        if watcher.is_file_complete(&f.file_name) {
-            let file_path = watcher.watch_dir.join(&f.file_name);
-            instance.install_file(f, &file_path)?;
+        if let Some(file_path) = watcher.file_path(&f.file_name) {
+            if let Err(e) = instance.install_file(f, &file_path) {
+                failed.push(e.to_string());
+            }
         }
     }
 
     if watcher.is_all_complete() {
-        return Ok(());
+        return check_installed(failed);
+    }
+
+    if blocked_out.is_some() || crate::json_output() || !Term::stdout().is_term() {
+        for e in &failed {
+            eprintln!("{e}");
+        }
+
+        let remaining: Vec<_> = downloads.iter()
+            .filter(|f| !watcher.is_file_complete(&f.file_name))
+            .collect();
+
+        return print_blocked(instance, &remaining, blocked_out);
     }
 
     let term = Term::stdout();
@@ -200,7 +371,10 @@ fn download_blocked(instance: Instance, downloads: Vec<FileDownload>) -> Result<
                     let file = downloads.iter()
                         .find(|d| d.file_name == file_name)
                         .unwrap();
-                    instance.install_file(file, &file_path)?;
+                    // don't leave the scope early, the watch threads must be cancelled
+                    if let Err(e) = instance.install_file(file, &file_path) {
+                        failed.push(e.to_string());
+                    }
                     print_download_state(&term, &watcher, &downloads)?;
                 },
                 WatcherMessage::AllComplete => {
@@ -238,7 +412,46 @@ fn download_blocked(instance: Instance, downloads: Vec<FileDownload>) -> Result<
         Ok(())
     })?;
 
-    Ok(())
+    check_installed(failed)
+}
+
+/// List urls of files that must be downloaded manually, for scripts to pick
+/// up, failing with `Error::BlockedDownloads`
+fn print_blocked(instance: &Instance, downloads: &[&FileDownload], blocked_out: Option<&Path>) -> Result<()> {
+    let downloads_dir = steve::env::get_downloads_dirs()
+        .into_iter()
+        .next()
+        .unwrap_or_default();
+
+    eprintln!("Files below must be downloaded manually to {}, then run `steve mods sync {}`",
+        downloads_dir.display(), instance.dir.display());
+
+    let urls: Vec<_> = downloads.iter()
+        .map(|f| f.url.as_str())
+        .collect();
+
+    if crate::json_output() {
+        crate::print_json_event(json!({ "event": "blocked", "urls": urls }));
+    } else {
+        for url in &urls {
+            println!("{url}");
+        }
+    }
+
+    if let Some(path) = blocked_out {
+        fs::write(path, urls.join("\n") + "\n")?;
+        eprintln!("Download urls written to {}", path.display());
+    }
+
+    bail!(Error::BlockedDownloads(downloads.len()))
+}
+
+fn check_installed(failed: Vec<String>) -> Result<()> {
+    if failed.is_empty() {
+        Ok(())
+    } else {
+        bail!(failed.join("\n"))
+    }
 }
 
 fn print_download_state(term: &Term, watcher: &DownloadWatcher, downloads: &Vec<FileDownload>) -> IoResult<()> {
@@ -261,11 +474,7 @@ fn open_urls<'a, T>(urls: T) -> IoResult<()>
     where T: Iterator<Item = &'a str>
 {
     for u in urls {
-        Command::new("xdg-open")
-            .arg(u)
-            .stdout(Stdio::null())
-            .stderr(Stdio::null())
-            .spawn()?;
+        open_url(u)?;
     }
 
     Ok(())
@@ -289,6 +498,78 @@ fn readkey_thread<'scope>(scope: &'scope Scope<'scope, '_>, term: Term, tx: Send
     exit_thread
 }
 
+fn print_new_instance(instance_dir: &Path, mc_version: &str, mod_loader: Option<ModLoader>) {
+    match mod_loader {
+        Some(loader) => outln!("Would create instance {} for Minecraft {mc_version} with {loader}",
+            instance_dir.display()),
+        None => outln!("Would create instance {} for Minecraft {mc_version}",
+            instance_dir.display())
+    }
+}
+
+fn print_pack_changes(
+    instance: &Instance,
+    mc_version: &str,
+    mod_loader: Option<ModLoader>,
+    changes: &PackChanges
+) {
+    if instance.manifest.mc_version != mc_version {
+        outln!("Minecraft {} -> {mc_version}", instance.manifest.mc_version);
+    }
+
+    let current_loader = instance.manifest.mod_loader.as_ref().map(|l| l.to_string());
+    let mod_loader = mod_loader.map(|l| l.to_string());
+
+    if current_loader != mod_loader {
+        outln!("Mod loader {} -> {}",
+            current_loader.as_deref().unwrap_or("none"),
+            mod_loader.as_deref().unwrap_or("none"));
+    }
+
+    let game_dir = instance.game_dir();
+
+    let print_files = |heading: &str, files: &[PathBuf]| {
+        if files.is_empty() {
+            return;
+        }
+
+        outln!("{heading} ({})", files.len());
+        for path in files {
+            let path = path.strip_prefix(&game_dir).unwrap_or(path);
+            outln!("  {}", path.display());
+        }
+    };
+
+    print_files("Files added", &changes.added);
+    print_files("Files replaced", &changes.replaced);
+    print_files("Files removed", &changes.removed);
+
+    if changes.added.is_empty() && changes.replaced.is_empty() && changes.removed.is_empty() {
+        outln!("No files would change");
+    }
+}
+
+/// Print the changelog of the selected version, a missing changelog shouldn't
+/// get in the way of installing the pack so errors are only reported
+async fn print_changelog(client: &AssetClient, pack: &ModpackManifest, version: &ModpackVersion) {
+    let changelog = if pack.provider == "curseforge" {
+        client.get_curseforge_changelog(pack.pack_id, version.version_id).await
+    } else {
+        client.get_ftb_modpack_changelog(pack.pack_id, version.version_id).await
+    };
+
+    match changelog {
+        Ok(changelog) if !changelog.trim().is_empty() => {
+            outln!("Changelog for {} {}", pack.name, version.name);
+            for line in changelog.lines() {
+                outln!("  {line}");
+            }
+        },
+        Ok(_) => outln!("No changelog for {} {}", pack.name, version.name),
+        Err(e) => eprintln!("Unable to fetch changelog: {e}")
+    }
+}
+
 fn format_modpack_results<'a, I>(items: I) -> Vec<String>
     where I: Iterator<Item = &'a ModpackManifest>
 {
@@ -305,17 +586,49 @@ fn format_modpack_versions<'a, I>(items: I) -> Vec<String>
     items.map(|v| v.name.clone()).collect()
 }
 
-fn remove_files_prompt<P>(files: &[P]) -> Result<()>
-    where P: AsRef<Path>
-{
+fn print_config_conflicts(instance: &Instance, files: &[PathBuf]) {
+    if files.is_empty() {
+        return;
+    }
+
+    // the install already failed if the variable was invalid
+    match steve::env::get_config_conflicts().unwrap_or_default() {
+        ConfigConflicts::Keep => outln!("Config files below were changed since the pack was installed, keeping your changes"),
+        ConfigConflicts::Overwrite => outln!("Config files below were changed since the pack was installed, replaced with the pack files"),
+        ConfigConflicts::Backup => outln!("Config files below were changed since the pack was installed, your changes are saved with a .bak suffix")
+    }
+
+    let game_dir = instance.game_dir();
+    for path in files {
+        let path = path.strip_prefix(&game_dir).unwrap_or(path);
+        outln!("  {}", path.display());
+    }
+}
+
+fn keep_user_files_prompt(instance: &Instance, files: &[PathBuf], skip_confirm: bool) -> Result<bool> {
+    outln!("Files below were added manually and are now part of the pack");
+
+    let game_dir = instance.game_dir();
+    for path in files {
+        let path = path.strip_prefix(&game_dir).unwrap_or(path);
+        outln!("  {}", path.display());
+    }
+
+    let prompt = "Keep these files when a later version of the pack removes them?";
+    Ok(skip_confirm || prompt_confirm(prompt, "Pass -y")?)
+}
+
+fn remove_files_prompt(files: &[PathBuf]) -> Result<Vec<PathBuf>> {
     let prompt = "Found the following extra files after pack install. \
 These should be removed, unless you added them manually. \
 Toggle files for removal and press enter to continue.";
 
+    require_terminal(prompt, "Pass -y to remove the files")?;
+
     let current_dir = env::current_dir()?;
 
     let options: Vec<_> = files.iter()
-        .map(|p| p.as_ref().strip_prefix(&current_dir).unwrap().to_string_lossy())
+        .map(|p| p.strip_prefix(&current_dir).unwrap().to_string_lossy())
         .collect();
 
     let select = MultiSelect::with_theme(&console_theme())
@@ -323,12 +636,7 @@ Toggle files for removal and press enter to continue.";
         .items(&options)
         .defaults(&vec![true; files.len()])
         .report(false)
-        .interact()
-        .unwrap();
-
-    for i in select {
-        fs::remove_file(files[i].as_ref())?;
-    }
+        .interact()?;
 
-    Ok(())
+    Ok(select.into_iter().map(|i| files[i].clone()).collect())
 }
@@ -0,0 +1,95 @@
+/*
+ * Steve Launcher - A Minecraft Launcher
+ * Copyright (C) 2023 Josh Kropf <josh@slashdev.ca>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use anyhow::Result;
+use std::path::Path;
+
+use crate::ProgressHandler;
+use steve::Instance;
+use super::{modpack::download_blocked, prompt_confirm};
+
+pub async fn mods_remove(instance_dir: &Path, name: &str, skip_confirm: bool) -> Result<()> {
+    let mut progress = ProgressHandler::new();
+
+    let instance = Instance::load(instance_dir)?;
+
+    let mod_file = instance.find_mod_file(name)?;
+    let file_name = mod_file.file_name().unwrap().to_string_lossy().to_string();
+
+    let dependents = instance.find_mod_dependents(&mod_file, &mut progress)
+        .await?;
+
+    if !dependents.is_empty() {
+        outln!("The following mods require {file_name}:");
+        for path in &dependents {
+            outln!("  {}", path.file_name().unwrap().to_string_lossy());
+        }
+    }
+
+    let prompt = format!("Remove {file_name}?");
+    if !skip_confirm && !prompt_confirm(prompt, "Pass -y")? {
+        return Ok(());
+    }
+
+    instance.remove_mod(&mod_file)?;
+
+    outln!("Removed {file_name}");
+
+    Ok(())
+}
+
+pub fn mods_disable(instance_dir: &Path, name: &str) -> Result<()> {
+    let instance = Instance::load(instance_dir)?;
+
+    let mod_file = instance.find_mod_file(name)?;
+    let disabled_file = instance.disable_mod(&mod_file)?;
+
+    outln!("Disabled {}", disabled_file.file_name().unwrap().to_string_lossy());
+
+    Ok(())
+}
+
+pub fn mods_enable(instance_dir: &Path, name: &str) -> Result<()> {
+    let instance = Instance::load(instance_dir)?;
+
+    let mod_file = instance.find_mod_file(name)?;
+    let enabled_file = instance.enable_mod(&mod_file)?;
+
+    outln!("Enabled {}", enabled_file.file_name().unwrap().to_string_lossy());
+
+    Ok(())
+}
+
+pub async fn mods_sync(instance_dir: &Path, blocked_out: Option<&Path>) -> Result<()> {
+    let mut progress = ProgressHandler::new();
+
+    let instance = Instance::load(instance_dir)?;
+
+    let (count, downloads) = instance.sync_mods(&mut progress)
+        .await?;
+
+    // files left to download manually don't undo the files downloaded
+    let result = match downloads {
+        Some(downloads) => download_blocked(&instance, downloads, blocked_out),
+        None => Ok(())
+    };
+
+    outln!("Downloaded {count} missing files");
+
+    result
+}
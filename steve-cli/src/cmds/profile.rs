@@ -0,0 +1,66 @@
+/*
+ * Steve Launcher - A Minecraft Launcher
+ * Copyright (C) 2023 Josh Kropf <josh@slashdev.ca>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use anyhow::Result;
+use serde_json::json;
+
+use steve::{dashed_uuid, MojangApiClient};
+
+pub async fn print_player_profile(name_or_uuid: &str) -> Result<()> {
+    let client = MojangApiClient::new()?;
+
+    let profile = client.get_profile(name_or_uuid).await?;
+
+    if crate::json_output() {
+        println!("{}", match profile {
+            Some(profile) => json!({
+                "uuid": dashed_uuid(&profile.uuid),
+                "name": profile.name,
+                "skin_url": profile.skin_url,
+                "slim_skin": profile.slim_skin,
+                "cape_url": profile.cape_url
+            }),
+            None => json!(null)
+        });
+
+        return Ok(());
+    }
+
+    let profile = match profile {
+        Some(profile) => profile,
+        None => {
+            outln!("No player found for '{name_or_uuid}', the name may be available");
+            return Ok(());
+        }
+    };
+
+    outln!("  Name: {}", profile.name);
+    outln!("  UUID: {}", dashed_uuid(&profile.uuid));
+
+    match &profile.skin_url {
+        Some(url) if profile.slim_skin => outln!("  Skin: {url} (slim)"),
+        Some(url) => outln!("  Skin: {url}"),
+        None => outln!("  Skin: default")
+    }
+
+    if let Some(url) = &profile.cape_url {
+        outln!("  Cape: {url}");
+    }
+
+    Ok(())
+}
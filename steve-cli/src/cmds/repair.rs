@@ -0,0 +1,46 @@
+/*
+ * Steve Launcher - A Minecraft Launcher
+ * Copyright (C) 2023 Josh Kropf <josh@slashdev.ca>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use anyhow::Result;
+use std::path::Path;
+
+use crate::ProgressHandler;
+use steve::{env, Instance};
+
+pub async fn repair_instance(instance_dir: &Path) -> Result<()> {
+    let instance = Instance::load(instance_dir)?;
+
+    let mut progress = ProgressHandler::new();
+    let corrupted = instance.repair(&mut progress).await?;
+
+    if corrupted.is_empty() {
+        outln!("No corrupted files found");
+        return Ok(());
+    }
+
+    let data_dir = env::get_data_dir();
+
+    for path in &corrupted {
+        let path = path.strip_prefix(&data_dir).unwrap_or(path);
+        outln!("Replaced {}", path.display());
+    }
+
+    outln!("{} corrupted files replaced", corrupted.len());
+
+    Ok(())
+}
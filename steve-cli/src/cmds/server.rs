@@ -0,0 +1,146 @@
+/*
+ * Steve Launcher - A Minecraft Launcher
+ * Copyright (C) 2023 Josh Kropf <josh@slashdev.ca>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use anyhow::{bail, Result};
+use serde_json::json;
+use std::path::Path;
+
+use steve::{
+    MojangApiClient, PlayerProfile, RconClient, ServerPlayers, ping_server, server_dir_address
+};
+
+pub fn server_command(server_dir: &Path, command: &[String]) -> Result<()> {
+    let mut client = RconClient::connect_server_dir(server_dir)?;
+
+    let output = client.command(&command.join(" "))?;
+
+    if !output.is_empty() {
+        outln!("{output}");
+    }
+
+    Ok(())
+}
+
+/// Print status of server at `target`, either a server directory or address
+pub fn server_status(target: &str) -> Result<()> {
+    let target_dir = Path::new(target);
+
+    let address = if target_dir.is_dir() {
+        server_dir_address(target_dir)?
+    } else {
+        target.to_string()
+    };
+
+    let status = ping_server(&address)?;
+
+    if crate::json_output() {
+        println!("{}", json!({
+            "address": address,
+            "version": status.version,
+            "protocol": status.protocol,
+            "motd": status.motd,
+            "players_online": status.players_online,
+            "players_max": status.players_max,
+            "players": status.players,
+            "latency_ms": status.latency.as_millis() as u64
+        }));
+
+        return Ok(());
+    }
+
+    outln!("   Server: {address}");
+    outln!("  Version: {}", status.version);
+
+    for (i, line) in status.motd.lines().enumerate() {
+        if i == 0 {
+            outln!("     MOTD: {}", line.trim());
+        } else {
+            outln!("           {}", line.trim());
+        }
+    }
+
+    if status.players.is_empty() {
+        outln!("  Players: {}/{}", status.players_online, status.players_max);
+    } else {
+        outln!("  Players: {}/{} ({})", status.players_online, status.players_max,
+            status.players.join(", "));
+    }
+
+    if !status.latency.is_zero() {
+        outln!("     Ping: {} ms", status.latency.as_millis());
+    }
+
+    Ok(())
+}
+
+pub async fn whitelist_add(server_dir: &Path, name: &str) -> Result<()> {
+    let profile = lookup_player(name).await?;
+
+    if ServerPlayers::new(server_dir).whitelist_add(&profile)? {
+        outln!("Added {} to the whitelist", profile.name);
+    } else {
+        outln!("{} is already whitelisted", profile.name);
+    }
+
+    Ok(())
+}
+
+pub fn whitelist_remove(server_dir: &Path, name: &str) -> Result<()> {
+    if ServerPlayers::new(server_dir).whitelist_remove(name)? {
+        outln!("Removed {name} from the whitelist");
+    } else {
+        outln!("{name} is not whitelisted");
+    }
+
+    Ok(())
+}
+
+pub fn whitelist_list(server_dir: &Path) -> Result<()> {
+    for entry in ServerPlayers::new(server_dir).whitelist()? {
+        outln!("{} {}", entry.uuid, entry.name);
+    }
+
+    Ok(())
+}
+
+pub async fn ops_add(server_dir: &Path, name: &str, level: u8) -> Result<()> {
+    let profile = lookup_player(name).await?;
+
+    ServerPlayers::new(server_dir).op_add(&profile, level)?;
+
+    outln!("Made {} a server operator with level {level}", profile.name);
+
+    Ok(())
+}
+
+pub fn ops_remove(server_dir: &Path, name: &str) -> Result<()> {
+    if ServerPlayers::new(server_dir).op_remove(name)? {
+        outln!("Removed {name} from the server operators");
+    } else {
+        outln!("{name} is not a server operator");
+    }
+
+    Ok(())
+}
+
+async fn lookup_player(name_or_uuid: &str) -> Result<PlayerProfile> {
+    match MojangApiClient::new()?.get_profile(name_or_uuid).await? {
+        Some(profile) => Ok(profile),
+        None => bail!("No player found for '{name_or_uuid}'")
+    }
+}
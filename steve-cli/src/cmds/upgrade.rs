@@ -0,0 +1,129 @@
+/*
+ * Steve Launcher - A Minecraft Launcher
+ * Copyright (C) 2023 Josh Kropf <josh@slashdev.ca>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use anyhow::{bail, Result};
+use std::path::Path;
+
+use crate::ProgressHandler;
+use steve::{AssetClient, Instance, ModLoader};
+use super::{create::{prompt_loader_version, prompt_mc_version}, prompt_confirm};
+
+pub async fn upgrade_mc_version(
+    instance_dir: &Path,
+    mc_version: Option<String>,
+    loader_version: Option<String>,
+    snapshots: bool,
+    skip_confirm: bool
+) -> Result<()> {
+    let mut progress = ProgressHandler::new();
+
+    let mut instance = Instance::load(instance_dir)?;
+
+    let mc_version = match mc_version {
+        Some(v) => v,
+        None => prompt_mc_version(snapshots).await?
+    };
+
+    if mc_version == instance.manifest.mc_version && loader_version.is_none() {
+        outln!("Instance is already Minecraft {mc_version}");
+        return Ok(());
+    }
+
+    let client = AssetClient::new()?;
+
+    let mut has_warnings = false;
+
+    if is_downgrade(&client, &instance.manifest.mc_version, &mc_version).await? {
+        outln!("Minecraft {mc_version} is older than {}, worlds saved by the newer version may not load",
+            instance.manifest.mc_version);
+        has_warnings = true;
+    }
+
+    let mod_loader = match &instance.manifest.mod_loader {
+        Some(current) => {
+            let version = match loader_version {
+                Some(v) => Some(v),
+                None if client.get_loader_versions(&mc_version, &current.name).await?.is_empty() => {
+                    outln!("No {} versions found for Minecraft {mc_version}, the mod loader will be removed",
+                        current.name);
+                    has_warnings = true;
+                    None
+                },
+                None => Some(prompt_loader_version(&mc_version, &current.name).await?)
+            };
+
+            version.map(|version| ModLoader { name: current.name.clone(), version })
+        },
+        None if loader_version.is_some() => {
+            bail!("Instance has no mod loader, use `steve loader switch` to add one");
+        },
+        None => None
+    };
+
+    let (incompatible, unknown) = instance.find_incompatible_mods(&mc_version, &mut progress)
+        .await?;
+
+    if !unknown.is_empty() {
+        outln!("Mods below were not found on CurseForge and could not be checked");
+        print_mods(&instance, &unknown);
+    }
+
+    if !incompatible.is_empty() {
+        outln!("Mods below have no files for Minecraft {mc_version}");
+        print_mods(&instance, &incompatible);
+    }
+
+    has_warnings |= !incompatible.is_empty() || !unknown.is_empty();
+
+    let prompt = format!("Upgrade instance to Minecraft {mc_version}?");
+    if has_warnings && !skip_confirm && !prompt_confirm(prompt, "Pass -y")? {
+        return Ok(());
+    }
+
+    instance.upgrade(&mc_version, mod_loader, &mut progress).await?;
+
+    match &instance.manifest.mod_loader {
+        Some(loader) => outln!("Instance upgraded to Minecraft {mc_version} with {loader}"),
+        None => outln!("Instance upgraded to Minecraft {mc_version}")
+    }
+
+    Ok(())
+}
+
+/// True when `new_version` was released before `current_version`, the
+/// manifest release times are ISO 8601 and compare as strings
+async fn is_downgrade(client: &AssetClient, current_version: &str, new_version: &str) -> Result<bool> {
+    let manifest = client.get_mc_version_manifest().await?;
+
+    let release_time = |id: &str| manifest.versions.iter()
+        .find(|v| v.id == id)
+        .map(|v| v.release_time.clone());
+
+    Ok(match (release_time(current_version), release_time(new_version)) {
+        (Some(current), Some(new)) => new < current,
+        _ => false
+    })
+}
+
+fn print_mods(instance: &Instance, mods: &[impl AsRef<Path>]) {
+    for path in mods {
+        let path = path.as_ref();
+        let path = path.strip_prefix(instance.mods_dir()).unwrap_or(path);
+        outln!("  {}", path.display());
+    }
+}
@@ -0,0 +1,127 @@
+/*
+ * Steve Launcher - A Minecraft Launcher
+ * Copyright (C) 2023 Josh Kropf <josh@slashdev.ca>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use anyhow::{bail, Result};
+use chrono::Local;
+use dialoguer::Select;
+use serde_json::json;
+use std::path::{Path, PathBuf};
+
+use steve::Instance;
+
+pub fn install_datapacks(instance_dir: &Path, files: &[PathBuf], world: Option<&str>) -> Result<()> {
+    let instance = Instance::load(instance_dir)?;
+
+    let world = match world {
+        Some(world) => world.to_string(),
+        None => select_world(&instance)?
+    };
+
+    for file in files {
+        let path = instance.install_datapack(&world, file)?;
+        outln!("Installed {}", path.strip_prefix(&instance.dir).unwrap_or(&path).display());
+    }
+
+    Ok(())
+}
+
+fn select_world(instance: &Instance) -> Result<String> {
+    let worlds = instance.worlds()?;
+
+    if worlds.is_empty() {
+        bail!("No worlds in {}, create a world in the game first", instance.saves_dir().display());
+    }
+
+    super::require_terminal("Select world", "Select a world with --world")?;
+
+    let items: Vec<_> = worlds.iter()
+        .map(|w| format!("{} ({})", w.name, w.dir_name))
+        .collect();
+
+    let selection = Select::with_theme(&super::console_theme())
+        .with_prompt("Select world")
+        .items(&items)
+        .default(0)
+        .interact()?;
+
+    Ok(worlds[selection].dir_name.clone())
+}
+
+pub fn list_worlds(instance_dir: &Path, show_seed: bool) -> Result<()> {
+    let instance = Instance::load(instance_dir)?;
+    let worlds = instance.worlds()?;
+
+    if crate::json_output() {
+        let worlds: Vec<_> = worlds.iter()
+            .map(|w| json!({
+                "dir": w.dir_name,
+                "name": w.name,
+                "version": w.version,
+                "seed": if show_seed { w.seed } else { None },
+                "last_played": w.last_played
+            }))
+            .collect();
+
+        println!("{}", json!(worlds));
+        return Ok(());
+    }
+
+    if worlds.is_empty() {
+        outln!("No worlds in {}", instance.saves_dir().display());
+        return Ok(());
+    }
+
+    let mut rows = vec![["WORLD", "NAME", "VERSION", "LAST PLAYED", "SEED"].map(String::from).to_vec()];
+
+    for world in &worlds {
+        let last_played = world.last_played
+            .map_or(String::from("never"), |t| {
+                t.with_timezone(&Local).format("%Y-%m-%d %H:%M").to_string()
+            });
+
+        rows.push(vec![
+            world.dir_name.clone(),
+            world.name.clone(),
+            world.version.clone().unwrap_or(String::from("-")),
+            last_played,
+            world.seed.map_or(String::from("-"), |s| s.to_string())
+        ]);
+    }
+
+    // seeds are spoilers, only shown when asked for
+    if !show_seed {
+        rows.iter_mut().for_each(|row| { row.pop(); });
+    }
+
+    let mut widths = vec![0; rows[0].len()];
+    for row in &rows {
+        for (width, col) in widths.iter_mut().zip(row) {
+            *width = (*width).max(col.len());
+        }
+    }
+
+    for row in rows {
+        let line: Vec<_> = row.iter().zip(&widths)
+            .map(|(col, width)| format!("{col:width$}"))
+            .collect();
+
+        outln!("{}", line.join("  ").trim_end());
+    }
+
+    Ok(())
+}
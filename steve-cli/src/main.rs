@@ -16,60 +16,367 @@
  * along with this program.  If not, see <https://www.gnu.org/licenses/>.
  */
 
+/// Print line of command output to stdout, as a `message` event in JSON output
+macro_rules! outln {
+    ($($arg:tt)*) => {
+        $crate::print_line(format!($($arg)*))
+    };
+}
+
 mod cli;
 mod cmds;
 
+use clap_complete::{CompleteEnv, env::{Bash, EnvCompleter, Fish, Powershell, Zsh}};
 use indicatif::{ProgressBar, ProgressDrawTarget, ProgressStyle};
-use std::{io, path::{Path, PathBuf}};
+use serde_json::json;
+use std::{io, path::{Path, PathBuf}, sync::atomic::{AtomicBool, Ordering}};
 
 use cmds::{
-    clear_credentials, create_instance, launch_instance, msal_login,
-    modpack_search_and_install, modpack_zip_install, print_account_status
+    CurseForgeSearch, PackSearch, clean_instance, clear_credentials, clone_instance, collect_garbage, config_get, config_set,
+    config_unset, create_instance, delete_instance, install_datapacks, java_install, java_list, java_pin, java_unpin,
+    launch_instance, list_accounts, list_instances, list_registered_instances, list_worlds,
+    loader_switch, loader_upgrade, msal_login, ops_add, pin_account, ops_remove, modpack_mrpack_export,
+    modpack_search_and_install, modpack_zip_export, modpack_zip_install, mods_disable, mods_enable, mods_remove, mods_sync,
+    print_account_status, print_disk_usage, print_instance_info, print_log, print_player_profile,
+    prism_import, prism_zip_export, remove_account, repair_instance, select_registered_instance,
+    server_command, server_status,
+    switch_account, unpin_account, upgrade_mc_version, vanilla_import, vendor_manifests, whitelist_add,
+    whitelist_list, whitelist_remove
 };
-use cli::{AuthCommands, Parser, Cli, Commands};
-use steve::{env, Progress};
+use cli::{
+    AuthCommands, CommandFactory, Parser, Cli, Commands, CompletionShell, ConfigCommands, JavaCommands,
+    LoaderCommands, ModsCommands, OpsCommands, PackFormat, ServerCommands, UpgradeCommands,
+    WhitelistCommands, WorldsCommands
+};
+use steve::{env, Config, Error, InstanceRegistry, LaunchOptions, Progress, QuickPlay};
+
+/// Exit status when files must be downloaded manually without a terminal to
+/// watch for the downloads
+const BLOCKED_DOWNLOADS_EXIT_CODE: i32 = 3;
 
 #[tokio::main(flavor = "current_thread")]
 async fn main() -> anyhow::Result<()> {
+    // answer completion requests from the shell code output by `steve completion`
+    CompleteEnv::with_factory(Cli::command)
+        .complete();
+
     let cli = Cli::parse();
 
     if let Some(dir) = cli.data_dir {
         env::set_data_dir(dir.to_str().unwrap());
     }
 
-    match cli.command {
-        Commands::Create { dir, mc_version, snapshots, loader } => {
+    // config values apply where the environment doesn't already set a value,
+    // a broken config file shouldn't stop `config` commands from fixing it
+    if let Err(e) = Config::load().and_then(|config| config.apply_env()) {
+        eprintln!("Ignoring config.toml: {e:#}");
+    }
+
+    if let Some(jobs) = cli.jobs {
+        env::set_download_jobs(jobs);
+    }
+
+    if !cli.downloads_dirs.is_empty() {
+        env::set_downloads_dirs(&cli.downloads_dirs);
+    }
+
+    JSON_OUTPUT.store(cli.json, Ordering::Relaxed);
+
+    let result = run(cli.command).await;
+
+    if let Err(e) = &result {
+        let code = match e.downcast_ref() {
+            Some(Error::BlockedDownloads(_)) => BLOCKED_DOWNLOADS_EXIT_CODE,
+            _ => 1
+        };
+
+        if json_output() {
+            print_json_event(json!({ "event": "error", "message": format!("{e:#}") }));
+            std::process::exit(code);
+        } else if code != 1 {
+            eprintln!("Error: {e:#}");
+            std::process::exit(code);
+        }
+    }
+
+    result
+}
+
+async fn run(command: Commands) -> anyhow::Result<()> {
+    match command {
+        Commands::Create { dir, mc_version, snapshots, loader, name } => {
             let instance_dir = absolute_path(&dir)?;
 
-            create_instance(&instance_dir, mc_version, snapshots, loader).await
+            create_instance(&instance_dir, mc_version, snapshots, loader, name).await
         },
-        Commands::Launch { dir, detach } => {
-            let instance_dir = absolute_path(&dir)?;
+        Commands::Launch { dir, detach, no_update, offline_user, demo, server, world } => {
+            let instance_dir = instance_path(&dir)?;
+
+            let quick_play = server.map(QuickPlay::Multiplayer)
+                .or(world.map(QuickPlay::Singleplayer));
+
+            let options = LaunchOptions { detach, no_update, demo, quick_play };
+
+            launch_instance(&instance_dir, offline_user.as_deref(), options).await
+        },
+        Commands::Clone { dir, dest } => {
+            let instance_dir = instance_path(&dir)?;
+            let dest_dir = absolute_path(&dest)?;
+
+            clone_instance(&instance_dir, &dest_dir)
+        },
+        Commands::Delete { dir, yes } => {
+            let instance_dir = instance_path(&dir)?;
+
+            delete_instance(&instance_dir, yes)
+        },
+        Commands::Info { dir } => {
+            let instance_dir = instance_path(&dir)?;
+
+            print_instance_info(&instance_dir).await
+        },
+        Commands::Logs { dir, follow } => {
+            let instance_dir = instance_path(&dir)?;
+
+            print_log(&instance_dir, follow)
+        },
+        Commands::List { dir, all } => {
+            if all {
+                list_registered_instances()
+            } else {
+                let root_dir = absolute_path(&dir.unwrap_or_else(env::get_instances_dir))?;
 
-            launch_instance(&instance_dir, detach).await
+                list_instances(&root_dir)
+            }
         },
         Commands::Auth { command } => {
             if let Some(command) = command {
                 match command {
                     AuthCommands::Status => print_account_status(),
+                    AuthCommands::List => list_accounts(),
+                    AuthCommands::Switch { name } => switch_account(&name),
+                    AuthCommands::Remove { name } => remove_account(&name),
+                    AuthCommands::Pin { dir, name } => {
+                        let instance_dir = instance_path(&dir)?;
+
+                        pin_account(&instance_dir, &name)
+                    },
+                    AuthCommands::Unpin { dir } => {
+                        let instance_dir = instance_path(&dir)?;
+
+                        unpin_account(&instance_dir)
+                    },
                     AuthCommands::Clear => clear_credentials()
                 }
             } else {
                 msal_login().await
             }
         },
-        Commands::Import { dir, zip_file } => {
+        Commands::Import { dir, zip_file, prism, vanilla, dry_run, config_conflicts, blocked_out, yes } => {
             let instance_dir = absolute_path(&dir)?;
 
-            modpack_zip_install(&instance_dir, &zip_file).await
+            if let Some(config_conflicts) = config_conflicts {
+                env::set_config_conflicts(&config_conflicts);
+            }
+
+            match zip_file {
+                Some(zip_file) => {
+                    modpack_zip_install(&instance_dir, &zip_file, dry_run, blocked_out.as_deref(), yes).await
+                },
+                None if prism => prism_import(&instance_dir, yes).await,
+                None if vanilla => vanilla_import(&instance_dir).await,
+                None => unreachable!("zip_file required unless --prism or --vanilla")
+            }
+        },
+        Commands::Export { dir, zip_file, format, name, pack_version, author } => {
+            let instance_dir = instance_path(&dir)?;
+
+            match format {
+                PackFormat::Curseforge => {
+                    modpack_zip_export(&instance_dir, &zip_file, name, &pack_version, author).await
+                },
+                PackFormat::Modrinth => {
+                    modpack_mrpack_export(&instance_dir, &zip_file, name, &pack_version).await
+                },
+                PackFormat::Prism => {
+                    prism_zip_export(&instance_dir, &zip_file, name)
+                }
+            }
         },
-        Commands::Modpack { dir, search, search_limit } => {
+        Commands::Modpack {
+            dir, search, search_limit, curseforge, sort, category, dry_run, config_conflicts, blocked_out, yes
+        } => {
             let instance_dir = absolute_path(&dir)?;
 
-            modpack_search_and_install(&instance_dir, &search, search_limit).await
+            if let Some(config_conflicts) = config_conflicts {
+                env::set_config_conflicts(&config_conflicts);
+            }
+
+            let search = PackSearch {
+                term: search,
+                limit: search_limit as usize,
+                curseforge: match curseforge || env::get_prefer_curseforge() {
+                    true => Some(CurseForgeSearch {
+                        sort: sort.unwrap_or_default(),
+                        category
+                    }),
+                    false => None
+                }
+            };
+
+            modpack_search_and_install(&instance_dir, &search, dry_run, blocked_out.as_deref(), yes)
+                .await
+        },
+        Commands::Clean { dir, dry_run } => {
+            let instance_dir = instance_path(&dir)?;
+
+            clean_instance(&instance_dir, dry_run)
+        },
+        Commands::Repair { dir } => {
+            let instance_dir = instance_path(&dir)?;
+
+            repair_instance(&instance_dir).await
+        },
+        Commands::Du { dir } => {
+            let root_dir = dir.map(|d| absolute_path(&d)).transpose()?;
+
+            print_disk_usage(root_dir.as_deref())
+        },
+        Commands::Gc { dry_run, forge_cache } => {
+            collect_garbage(dry_run, forge_cache).await
+        },
+        Commands::Worlds { command } => {
+            match command {
+                WorldsCommands::List { dir, seed } => {
+                    let instance_dir = instance_path(&dir)?;
+
+                    list_worlds(&instance_dir, seed)
+                },
+                WorldsCommands::Datapack { dir, files, world } => {
+                    let instance_dir = instance_path(&dir)?;
+
+                    install_datapacks(&instance_dir, &files, world.as_deref())
+                }
+            }
+        },
+        Commands::Java { command } => {
+            match command {
+                JavaCommands::List => java_list(),
+                JavaCommands::Install { version } => java_install(version).await,
+                JavaCommands::Pin { dir, version } => {
+                    let instance_dir = instance_path(&dir)?;
+
+                    java_pin(&instance_dir, version).await
+                },
+                JavaCommands::Unpin { dir } => {
+                    let instance_dir = instance_path(&dir)?;
+
+                    java_unpin(&instance_dir)
+                }
+            }
         },
-        Commands::Completion => {
-            Ok(print!("{}", include_str!("../steve-completion.bash")))
+        Commands::Loader { command } => {
+            match command {
+                LoaderCommands::Upgrade { dir, version } => {
+                    let instance_dir = instance_path(&dir)?;
+
+                    loader_upgrade(&instance_dir, version).await
+                },
+                LoaderCommands::Switch { dir, loader } => {
+                    let instance_dir = instance_path(&dir)?;
+
+                    loader_switch(&instance_dir, &loader).await
+                }
+            }
+        },
+        Commands::Mods { command } => {
+            match command {
+                ModsCommands::Remove { dir, name, yes } => {
+                    let instance_dir = instance_path(&dir)?;
+
+                    mods_remove(&instance_dir, &name, yes).await
+                },
+                ModsCommands::Disable { dir, name } => {
+                    let instance_dir = instance_path(&dir)?;
+
+                    mods_disable(&instance_dir, &name)
+                },
+                ModsCommands::Enable { dir, name } => {
+                    let instance_dir = instance_path(&dir)?;
+
+                    mods_enable(&instance_dir, &name)
+                },
+                ModsCommands::Sync { dir, blocked_out } => {
+                    let instance_dir = instance_path(&dir)?;
+
+                    mods_sync(&instance_dir, blocked_out.as_deref()).await
+                }
+            }
+        },
+        Commands::Profile { name } => {
+            print_player_profile(&name).await
+        },
+        Commands::Server { command } => {
+            match command {
+                ServerCommands::Cmd { dir, command } => {
+                    let server_dir = absolute_path(&dir)?;
+
+                    server_command(&server_dir, &command)
+                },
+                ServerCommands::Status { target } => {
+                    server_status(target.as_deref().unwrap_or("."))
+                },
+                ServerCommands::Whitelist { command } => {
+                    match command {
+                        WhitelistCommands::Add { dir, name } =>
+                            whitelist_add(&absolute_path(&dir)?, &name).await,
+                        WhitelistCommands::Remove { dir, name } =>
+                            whitelist_remove(&absolute_path(&dir)?, &name),
+                        WhitelistCommands::List { dir } =>
+                            whitelist_list(&absolute_path(&dir)?)
+                    }
+                },
+                ServerCommands::Ops { command } => {
+                    match command {
+                        OpsCommands::Add { dir, name, level } =>
+                            ops_add(&absolute_path(&dir)?, &name, level).await,
+                        OpsCommands::Remove { dir, name } =>
+                            ops_remove(&absolute_path(&dir)?, &name)
+                    }
+                }
+            }
+        },
+        Commands::Upgrade { command } => {
+            match command {
+                UpgradeCommands::McVersion { dir, mc_version, loader_version, snapshots, yes } => {
+                    let instance_dir = instance_path(&dir)?;
+
+                    upgrade_mc_version(&instance_dir, mc_version, loader_version, snapshots, yes).await
+                }
+            }
+        },
+        Commands::Vendor { dir } => {
+            let instance_dir = instance_path(&dir)?;
+
+            vendor_manifests(&instance_dir).await
+        },
+        Commands::Config { command } => {
+            match command {
+                ConfigCommands::Get { key } => config_get(key.as_deref()),
+                ConfigCommands::Set { key, value } => config_set(&key, &value),
+                ConfigCommands::Unset { key } => config_unset(&key)
+            }
+        },
+        Commands::Completion { shell } => {
+            let completer: &dyn EnvCompleter = match shell {
+                CompletionShell::Bash => &Bash,
+                CompletionShell::Zsh => &Zsh,
+                CompletionShell::Fish => &Fish,
+                CompletionShell::Powershell => &Powershell
+            };
+
+            completer.write_registration("COMPLETE", "steve", "steve", "steve", &mut io::stdout())?;
+            Ok(())
         }
     }
 }
@@ -82,31 +389,121 @@ fn absolute_path(path: &Path) -> io::Result<PathBuf> {
     })
 }
 
+/// Absolute path of instance directory, or directory of the registered
+/// instance named `path` when no such directory exists, prompting to select
+/// from the instances with similar names when there is no exact match
+fn instance_path(path: &Path) -> anyhow::Result<PathBuf> {
+    if path.exists() {
+        return Ok(absolute_path(path)?);
+    }
+
+    let registry = InstanceRegistry::load()?;
+
+    match select_registered_instance(&registry, &path.to_string_lossy())? {
+        Some(dir) => Ok(dir),
+        None => Ok(absolute_path(path)?)
+    }
+}
+
+/// Set by the `--json` flag
+static JSON_OUTPUT: AtomicBool = AtomicBool::new(false);
+
+fn json_output() -> bool {
+    JSON_OUTPUT.load(Ordering::Relaxed)
+}
+
+/// Print event as a single line of JSON to stdout
+fn print_json_event(event: serde_json::Value) {
+    println!("{event}");
+}
+
+/// Print line of command output, use `outln!` rather than calling directly
+fn print_line(line: String) {
+    if json_output() {
+        print_json_event(json!({ "event": "message", "message": line }));
+    } else {
+        println!("{line}");
+    }
+}
+
 struct ProgressHandler {
-    progress: ProgressBar
+    progress: ProgressBar,
+    /// Message, total and last reported percent of the task, in JSON output
+    json_task: Option<(&'static str, u64, u64)>
 }
 
 impl ProgressHandler {
     fn new() -> Self {
+        let draw_target = match json_output() {
+            true => ProgressDrawTarget::hidden(),
+            false => ProgressDrawTarget::stdout()
+        };
+
         ProgressHandler {
-            progress: ProgressBar::with_draw_target(None, ProgressDrawTarget::stdout())
-                .with_style(ProgressStyle::with_template("{bar:40.cyan/blue} {msg} {pos}/{len}").unwrap())
+            progress: ProgressBar::with_draw_target(None, draw_target),
+            json_task: None
+        }
+    }
+
+    fn begin_json(&mut self, message: &'static str, total: Option<u64>, unit: &str) {
+        if json_output() {
+            print_json_event(json!({ "event": "begin", "message": message, "total": total, "unit": unit }));
+            self.json_task = Some((message, total.unwrap_or(0), 0));
         }
     }
+
+    fn begin_style(&mut self, message: &'static str, total: Option<u64>, template: &str) {
+        self.progress.disable_steady_tick();
+        self.progress.set_style(ProgressStyle::with_template(template).unwrap());
+        self.progress.set_length(total.unwrap_or(0));
+        self.progress.set_message(message);
+        self.progress.reset();
+    }
 }
 
 impl Progress for ProgressHandler {
     fn advance(&mut self, current: usize) {
         self.progress.set_position(current as u64);
+
+        // report each percent, rather than every file of large downloads
+        if let Some((message, total, reported)) = &mut self.json_task {
+            let percent = (current as u64 * 100).checked_div(*total).unwrap_or(100);
+
+            if percent > *reported {
+                *reported = percent;
+                print_json_event(json!({
+                    "event": "progress", "message": message, "current": current, "total": total
+                }));
+            }
+        }
     }
 
     fn begin(&mut self, message: &'static str, total: usize) {
-        self.progress.set_length(total as u64);
-        self.progress.set_message(message);
-        self.progress.reset();
+        self.begin_style(message, Some(total as u64), "{bar:40.cyan/blue} {msg} {pos}/{len}");
+        self.begin_json(message, Some(total as u64), "items");
+    }
+
+    fn begin_bytes(&mut self, message: &'static str, total: u64) {
+        self.begin_style(message, Some(total),
+            "{bar:40.cyan/blue} {msg} {bytes}/{total_bytes} {binary_bytes_per_sec}");
+        self.begin_json(message, Some(total), "bytes");
+    }
+
+    fn begin_spinner(&mut self, message: &'static str) {
+        self.begin_style(message, None, "{spinner:.cyan} {msg}");
+        self.begin_json(message, None, "none");
+
+        if !json_output() {
+            self.progress.enable_steady_tick(std::time::Duration::from_millis(100));
+        }
     }
 
     fn end(&mut self) {
+        self.progress.disable_steady_tick();
         self.progress.finish_and_clear();
+
+        if let Some((message, _, _)) = self.json_task.take() {
+            print_json_event(json!({ "event": "end", "message": message }));
+        }
     }
 }